@@ -98,26 +98,12 @@ impl<S: Storage> Binder<S> {
                     index,
                     ty: return_type.clone(),
                 };
-                match std::mem::replace(expr, input_ref) {
-                    ScalarExpression::AggCall {
-                        kind,
-                        args,
-                        ty,
-                        distinct
-                    } => {
-                        self.context.agg_calls.push(ScalarExpression::AggCall {
-                            distinct,
-                            kind,
-                            args,
-                            ty,
-                        });
-                    }
-                    _ => unreachable!(),
-                }
+                let agg_call = std::mem::replace(expr, input_ref);
+                self.context.agg_calls.push(agg_call);
             }
 
             ScalarExpression::TypeCast { expr, .. } => self.visit_column_agg_expr(expr),
-            ScalarExpression::IsNull { expr } => self.visit_column_agg_expr(expr),
+            ScalarExpression::IsNull { expr, .. } => self.visit_column_agg_expr(expr),
             ScalarExpression::Unary { expr, .. } => self.visit_column_agg_expr(expr),
             ScalarExpression::Alias { expr, .. } => self.visit_column_agg_expr(expr),
             ScalarExpression::Binary {
@@ -128,6 +114,11 @@ impl<S: Storage> Binder<S> {
                 self.visit_column_agg_expr(left_expr);
                 self.visit_column_agg_expr(right_expr);
             }
+            ScalarExpression::Coalesce { exprs, .. } => {
+                for expr in exprs {
+                    self.visit_column_agg_expr(expr);
+                }
+            }
             ScalarExpression::Constant(_)
             | ScalarExpression::ColumnRef { .. }
             | ScalarExpression::InputRef { .. } => {}
@@ -281,7 +272,7 @@ impl<S: Storage> Binder<S> {
             }
 
             ScalarExpression::TypeCast { expr, .. } => self.validate_having_orderby(expr),
-            ScalarExpression::IsNull { expr } => self.validate_having_orderby(expr),
+            ScalarExpression::IsNull { expr, .. } => self.validate_having_orderby(expr),
             ScalarExpression::Unary { expr, .. } => self.validate_having_orderby(expr),
             ScalarExpression::Binary {
                 left_expr,
@@ -292,6 +283,12 @@ impl<S: Storage> Binder<S> {
                 self.validate_having_orderby(right_expr)?;
                 Ok(())
             }
+            ScalarExpression::Coalesce { exprs, .. } => {
+                for expr in exprs {
+                    self.validate_having_orderby(expr)?;
+                }
+                Ok(())
+            }
 
             ScalarExpression::Constant(_) | ScalarExpression::InputRef { .. } => Ok(()),
         }
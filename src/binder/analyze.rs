@@ -0,0 +1,44 @@
+use std::sync::Arc;
+use itertools::Itertools;
+use sqlparser::ast::{Ident, ObjectName};
+use crate::binder::{Binder, BindError, lower_case_name, split_name};
+use crate::planner::LogicalPlan;
+use crate::planner::operator::analyze::AnalyzeOperator;
+use crate::planner::operator::scan::ScanOperator;
+use crate::storage::Storage;
+
+impl<S: Storage> Binder<S> {
+    /// `ANALYZE TABLE t [FOR COLUMNS c1, c2]`, all the columns are analyzed by default.
+    pub(crate) async fn bind_analyze(
+        &mut self,
+        name: &ObjectName,
+        columns: &[Ident],
+    ) -> Result<LogicalPlan, BindError> {
+        let name = lower_case_name(name);
+        let (_, name) = split_name(&name)?;
+        let table_name = Arc::new(name.to_string());
+
+        let table_catalog = self.context.storage
+            .table_catalog(&table_name)
+            .await
+            .ok_or_else(|| BindError::InvalidTable(format!("bind table {}", table_name)))?;
+        let columns = if columns.is_empty() {
+            table_catalog.all_columns()
+        } else {
+            columns
+                .iter()
+                .map(|ident| {
+                    let column_name = ident.value.to_lowercase();
+
+                    table_catalog
+                        .get_column_by_name(&column_name)
+                        .cloned()
+                        .ok_or(BindError::InvalidColumn(column_name))
+                })
+                .try_collect()?
+        };
+        let scan = ScanOperator::new(table_name.clone(), table_catalog);
+
+        Ok(AnalyzeOperator::new(table_name, columns, scan))
+    }
+}
@@ -14,7 +14,7 @@ impl<S: Storage> Binder<S> {
         if let TableFactor::Table { name, .. } = &from.relation {
             let name = lower_case_name(name);
             let (_, name) = split_name(&name)?;
-            let (table_name, mut plan) = self._bind_single_table_ref(None, name).await?;
+            let (table_name, mut plan) = self._bind_single_table_ref(None, name, None).await?;
 
             if let Some(predicate) = selection {
                 plan = self.bind_where(plan, predicate).await?;
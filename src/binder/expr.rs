@@ -1,6 +1,6 @@
 use crate::binder::BindError;
 use itertools::Itertools;
-use sqlparser::ast::{BinaryOperator, Expr, Function, FunctionArg, FunctionArgExpr, Ident, UnaryOperator};
+use sqlparser::ast::{BinaryOperator, Expr, Function, FunctionArg, FunctionArgExpr, Ident, OrderByExpr, UnaryOperator, Value};
 use std::slice;
 use std::sync::Arc;
 use async_recursion::async_recursion;
@@ -8,8 +8,10 @@ use crate::expression::agg::AggKind;
 
 use super::Binder;
 use crate::expression::ScalarExpression;
+use crate::expression::value_compute::binary_result_type;
+use crate::planner::operator::sort::SortField;
 use crate::storage::Storage;
-use crate::types::LogicalType;
+use crate::types::{decimal, LogicalType};
 use crate::types::value::DataValue;
 
 impl<S: Storage> Binder<S> {
@@ -29,6 +31,34 @@ impl<S: Storage> Binder<S> {
             Expr::Function(func) => self.bind_agg_call(func).await,
             Expr::Nested(expr) => self.bind_expr(expr).await,
             Expr::UnaryOp { expr, op } => self.bind_unary_op_internal(expr, op).await,
+            Expr::Cast { expr, data_type } => {
+                let ty = LogicalType::try_from(data_type.clone())?;
+                let expr = match (ty, bind_decimal_literal(expr)) {
+                    (LogicalType::Decimal(..), Some(literal)) => literal,
+                    _ => self.bind_expr(expr).await?,
+                };
+
+                Ok(ScalarExpression::TypeCast {
+                    expr: Box::new(expr),
+                    ty,
+                    is_try: false,
+                })
+            }
+            Expr::AggregateExpressionWithFilter { expr, filter } => {
+                self.bind_agg_filter(expr, filter).await
+            }
+            Expr::IsNull(expr) | Expr::IsUnknown(expr) => self.bind_is_null(expr, false).await,
+            Expr::IsNotNull(expr) | Expr::IsNotUnknown(expr) => self.bind_is_null(expr, true).await,
+            Expr::IsTrue(expr) => self.bind_is_distinct_from(expr, &Expr::Value(Value::Boolean(true)), false).await,
+            Expr::IsNotTrue(expr) => self.bind_is_distinct_from(expr, &Expr::Value(Value::Boolean(true)), true).await,
+            Expr::IsFalse(expr) => self.bind_is_distinct_from(expr, &Expr::Value(Value::Boolean(false)), false).await,
+            Expr::IsNotFalse(expr) => self.bind_is_distinct_from(expr, &Expr::Value(Value::Boolean(false)), true).await,
+            Expr::IsDistinctFrom(left, right) => self.bind_is_distinct_from(left, right, true).await,
+            Expr::IsNotDistinctFrom(left, right) => self.bind_is_distinct_from(left, right, false).await,
+            // only the conjunctions of WHERE are rewritten into semi or anti joins by `bind_where`
+            Expr::InSubquery { .. } | Expr::Exists { .. } | Expr::Subquery(_) => Err(BindError::UnsupportedStmt(
+                "subquery under OR or outside of the conjunctions of WHERE not supported".to_string()
+            )),
             _ => {
                 todo!()
             }
@@ -61,36 +91,56 @@ impl<S: Storage> Binder<S> {
         };
 
         if let Some(table) = table_name.or(bind_table_name) {
-            let table_catalog = self
-                .context
-                .storage
-                .table_catalog(table)
-                .await
-                .ok_or_else(|| BindError::InvalidTable(table.to_string()))?;
-
-            let column_catalog = table_catalog
-                .get_column_by_name(column_name)
-                .ok_or_else(|| BindError::InvalidColumn(column_name.to_string()))?;
-            Ok(ScalarExpression::ColumnRef(column_catalog.clone()))
+            let column_catalog = if let Some((table_catalog, _)) = self.context.bind_table.get(table) {
+                table_catalog.get_column_by_name(column_name).cloned()
+            } else if let Some(table_catalog) = self.context.outer_bind_table.get(table) {
+                let column_catalog = table_catalog.get_column_by_name(column_name).cloned();
+
+                if let Some(column) = &column_catalog {
+                    self.context.add_correlated_column(column.clone());
+                }
+                column_catalog
+            } else {
+                self.context
+                    .storage
+                    .table_catalog(table)
+                    .await
+                    .ok_or_else(|| BindError::InvalidTable(table.to_string()))?
+                    .get_column_by_name(column_name)
+                    .cloned()
+            };
+            let column_catalog =
+                column_catalog.ok_or_else(|| BindError::InvalidColumn(column_name.to_string()))?;
+            Ok(ScalarExpression::ColumnRef(column_catalog))
         } else {
             // handle col syntax
-            let mut got_column = None;
-            for (_, (table_catalog, _)) in &self.context.bind_table {
-                if let Some(column_catalog) = table_catalog.get_column_by_name(column_name) {
-                    if got_column.is_some() {
-                        return Err(BindError::InvalidColumn(column_name.to_string()).into());
+            let got_columns = self.context.bind_table
+                .values()
+                .filter_map(|(table_catalog, _)| table_catalog.get_column_by_name(column_name))
+                .cloned()
+                .collect_vec();
+
+            match got_columns.as_slice() {
+                [] => {
+                    if let Some(expr) = self.context.aliases.get(column_name) {
+                        return Ok(expr.clone());
                     }
-                    got_column = Some(column_catalog);
-                }
-            }
-            if got_column.is_none() {
-                if let Some(expr) = self.context.aliases.get(column_name) {
-                    return Ok(expr.clone());
+                    // the columns of the left side, referenced in a `LATERAL` subquery
+                    let outer_column = self.context.outer_bind_table
+                        .values()
+                        .find_map(|table_catalog| table_catalog.get_column_by_name(column_name))
+                        .cloned()
+                        .ok_or_else(|| BindError::InvalidColumn(column_name.to_string()))?;
+                    self.context.add_correlated_column(outer_column.clone());
+
+                    Ok(ScalarExpression::ColumnRef(outer_column))
                 }
+                [column_catalog] => Ok(ScalarExpression::ColumnRef(column_catalog.clone())),
+                // the column of `JOIN ... USING` exists in both sides
+                _ => self.context
+                    .using_column_expr(&got_columns)
+                    .ok_or_else(|| BindError::AmbiguousColumn(column_name.to_string())),
             }
-            let column_catalog =
-                got_column.ok_or_else(|| BindError::InvalidColumn(column_name.to_string()))?;
-            Ok(ScalarExpression::ColumnRef(column_catalog.clone()))
         }
     }
 
@@ -100,20 +150,32 @@ impl<S: Storage> Binder<S> {
         right: &Expr,
         op: &BinaryOperator,
     ) -> Result<ScalarExpression, BindError> {
-        let left_expr = Box::new(self.bind_expr(left).await?);
-        let right_expr = Box::new(self.bind_expr(right).await?);
+        let mut left_expr = Box::new(self.bind_expr(left).await?);
+        let mut right_expr = Box::new(self.bind_expr(right).await?);
+
+        // the literals with a decimal point are floats, unless computed with a decimal
+        if let LogicalType::Decimal(..) = left_expr.return_type() {
+            if let Some(literal) = bind_decimal_literal(right) {
+                right_expr = Box::new(literal);
+            }
+        } else if let LogicalType::Decimal(..) = right_expr.return_type() {
+            if let Some(literal) = bind_decimal_literal(left) {
+                left_expr = Box::new(literal);
+            }
+        }
 
         let ty = match op {
             BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Multiply |
             BinaryOperator::Divide | BinaryOperator::Modulo => {
-                LogicalType::max_logical_type(
+                binary_result_type(
                     &left_expr.return_type(),
-                    &right_expr.return_type()
+                    &right_expr.return_type(),
+                    &(op.clone()).into()
                 )?
             }
             BinaryOperator::Gt | BinaryOperator::Lt | BinaryOperator::GtEq |
             BinaryOperator::LtEq | BinaryOperator::Eq | BinaryOperator::NotEq |
-            BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor => {
+            BinaryOperator::Spaceship | BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Xor => {
                 LogicalType::Boolean
             },
             _ => todo!()
@@ -127,6 +189,34 @@ impl<S: Storage> Binder<S> {
         })
     }
 
+    async fn bind_is_null(&mut self, expr: &Expr, negated: bool) -> Result<ScalarExpression, BindError> {
+        Ok(ScalarExpression::IsNull {
+            negated,
+            expr: Box::new(self.bind_expr(expr).await?),
+        })
+    }
+
+    /// `left IS [NOT] DISTINCT FROM right`, the comparison which treats NULL as a value, also
+    /// `IS [NOT] TRUE` and `IS [NOT] FALSE` as the comparisons with the boolean.
+    async fn bind_is_distinct_from(&mut self, left: &Expr, right: &Expr, distinct: bool) -> Result<ScalarExpression, BindError> {
+        let expr = ScalarExpression::Binary {
+            op: BinaryOperator::Spaceship.into(),
+            left_expr: Box::new(self.bind_expr(left).await?),
+            right_expr: Box::new(self.bind_expr(right).await?),
+            ty: LogicalType::Boolean,
+        };
+
+        Ok(if distinct {
+            ScalarExpression::Unary {
+                op: UnaryOperator::Not.into(),
+                expr: Box::new(expr),
+                ty: LogicalType::Boolean,
+            }
+        } else {
+            expr
+        })
+    }
+
     async fn bind_unary_op_internal(
         &mut self,
         expr: &Expr,
@@ -161,43 +251,89 @@ impl<S: Storage> Binder<S> {
             }
         }
         let ty = args[0].return_type();
+        let order_by = self.bind_agg_order_by(&func.order_by).await?;
 
-        Ok(match func.name.to_string().to_lowercase().as_str() {
-            "count" => ScalarExpression::AggCall{
-                distinct: func.distinct,
-                kind: AggKind::Count,
-                args,
-                ty: LogicalType::UInteger,
-            },
-            "sum" => ScalarExpression::AggCall{
-                distinct: func.distinct,
-                kind: AggKind::Sum,
-                args,
-                ty,
-            },
-            "min" => ScalarExpression::AggCall{
-                distinct: func.distinct,
-                kind: AggKind::Min,
-                args,
-                ty,
-            },
-            "max" => ScalarExpression::AggCall{
-                distinct: func.distinct,
-                kind: AggKind::Max,
-                args,
-                ty,
-            },
-            "avg" => ScalarExpression::AggCall{
-                distinct: func.distinct,
-                kind: AggKind::Avg,
-                args,
-                ty,
-            },
+        let kind = match func.name.to_string().to_lowercase().as_str() {
+            "count" => AggKind::Count,
+            "sum" => AggKind::Sum,
+            "min" => AggKind::Min,
+            "max" => AggKind::Max,
+            "avg" => AggKind::Avg,
+            "string_agg" => {
+                if args.len() != 2 {
+                    return Err(BindError::InvalidColumn(
+                        "string_agg requires a value and a delimiter".to_string()
+                    ));
+                }
+                if !matches!(args[1], ScalarExpression::Constant(_)) {
+                    return Err(BindError::AggMiss(
+                        "string_agg delimiter must be a constant".to_string()
+                    ));
+                }
+                AggKind::StringAgg
+            }
             _ => todo!(),
+        };
+        let ty = kind.return_type(ty);
+
+        Ok(ScalarExpression::AggCall {
+            distinct: func.distinct,
+            kind,
+            args,
+            ty,
+            filter: None,
+            order_by,
         })
     }
 
+    async fn bind_agg_order_by(&mut self, order_by: &[OrderByExpr]) -> Result<Vec<SortField>, BindError> {
+        let mut sort_fields = Vec::with_capacity(order_by.len());
+
+        for OrderByExpr { expr, asc, nulls_first } in order_by {
+            sort_fields.push(SortField::new(
+                self.bind_expr(expr).await?,
+                asc.map_or(false, |asc| !asc),
+                nulls_first.map_or(false, |first| first),
+            ));
+        }
+
+        Ok(sort_fields)
+    }
+
+    /// e.g. `count(*) FILTER (WHERE status = 'ok')`
+    async fn bind_agg_filter(&mut self, expr: &Expr, filter: &Expr) -> Result<ScalarExpression, BindError> {
+        let mut agg_call = self.bind_expr(expr).await?;
+        let predicate = self.bind_expr(filter).await?;
+
+        if let ScalarExpression::AggCall { filter, .. } = &mut agg_call {
+            *filter = Some(Box::new(predicate));
+
+            Ok(agg_call)
+        } else {
+            Err(BindError::AggMiss(
+                format!("FILTER specified, but {:?} is not an aggregate function", expr)
+            ))
+        }
+    }
+
     fn wildcard_expr() -> ScalarExpression {
         ScalarExpression::Constant(Arc::new(DataValue::Utf8(Some("*".to_string()))))
     }
 }
+
+/// Binds a number literal with a decimal point, e.g. `19.99` or `-0.5`, to an exact decimal
+/// instead of a float, for the decimal typed targets of the literal.
+pub(crate) fn bind_decimal_literal(expr: &Expr) -> Option<ScalarExpression> {
+    let literal = match expr {
+        Expr::Value(Value::Number(n, _)) => n.clone(),
+        Expr::Nested(expr) => return bind_decimal_literal(expr),
+        Expr::UnaryOp { op: UnaryOperator::Minus, expr } => match expr.as_ref() {
+            Expr::Value(Value::Number(n, _)) => format!("-{}", n),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let (value, precision, scale) = decimal::parse_literal(&literal)?;
+
+    Some(ScalarExpression::Constant(Arc::new(DataValue::Decimal(Some(value), precision, scale))))
+}
@@ -2,14 +2,16 @@ use std::slice;
 use std::sync::Arc;
 use sqlparser::ast::{Expr, Ident, ObjectName};
 use crate::binder::{Binder, BindError, lower_case_name, split_name};
+use crate::binder::expr::bind_decimal_literal;
 use crate::catalog::ColumnRef;
 use crate::expression::ScalarExpression;
-use crate::expression::value_compute::unary_op;
 use crate::planner::LogicalPlan;
 use crate::planner::operator::insert::InsertOperator;
 use crate::planner::operator::Operator;
 use crate::planner::operator::values::ValuesOperator;
 use crate::storage::Storage;
+use crate::types::LogicalType;
+use crate::types::tuple::Tuple;
 use crate::types::value::{DataValue, ValueRef};
 
 impl<S: Storage> Binder<S> {
@@ -47,22 +49,17 @@ impl<S: Storage> Binder<S> {
                 let mut row = Vec::with_capacity(expr_row.len());
 
                 for (i, expr) in expr_row.into_iter().enumerate() {
-                    match &self.bind_expr(expr).await? {
-                        ScalarExpression::Constant(value) => {
-                            let cast_value = DataValue::clone(value)
-                                .cast(columns[i].datatype())?;
+                    let expr = match (columns[i].datatype(), bind_decimal_literal(expr)) {
+                        (LogicalType::Decimal(..), Some(literal)) => literal,
+                        _ => self.bind_expr(expr).await?,
+                    };
 
-                            row.push(Arc::new(cast_value))
-                        },
-                        ScalarExpression::Unary { expr, op, .. } => {
-                            if let ScalarExpression::Constant(value) = expr.as_ref() {
-                                row.push(Arc::new(unary_op(value, op)?))
-                            } else {
-                                unreachable!()
-                            }
-                        }
-                        _ => unreachable!(),
+                    if !is_constant(&expr) {
+                        return Err(BindError::UnsupportedStmt(format!("{} in VALUES", expr)));
                     }
+                    let value = expr.eval_column(&Tuple { id: None, columns: vec![], values: vec![] })?;
+
+                    row.push(Arc::new(DataValue::clone(&value).cast(columns[i].datatype())?));
                 }
 
                 rows.push(row);
@@ -96,4 +93,14 @@ impl<S: Storage> Binder<S> {
             childrens: vec![],
         }
     }
-}
\ No newline at end of file
+}
+
+/// Whether the expression is folded into a value of `VALUES`, e.g. `-1` or
+/// `CAST('1.5' AS DECIMAL(4, 1))`.
+fn is_constant(expr: &ScalarExpression) -> bool {
+    match expr {
+        ScalarExpression::Constant(_) => true,
+        ScalarExpression::Unary { expr, .. } | ScalarExpression::TypeCast { expr, .. } => is_constant(expr),
+        _ => false,
+    }
+}
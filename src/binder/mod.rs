@@ -1,4 +1,5 @@
 pub mod aggregate;
+mod analyze;
 mod create_table;
 pub mod expr;
 mod select;
@@ -8,13 +9,16 @@ mod delete;
 mod drop_table;
 mod truncate;
 mod distinct;
+mod subquery;
 
 use std::collections::BTreeMap;
-use sqlparser::ast::{Ident, ObjectName, ObjectType, SetExpr, Statement};
+use itertools::Itertools;
+use sqlparser::ast::{Ident, ObjectName, ObjectType, SetExpr, Statement, TableFactor, TableWithJoins};
 
-use crate::catalog::{DEFAULT_SCHEMA_NAME, CatalogError, TableName, TableCatalog};
+use crate::catalog::{DEFAULT_SCHEMA_NAME, CatalogError, TableName, TableCatalog, ColumnRef, STATISTICS_TABLE_NAME};
 use crate::expression::ScalarExpression;
 use crate::planner::LogicalPlan;
+use crate::planner::operator::explain::ExplainOperator;
 use crate::planner::operator::join::JoinType;
 use crate::storage::Storage;
 use crate::types::errors::TypeError;
@@ -31,6 +35,12 @@ pub struct BinderContext<S: Storage> {
     aliases: BTreeMap<String, ScalarExpression>,
     group_by_exprs: Vec<ScalarExpression>,
     pub(crate) agg_calls: Vec<ScalarExpression>,
+    /// Column pairs merged by `JOIN ... USING` or `NATURAL JOIN`
+    using_columns: Vec<(ColumnRef, ColumnRef, JoinType)>,
+    /// Tables of the enclosing query, visible to a `LATERAL` subquery
+    outer_bind_table: BTreeMap<TableName, TableCatalog>,
+    /// Columns of `outer_bind_table` referenced by the subquery
+    correlated_columns: Vec<ColumnRef>,
 }
 
 impl<S: Storage> BinderContext<S> {
@@ -41,6 +51,9 @@ impl<S: Storage> BinderContext<S> {
             aliases: Default::default(),
             group_by_exprs: vec![],
             agg_calls: Default::default(),
+            using_columns: vec![],
+            outer_bind_table: Default::default(),
+            correlated_columns: vec![],
         }
     }
 
@@ -67,6 +80,68 @@ impl<S: Storage> BinderContext<S> {
     pub fn has_agg_call(&self, expr: &ScalarExpression) -> bool {
         self.group_by_exprs.contains(expr)
     }
+
+    fn add_correlated_column(&mut self, column: ColumnRef) {
+        if !self.correlated_columns.contains(&column) {
+            self.correlated_columns.push(column);
+        }
+    }
+
+    /// The merged column of `JOIN ... USING` if all of `columns` are joined by it.
+    ///
+    /// The left column is used for `INNER` and `LEFT` joins, the right column for `RIGHT`
+    /// joins, and `FULL` joins take the first non-NULL of both.
+    pub fn using_column_expr(&self, columns: &[ColumnRef]) -> Option<ScalarExpression> {
+        let pairs = self.using_columns
+            .iter()
+            .filter(|(left, right, _)| columns.contains(left) && columns.contains(right))
+            .collect_vec();
+        let (left, ..) = pairs.first()?;
+
+        if pairs.len() + 1 != columns.len() || pairs.iter().any(|(other, ..)| other != left) {
+            return None;
+        }
+        let mut expr = ScalarExpression::ColumnRef(left.clone());
+
+        for (_, right, join_type) in pairs {
+            let right_expr = ScalarExpression::ColumnRef(right.clone());
+
+            expr = match join_type {
+                JoinType::Right => right_expr,
+                JoinType::Full => ScalarExpression::Coalesce {
+                    exprs: vec![expr, right_expr],
+                    ty: *left.datatype(),
+                },
+                _ => expr,
+            };
+        }
+        if let ScalarExpression::Coalesce { .. } = expr {
+            expr = ScalarExpression::Alias {
+                expr: Box::new(expr),
+                alias: left.name.clone(),
+            };
+        }
+
+        Some(expr)
+    }
+
+    /// The left columns of `JOIN ... USING` with all the columns merged into them.
+    fn using_column_groups(&self) -> Vec<Vec<ColumnRef>> {
+        self.using_columns
+            .iter()
+            .map(|(left, ..)| left)
+            .unique()
+            .map(|left| {
+                let mut group = vec![left.clone()];
+                group.extend(self.using_columns
+                    .iter()
+                    .filter(|(other, ..)| other == left)
+                    .map(|(_, right, _)| right.clone()));
+
+                group
+            })
+            .collect_vec()
+    }
 }
 
 pub struct Binder<S: Storage> {
@@ -79,6 +154,22 @@ impl<S: Storage> Binder<S> {
     }
 
     pub async fn bind(mut self, stmt: &Statement) -> Result<LogicalPlan, BindError> {
+        // the explained statement is bound as the child of `Explain`
+        let (stmt, explain) = match stmt {
+            Statement::Explain { statement, verbose, analyze, .. } => {
+                (statement.as_ref(), Some((*verbose, *analyze)))
+            }
+            stmt => (stmt, None),
+        };
+        // the system table is only written by `ANALYZE`, along with the statistics of the tables
+        if let Some(name) = written_table_name(stmt) {
+            let name = lower_case_name(name);
+            let (_, table_name) = split_name(&name)?;
+
+            if table_name == STATISTICS_TABLE_NAME {
+                return Err(BindError::InvalidTable(format!("system table {} is read-only", table_name)));
+            }
+        }
         let plan = match stmt {
             Statement::Query(query) => self.bind_query(query).await?,
             Statement::CreateTable { name, columns, .. } => self.bind_create_table(name, &columns)?,
@@ -121,9 +212,37 @@ impl<S: Storage> Binder<S> {
             Statement::Truncate { table_name, .. } => {
                 self.bind_truncate(table_name).await?
             }
+            Statement::Analyze { table_name, columns, .. } => {
+                self.bind_analyze(table_name, columns).await?
+            }
             _ => unimplemented!(),
         };
-        Ok(plan)
+
+        Ok(match explain {
+            Some((verbose, analyze)) => ExplainOperator::new(verbose, analyze, plan),
+            None => plan,
+        })
+    }
+}
+
+/// The table created, dropped or written by the statement.
+fn written_table_name(stmt: &Statement) -> Option<&ObjectName> {
+    fn table_with_joins_name(table: &TableWithJoins) -> Option<&ObjectName> {
+        match &table.relation {
+            TableFactor::Table { name, .. } => Some(name),
+            _ => None,
+        }
+    }
+
+    match stmt {
+        Statement::CreateTable { name, .. } => Some(name),
+        Statement::Drop { names, .. } => names.first(),
+        Statement::Insert { table_name, .. }
+        | Statement::Truncate { table_name, .. }
+        | Statement::Analyze { table_name, .. } => Some(table_name),
+        Statement::Update { table, .. } => table_with_joins_name(table),
+        Statement::Delete { from, .. } => from.first().and_then(table_with_joins_name),
+        _ => None,
     }
 }
 
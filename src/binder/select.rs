@@ -1,5 +1,5 @@
 use std::borrow::Borrow;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use async_recursion::async_recursion;
 
@@ -15,21 +15,27 @@ use crate::{
     types::value::DataValue,
 };
 
-use super::Binder;
+use super::{Binder, BinderContext};
 
-use crate::catalog::{ColumnCatalog, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, TableCatalog, TableName};
+use crate::catalog::{ColumnCatalog, ColumnDesc, ColumnRef, DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, TableCatalog, TableName};
 use itertools::Itertools;
 use sqlparser::ast;
-use sqlparser::ast::{Distinct, Expr, Ident, Join, JoinConstraint, JoinOperator, Offset, OrderByExpr, Query, Select, SelectItem, SetExpr, TableFactor, TableWithJoins};
+use sqlparser::ast::{Distinct, Expr, Ident, Join, JoinConstraint, JoinOperator, Offset, OrderByExpr, Query, Select, SelectItem, SetExpr, TableAlias, TableFactor, TableWithJoins};
 use crate::binder::BindError;
+use crate::binder::subquery::{is_subquery_predicate, split_conjunctions, subquery_output_exprs, subquery_output_exprs_mut};
 use crate::execution::executor::dql::join::joins_nullable;
 use crate::expression::BinaryOperator;
 use crate::planner::LogicalPlan;
+use crate::planner::operator::apply::ApplyOperator;
 use crate::planner::operator::join::JoinCondition;
 use crate::planner::operator::sort::{SortField, SortOperator};
 use crate::storage::Storage;
 use crate::types::LogicalType;
 
+fn is_lateral(relation: &TableFactor) -> bool {
+    matches!(relation, TableFactor::Derived { lateral: true, .. })
+}
+
 impl<S: Storage> Binder<S> {
     #[async_recursion]
     pub(crate) async fn bind_query(&mut self, query: &Query) -> Result<LogicalPlan, BindError> {
@@ -109,7 +115,6 @@ impl<S: Storage> Binder<S> {
     }
 
     pub(crate) async fn bind_table_ref(&mut self, from: &[TableWithJoins]) -> Result<LogicalPlan, BindError> {
-        assert!(from.len() < 2, "not support yet.");
         if from.is_empty() {
             return Ok(LogicalPlan {
                 operator: Operator::Dummy,
@@ -117,15 +122,53 @@ impl<S: Storage> Binder<S> {
             });
         }
 
-        let TableWithJoins { relation, joins } = &from[0];
+        let mut plan = self.bind_table_with_joins(&from[0], None).await?;
+
+        // `FROM a, b` is an implicit cross join, equalities in WHERE are turned into
+        // join conditions by `PushPredicateThroughJoin`
+        for table_with_joins in &from[1..] {
+            if is_lateral(&table_with_joins.relation) {
+                let (lateral_name, apply) = self
+                    .bind_apply(&[], plan, &table_with_joins.relation, JoinType::Cross, None)
+                    .await?;
+                plan = apply;
+
+                // the joins after `LATERAL (...) s` have `s` on their left side, as the joins
+                // after a table of the storage have that table
+                let mut left_tables = vec![lateral_name];
+
+                for join in &table_with_joins.joins {
+                    let (right_table, join_plan) = self.bind_join(&left_tables, plan, join).await?;
+                    plan = join_plan;
+                    left_tables.push(right_table);
+                }
+                continue;
+            }
+            let right = self.bind_table_with_joins(table_with_joins, Some(JoinType::Cross)).await?;
 
-        let (left_name, mut plan) = self.bind_single_table_ref(relation, None).await?;
+            plan = LJoinOperator::new(plan, right, JoinCondition::None, JoinType::Cross);
+        }
 
-        if !joins.is_empty() {
-            for join in joins {
-                plan = self.bind_join(left_name.clone(), plan, join).await?;
-            }
+        Ok(plan)
+    }
+
+    async fn bind_table_with_joins(
+        &mut self,
+        table_with_joins: &TableWithJoins,
+        joint_type: Option<JoinType>,
+    ) -> Result<LogicalPlan, BindError> {
+        let TableWithJoins { relation, joins } = table_with_joins;
+
+        let (left_name, mut plan) = self.bind_single_table_ref(relation, joint_type).await?;
+        // every table joined so far is on the left side of the next join
+        let mut left_tables = vec![left_name];
+
+        for join in joins {
+            let (right_table, join_plan) = self.bind_join(&left_tables, plan, join).await?;
+            plan = join_plan;
+            left_tables.push(right_table);
         }
+
         Ok(plan)
     }
 
@@ -138,18 +181,30 @@ impl<S: Storage> Binder<S> {
                     .map(|ident| Ident::new(ident.value.to_lowercase()))
                     .collect_vec();
 
-                let (_database, _schema, mut table): (&str, &str, &str) = match obj_name.as_slice()
+                let (_database, _schema, table): (&str, &str, &str) = match obj_name.as_slice()
                 {
                     [table] => (DEFAULT_DATABASE_NAME, DEFAULT_SCHEMA_NAME, &table.value),
                     [schema, table] => (DEFAULT_DATABASE_NAME, &schema.value, &table.value),
                     [database, schema, table] => (&database.value, &schema.value, &table.value),
                     _ => return Err(BindError::InvalidTableName(obj_name)),
                 };
-                if let Some(alias) = alias {
-                    table = &alias.name.value;
-                }
+                let alias = alias
+                    .as_ref()
+                    .map(|alias| alias.name.value.to_lowercase());
 
-                self._bind_single_table_ref(joint_type, table).await?
+                self._bind_single_table_ref(joint_type, table, alias.as_deref()).await?
+            }
+            TableFactor::Derived { lateral, subquery, alias } => {
+                let (table_name, plan, correlated_columns, _) = self
+                    .bind_derived_table(subquery, alias, joint_type, *lateral)
+                    .await?;
+
+                if !correlated_columns.is_empty() {
+                    return Err(BindError::UnsupportedStmt(
+                        "LATERAL subquery must be joined with the tables it references".to_string()
+                    ));
+                }
+                (table_name, plan)
             }
             _ => unimplemented!(),
         };
@@ -157,11 +212,14 @@ impl<S: Storage> Binder<S> {
         Ok(plan_with_name)
     }
 
-    pub(crate) async fn _bind_single_table_ref(&mut self, joint_type: Option<JoinType>, table: &str) -> Result<(Arc<String>, LogicalPlan), BindError> {
+    /// Bind a table of the storage, `alias` is the name it is referenced by in the query, the
+    /// columns of an aliased table are bound to the alias.
+    pub(crate) async fn _bind_single_table_ref(&mut self, joint_type: Option<JoinType>, table: &str, alias: Option<&str>) -> Result<(Arc<String>, LogicalPlan), BindError> {
         let table_name = Arc::new(table.to_string());
+        let bind_name = Arc::new(alias.unwrap_or(table).to_string());
 
-        if self.context.bind_table.contains_key(&table_name) {
-            return Err(BindError::InvalidTable(format!("{} duplicated", table)));
+        if self.context.bind_table.contains_key(&bind_name) {
+            return Err(BindError::InvalidTable(format!("{} duplicated", bind_name)));
         }
 
         let table_catalog = self
@@ -170,10 +228,138 @@ impl<S: Storage> Binder<S> {
             .table_catalog(&table_name)
             .await
             .ok_or_else(|| BindError::InvalidTable(format!("bind table {}", table)))?;
+        let table_catalog = match alias {
+            Some(_) => table_catalog.with_binding(bind_name.clone()),
+            None => table_catalog.clone(),
+        };
+
+        self.context.bind_table.insert(bind_name.clone(), (table_catalog.clone(), joint_type));
+
+        Ok((bind_name, ScanOperator::new(table_name, &table_catalog)))
+    }
+
+    /// Bind a subquery in FROM, its output columns become the columns of the table `alias`.
+    ///
+    /// A `LATERAL` subquery can reference the tables bound before it, those columns are
+    /// returned as the correlated columns together with the output columns of the subquery.
+    async fn bind_derived_table(
+        &mut self,
+        subquery: &Query,
+        alias: &Option<TableAlias>,
+        joint_type: Option<JoinType>,
+        lateral: bool,
+    ) -> Result<(TableName, LogicalPlan, Vec<ColumnRef>, Vec<ColumnRef>), BindError> {
+        let alias = alias.as_ref().ok_or(BindError::SubqueryMustHaveAlias)?;
+        let table_name = Arc::new(alias.name.value.to_lowercase());
+
+        if self.context.bind_table.contains_key(&table_name) {
+            return Err(BindError::InvalidTable(format!("{} duplicated", table_name)));
+        }
+
+        let mut binder = if lateral {
+            self.subquery_binder()
+        } else {
+            Binder::new(BinderContext::new(self.context.storage.clone()))
+        };
+        let mut plan = binder.bind_query(subquery).await?;
+        let output_exprs = subquery_output_exprs(&plan);
+        let column_names = alias.columns
+            .iter()
+            .map(|ident| Some(ident.value.to_lowercase()))
+            .chain(std::iter::repeat(None));
+
+        let mut columns = Vec::with_capacity(output_exprs.len());
+        for (expr, column_name) in output_exprs.iter().zip(column_names) {
+            let column_name = match (column_name, expr) {
+                (Some(name), _) => name,
+                (None, ScalarExpression::ColumnRef(col)) => col.name.clone(),
+                (None, ScalarExpression::Alias { alias, .. }) => alias.clone(),
+                (None, expr) => return Err(BindError::InvalidColumn(
+                    format!("{:?} in subquery {} must have an alias", expr, table_name)
+                )),
+            };
+            columns.push(ColumnCatalog::new(
+                column_name,
+                expr.nullable(),
+                ColumnDesc::new(expr.return_type(), false),
+            ));
+        }
+        // the subquery outputs new columns named after the ones of the derived table, rather
+        // than the columns of its tables, which may also be joined with the derived table
+        for (expr, col) in subquery_output_exprs_mut(&mut plan).iter_mut().zip(columns.iter()) {
+            if !matches!(expr, ScalarExpression::Alias { alias, .. } if *alias == col.name) {
+                *expr = ScalarExpression::Alias {
+                    expr: Box::new(expr.clone()),
+                    alias: col.name.clone(),
+                };
+            }
+        }
+        let table_catalog = TableCatalog::new(table_name.clone(), columns)?;
+        // keep the order of the subquery output
+        let output_columns = table_catalog
+            .all_columns_with_id()
+            .into_iter()
+            .sorted_by_key(|(id, _)| **id)
+            .map(|(_, col)| col.clone())
+            .collect_vec();
+
+        self.context.bind_table.insert(table_name.clone(), (table_catalog, joint_type));
+
+        Ok((table_name, plan, binder.context.correlated_columns, output_columns))
+    }
+
+    /// Bind a `LATERAL` subquery, which is evaluated for every row of `left`, the name of the
+    /// subquery is returned with the plan.
+    async fn bind_apply(
+        &mut self,
+        left_tables: &[TableName],
+        left: LogicalPlan,
+        relation: &TableFactor,
+        join_type: JoinType,
+        constraint: Option<&JoinConstraint>,
+    ) -> Result<(TableName, LogicalPlan), BindError> {
+        if !matches!(join_type, JoinType::Inner | JoinType::Left | JoinType::Cross) {
+            return Err(BindError::UnsupportedStmt(
+                format!("{:?} join with a LATERAL subquery", join_type)
+            ));
+        }
+        let TableFactor::Derived { subquery, alias, .. } = relation else {
+            unreachable!("LATERAL must be a subquery")
+        };
+        let (right_table, right, correlated_columns, right_columns) = self
+            .bind_derived_table(subquery, alias, Some(join_type), true)
+            .await?;
+
+        let on = match constraint {
+            Some(constraint) => {
+                if left_tables.is_empty() {
+                    return Err(BindError::InvalidTable(
+                        "LATERAL subquery with a join constraint".to_string()
+                    ));
+                }
+                let left_tables = self.bound_table_catalogs(left_tables)?;
+                let right_table = self.bound_table_catalog(&right_table)?;
+
+                self.bind_join_constraint(&left_tables, &right_table, constraint, join_type).await?
+            }
+            None => JoinCondition::None,
+        };
+
+        Ok((right_table, ApplyOperator::new(left, right, on, join_type, correlated_columns, right_columns)))
+    }
 
-        self.context.bind_table.insert(table_name.clone(), (table_catalog.clone(), joint_type));
+    fn bound_table_catalog(&self, table_name: &TableName) -> Result<TableCatalog, BindError> {
+        self.context.bind_table
+            .get(table_name)
+            .map(|(table_catalog, _)| table_catalog.clone())
+            .ok_or_else(|| BindError::InvalidTable(format!("{} not found", table_name)))
+    }
 
-        Ok((table_name.clone(), ScanOperator::new(table_name, &table_catalog)))
+    fn bound_table_catalogs(&self, table_names: &[TableName]) -> Result<Vec<TableCatalog>, BindError> {
+        table_names
+            .iter()
+            .map(|table_name| self.bound_table_catalog(table_name))
+            .try_collect()
     }
 
     /// Normalize select item.
@@ -200,7 +386,7 @@ impl<S: Storage> Binder<S> {
                     });
                 }
                 SelectItem::Wildcard(_) => {
-                    select_items.extend_from_slice(self.bind_all_column_refs().await?.as_slice());
+                    select_items.extend_from_slice(self.bind_all_column_refs()?.as_slice());
                 }
 
                 _ => todo!("bind select list"),
@@ -210,23 +396,36 @@ impl<S: Storage> Binder<S> {
         Ok(select_items)
     }
 
-    async fn bind_all_column_refs(&mut self) -> Result<Vec<ScalarExpression>, BindError> {
+    fn bind_all_column_refs(&mut self) -> Result<Vec<ScalarExpression>, BindError> {
         let mut exprs = vec![];
-        for table_name in self.context.bind_table.keys().cloned() {
-            let table = self.context
-                .storage
-                .table_catalog(&table_name)
-                .await
-                .ok_or_else(|| BindError::InvalidTable(table_name.to_string()))?;
+        let mut merged_columns = HashSet::new();
+
+        // the columns of `JOIN ... USING` come first and only once
+        for group in self.context.using_column_groups() {
+            if let Some(expr) = self.context.using_column_expr(&group) {
+                exprs.push(expr);
+                merged_columns.extend(group);
+            }
+        }
+        for (table, _) in self.context.bind_table.values() {
             for col in table.all_columns() {
-                exprs.push(ScalarExpression::ColumnRef(col));
+                if !merged_columns.contains(&col) {
+                    exprs.push(ScalarExpression::ColumnRef(col));
+                }
             }
         }
 
         Ok(exprs)
     }
 
-    async fn bind_join(&mut self, left_table: TableName, left: LogicalPlan, join: &Join) -> Result<LogicalPlan, BindError> {
+    /// Bind the join of `left`, which joins `left_tables`, with the relation of `join`, the name
+    /// of the relation is returned with the plan.
+    async fn bind_join(
+        &mut self,
+        left_tables: &[TableName],
+        left: LogicalPlan,
+        join: &Join,
+    ) -> Result<(TableName, LogicalPlan), BindError> {
         let Join {
             relation,
             join_operator,
@@ -241,41 +440,75 @@ impl<S: Storage> Binder<S> {
             _ => unimplemented!(),
         };
 
+        if is_lateral(relation) {
+            return self.bind_apply(left_tables, left, relation, join_type, joint_condition).await;
+        }
         let (right_table, right) = self.bind_single_table_ref(relation, Some(join_type)).await?;
 
-        let left_table = self.context.storage
-            .table_catalog(&left_table)
-            .await
-            .cloned()
-            .ok_or_else(|| BindError::InvalidTable(format!("Left: {} not found", left_table)))?;
-        let right_table = self.context.storage
-            .table_catalog(&right_table)
-            .await
-            .cloned()
-            .ok_or_else(|| BindError::InvalidTable(format!("Right: {} not found", right_table)))?;
-
         let on = match joint_condition {
-            Some(constraint) => self.bind_join_constraint(
-                &left_table,
-                &right_table,
-                constraint
-            ).await?,
+            Some(constraint) => {
+                if left_tables.is_empty() {
+                    return Err(BindError::InvalidTable(
+                        "the left side of the join is unknown".to_string()
+                    ));
+                }
+                let left_tables = self.bound_table_catalogs(left_tables)?;
+                let right_table = self.bound_table_catalog(&right_table)?;
+
+                self.bind_join_constraint(
+                    &left_tables,
+                    &right_table,
+                    constraint,
+                    join_type,
+                ).await?
+            }
             None => JoinCondition::None,
         };
 
-        Ok(LJoinOperator::new(left, right, on, join_type))
+        Ok((right_table, LJoinOperator::new(left, right, on, join_type)))
     }
 
     pub(crate) async fn bind_where(
         &mut self,
-        children: LogicalPlan,
+        mut children: LogicalPlan,
         predicate: &Expr,
     ) -> Result<LogicalPlan, BindError> {
-        Ok(FilterOperator::new(
-            self.bind_expr(predicate).await?,
-            children,
-            false,
-        ))
+        let mut conjunctions = vec![];
+        split_conjunctions(predicate, &mut conjunctions);
+
+        if !conjunctions.iter().any(|expr| is_subquery_predicate(expr)) {
+            return Ok(FilterOperator::new(
+                self.bind_expr(predicate).await?,
+                children,
+                false,
+            ));
+        }
+        // `IN (subquery)` and `EXISTS` become semi or anti joins, the rest stay in the filter
+        let mut predicates = vec![];
+        for expr in conjunctions {
+            match expr {
+                Expr::InSubquery { expr, subquery, negated } => {
+                    children = self.bind_in_subquery(children, expr, subquery, *negated).await?;
+                }
+                Expr::Exists { subquery, negated } => {
+                    children = self.bind_exists(children, subquery, *negated).await?;
+                }
+                expr => predicates.push(self.bind_expr(expr).await?),
+            }
+        }
+        let predicate = predicates
+            .into_iter()
+            .reduce(|acc, expr| ScalarExpression::Binary {
+                op: BinaryOperator::And,
+                left_expr: Box::new(acc),
+                right_expr: Box::new(expr),
+                ty: LogicalType::Boolean,
+            });
+
+        Ok(match predicate {
+            Some(predicate) => FilterOperator::new(predicate, children, false),
+            None => children,
+        })
     }
 
     fn bind_having(
@@ -364,13 +597,14 @@ impl<S: Storage> Binder<S> {
         let mut left_table_force_nullable = false;
         let mut left_table = None;
 
-        for (table_name, (_, join_option)) in bind_tables {
+        // keyed by the names the tables are bound to, a table may be joined with itself
+        for (bind_name, (_, join_option)) in bind_tables.iter() {
             if let Some(join_type) = join_option {
                 let (left_force_nullable, right_force_nullable) = joins_nullable(join_type);
-                table_force_nullable.insert(table_name.clone(), right_force_nullable);
+                table_force_nullable.insert(bind_name.clone(), right_force_nullable);
                 left_table_force_nullable = left_force_nullable;
             } else {
-                left_table = Some(table_name.clone());
+                left_table = Some(bind_name.clone());
             }
         }
 
@@ -380,7 +614,7 @@ impl<S: Storage> Binder<S> {
 
         for column in select_items {
             if let ScalarExpression::ColumnRef(col) = column {
-                if let Some(nullable) = table_force_nullable.get(col.table_name.as_ref().unwrap()) {
+                if let Some(nullable) = table_force_nullable.get(col.binding.as_ref().or(col.table_name.as_ref()).unwrap()) {
                     let mut new_col = ColumnCatalog::clone(col);
                     new_col.nullable = *nullable;
 
@@ -392,9 +626,10 @@ impl<S: Storage> Binder<S> {
 
     async fn bind_join_constraint(
         &mut self,
-        left_table: &TableCatalog,
+        left_tables: &[TableCatalog],
         right_table: &TableCatalog,
         constraint: &JoinConstraint,
+        join_type: JoinType,
     ) -> Result<JoinCondition, BindError> {
         match constraint {
             JoinConstraint::On(expr) => {
@@ -403,7 +638,7 @@ impl<S: Storage> Binder<S> {
                 // expression that didn't match equi-join pattern
                 let mut filter = vec![];
 
-                self.extract_join_keys(expr, &mut on_keys, &mut filter, left_table, right_table).await?;
+                self.extract_join_keys(expr, &mut on_keys, &mut filter, left_tables, right_table).await?;
 
                 // combine multiple filter exprs into one BinaryExpr
                 let join_filter = filter
@@ -420,10 +655,77 @@ impl<S: Storage> Binder<S> {
                     filter: join_filter,
                 })
             }
-            _ => unimplemented!("not supported join constraint {:?}", constraint),
+            JoinConstraint::Using(idents) => {
+                let column_names = idents
+                    .iter()
+                    .map(|ident| ident.value.to_lowercase())
+                    .collect_vec();
+
+                self.bind_using_columns(left_tables, right_table, &column_names, join_type)
+            }
+            JoinConstraint::Natural => {
+                let column_names = left_tables
+                    .iter()
+                    .flat_map(|table| table.all_columns())
+                    .map(|col| col.name.clone())
+                    .filter(|name| right_table.contains_column(name))
+                    .unique()
+                    .collect_vec();
+
+                self.bind_using_columns(left_tables, right_table, &column_names, join_type)
+            }
+            JoinConstraint::None => Ok(JoinCondition::None),
         }
     }
 
+    /// `JOIN ... USING (a, b)` is `ON left.a = right.a AND left.b = right.b`, and each pair of
+    /// columns is output as one column.
+    ///
+    /// The left column is looked up in all the tables on the left side of the join, where the
+    /// columns merged by a previous `USING` count once, e.g. `a JOIN b USING (x) JOIN c USING (x)`.
+    fn bind_using_columns(
+        &mut self,
+        left_tables: &[TableCatalog],
+        right_table: &TableCatalog,
+        column_names: &[String],
+        join_type: JoinType,
+    ) -> Result<JoinCondition, BindError> {
+        let mut on_keys = Vec::with_capacity(column_names.len());
+
+        for column_name in column_names {
+            let left_columns = left_tables
+                .iter()
+                .filter_map(|table| table.get_column_by_name(column_name))
+                .filter(|col| !self.context.using_columns.iter().any(|(_, right, _)| right == *col))
+                .cloned()
+                .collect_vec();
+            let left_column = match left_columns.as_slice() {
+                [column] => column.clone(),
+                [] => return Err(BindError::InvalidColumn(
+                    format!("{} specified in USING does not exist in the left side of the join", column_name)
+                )),
+                _ => return Err(BindError::AmbiguousColumn(column_name.to_string())),
+            };
+            let right_column = right_table
+                .get_column_by_name(column_name)
+                .cloned()
+                .ok_or_else(|| BindError::InvalidColumn(
+                    format!("{} specified in USING does not exist in {}", column_name, right_table.name)
+                ))?;
+
+            on_keys.push((
+                ScalarExpression::ColumnRef(left_column.clone()),
+                ScalarExpression::ColumnRef(right_column.clone()),
+            ));
+            self.context.using_columns.push((left_column, right_column, join_type));
+        }
+
+        Ok(JoinCondition::On {
+            on: on_keys,
+            filter: None,
+        })
+    }
+
     /// for sqlrs
     /// original idea from datafusion planner.rs
     /// Extracts equijoin ON condition be a single Eq or multiple conjunctive Eqs
@@ -441,7 +743,7 @@ impl<S: Storage> Binder<S> {
         expr: &Expr,
         accum: &mut Vec<(ScalarExpression, ScalarExpression)>,
         accum_filter: &mut Vec<ScalarExpression>,
-        left_schema: &TableCatalog,
+        left_schemas: &[TableCatalog],
         right_schema: &TableCatalog,
     ) -> Result<(), BindError> {
         match expr {
@@ -454,11 +756,15 @@ impl<S: Storage> Binder<S> {
                         // example: foo = bar
                         (ScalarExpression::ColumnRef(l), ScalarExpression::ColumnRef(r)) => {
                             // reorder left and right joins keys to pattern: (left, right)
-                            if left_schema.contains_column(&l.name)
+                            let left_contains = |name: &String| left_schemas
+                                .iter()
+                                .any(|schema| schema.contains_column(name));
+
+                            if left_contains(&l.name)
                                 && right_schema.contains_column(&r.name)
                             {
                                 accum.push((left, right));
-                            } else if left_schema.contains_column(&r.name)
+                            } else if left_contains(&r.name)
                                 && right_schema.contains_column(&l.name)
                             {
                                 accum.push((right, left));
@@ -479,14 +785,14 @@ impl<S: Storage> Binder<S> {
                             left,
                             accum,
                             accum_filter,
-                            left_schema,
+                            left_schemas,
                             right_schema,
                         ).await?;
                         self.extract_join_keys(
                             right,
                             accum,
                             accum_filter,
-                            left_schema,
+                            left_schemas,
                             right_schema,
                         ).await?;
                     }
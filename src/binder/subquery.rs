@@ -0,0 +1,145 @@
+use std::sync::Arc;
+use sqlparser::ast;
+use sqlparser::ast::{Expr, Query};
+use crate::binder::{Binder, BinderContext, BindError};
+use crate::catalog::{ColumnCatalog, ColumnDesc};
+use crate::expression::ScalarExpression;
+use crate::planner::LogicalPlan;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::planner::operator::Operator;
+use crate::planner::operator::project::ProjectOperator;
+use crate::storage::Storage;
+
+impl<S: Storage> Binder<S> {
+    /// A binder for a subquery, the tables of this query are visible to it as the outer scope.
+    pub(crate) fn subquery_binder(&self) -> Binder<S> {
+        let mut context = BinderContext::new(self.context.storage.clone());
+        context.outer_bind_table = self.context.outer_bind_table
+            .clone()
+            .into_iter()
+            .chain(self.context.bind_table
+                .iter()
+                .map(|(name, (table_catalog, _))| (name.clone(), table_catalog.clone())))
+            .collect();
+
+        Binder::new(context)
+    }
+
+    /// Bind a subquery that doesn't reference the columns of the outer query.
+    async fn bind_uncorrelated_subquery(&mut self, subquery: &Query) -> Result<LogicalPlan, BindError> {
+        let mut binder = self.subquery_binder();
+        let plan = binder.bind_query(subquery).await?;
+
+        if !binder.context.correlated_columns.is_empty() {
+            return Err(BindError::UnsupportedStmt("correlated subquery in WHERE".to_string()));
+        }
+        Ok(plan)
+    }
+
+    /// `expr [NOT] IN (subquery)` as a semi join or a null-aware anti join with the subquery.
+    pub(crate) async fn bind_in_subquery(
+        &mut self,
+        children: LogicalPlan,
+        expr: &Expr,
+        subquery: &Query,
+        negated: bool,
+    ) -> Result<LogicalPlan, BindError> {
+        let left_key = self.bind_expr(expr).await?;
+        let right = self.bind_uncorrelated_subquery(subquery).await?;
+
+        let mut right_key = match subquery_output_exprs(&right) {
+            [ScalarExpression::ColumnRef(col)] => ScalarExpression::ColumnRef(col.clone()),
+            [expr @ ScalarExpression::Alias { alias, .. }] => ScalarExpression::ColumnRef(Arc::new(
+                ColumnCatalog::new(
+                    alias.clone(),
+                    expr.nullable(),
+                    ColumnDesc::new(expr.return_type(), false),
+                )
+            )),
+            [expr] => return Err(BindError::InvalidColumn(
+                format!("{:?} in IN subquery must have an alias", expr)
+            )),
+            _ => return Err(BindError::InvalidColumn(
+                "IN subquery must return only one column".to_string()
+            )),
+        };
+        // the keys are hashed, so both sides must have the same type
+        if right_key.return_type() != left_key.return_type() {
+            right_key = ScalarExpression::TypeCast {
+                expr: Box::new(right_key),
+                ty: left_key.return_type(),
+                is_try: false,
+            };
+        }
+        let join_type = if negated {
+            JoinType::NullAwareLeftAnti
+        } else {
+            JoinType::LeftSemi
+        };
+
+        Ok(JoinOperator::new(
+            children,
+            right,
+            JoinCondition::On { on: vec![(left_key, right_key)], filter: None },
+            join_type,
+        ))
+    }
+
+    /// `[NOT] EXISTS (subquery)` as a semi or anti join with the subquery.
+    pub(crate) async fn bind_exists(
+        &mut self,
+        children: LogicalPlan,
+        subquery: &Query,
+        negated: bool,
+    ) -> Result<LogicalPlan, BindError> {
+        let right = self.bind_uncorrelated_subquery(subquery).await?;
+        let join_type = if negated {
+            JoinType::LeftAnti
+        } else {
+            JoinType::LeftSemi
+        };
+
+        Ok(JoinOperator::new(children, right, JoinCondition::None, join_type))
+    }
+}
+
+/// The output expressions of a bound subquery.
+pub(crate) fn subquery_output_exprs(plan: &LogicalPlan) -> &[ScalarExpression] {
+    let mut plan = plan;
+    while let Operator::Limit(_) = plan.operator {
+        plan = &plan.childrens[0];
+    }
+    match &plan.operator {
+        Operator::Project(ProjectOperator { columns }) => columns,
+        _ => unreachable!("the output of a subquery must be a projection"),
+    }
+}
+
+/// Like `subquery_output_exprs`, to rewrite them.
+pub(crate) fn subquery_output_exprs_mut(plan: &mut LogicalPlan) -> &mut Vec<ScalarExpression> {
+    let mut plan = plan;
+    while let Operator::Limit(_) = plan.operator {
+        plan = &mut plan.childrens[0];
+    }
+    match &mut plan.operator {
+        Operator::Project(ProjectOperator { columns }) => columns,
+        _ => unreachable!("the output of a subquery must be a projection"),
+    }
+}
+
+/// Whether the conjunction is rewritten into a join by `bind_where`.
+pub(crate) fn is_subquery_predicate(expr: &Expr) -> bool {
+    matches!(expr, Expr::InSubquery { .. } | Expr::Exists { .. })
+}
+
+/// Split `a AND (b AND c)` into `[a, b, c]`.
+pub(crate) fn split_conjunctions<'a>(expr: &'a Expr, conjunctions: &mut Vec<&'a Expr>) {
+    match expr {
+        Expr::BinaryOp { left, op: ast::BinaryOperator::And, right } => {
+            split_conjunctions(left, conjunctions);
+            split_conjunctions(right, conjunctions);
+        }
+        Expr::Nested(expr) => split_conjunctions(expr, conjunctions),
+        expr => conjunctions.push(expr),
+    }
+}
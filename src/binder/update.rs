@@ -2,12 +2,14 @@ use std::slice;
 use std::sync::Arc;
 use sqlparser::ast::{Assignment, Expr, TableFactor, TableWithJoins};
 use crate::binder::{Binder, BindError, lower_case_name, split_name};
+use crate::binder::expr::bind_decimal_literal;
 use crate::expression::ScalarExpression;
 use crate::planner::LogicalPlan;
 use crate::planner::operator::Operator;
 use crate::planner::operator::update::UpdateOperator;
 use crate::storage::Storage;
-use crate::types::value::ValueRef;
+use crate::types::LogicalType;
+use crate::types::value::{DataValue, ValueRef};
 
 impl<S: Storage> Binder<S> {
     pub(crate) async fn bind_update(
@@ -44,8 +46,14 @@ impl<S: Storage> Binder<S> {
                         bind_table_name.as_ref()
                     ).await? {
                         ScalarExpression::ColumnRef(catalog) => {
+                            let value = match (catalog.datatype(), bind_decimal_literal(&assignment.value)) {
+                                (LogicalType::Decimal(..), Some(ScalarExpression::Constant(literal))) => {
+                                    Arc::new(DataValue::clone(&literal).cast(catalog.datatype())?)
+                                }
+                                _ => value.clone(),
+                            };
                             columns.push(catalog);
-                            row.push(value.clone());
+                            row.push(value);
                         },
                         _ => unreachable!()
                     }
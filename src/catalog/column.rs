@@ -12,6 +12,10 @@ pub struct ColumnCatalog {
     pub id: ColumnId,
     pub name: String,
     pub table_name: Option<TableName>,
+    /// The alias of the table in the query, which tells apart the columns of a table joined
+    /// with itself, e.g. `t a JOIN t b`. The columns of the catalog are not bound.
+    #[serde(skip)]
+    pub binding: Option<TableName>,
     pub nullable: bool,
     pub desc: ColumnDesc,
 }
@@ -22,11 +26,18 @@ impl ColumnCatalog {
             id: IdGenerator::build(),
             name: column_name,
             table_name: None,
+            binding: None,
             nullable,
             desc: column_desc,
         }
     }
 
+    /// Whether both are the column of the same table bound by the same name in the query,
+    /// the nullability forced by outer joins aside.
+    pub(crate) fn is_same_column(&self, other: &ColumnCatalog) -> bool {
+        self.id == other.id && self.table_name == other.table_name && self.binding == other.binding
+    }
+
     pub(crate) fn datatype(&self) -> &LogicalType {
         &self.desc.column_datatype
     }
@@ -3,6 +3,7 @@ use std::sync::Arc;
 
 pub(crate) use self::column::*;
 pub(crate) use self::root::*;
+pub(crate) use self::statistics::*;
 pub(crate) use self::table::*;
 
 /// The type of catalog reference.
@@ -13,6 +14,7 @@ pub(crate) static DEFAULT_SCHEMA_NAME: &str = "kipsql";
 
 mod column;
 mod root;
+mod statistics;
 mod table;
 
 #[derive(thiserror::Error, Debug)]
@@ -25,6 +25,10 @@ impl RootCatalog {
         self.table_idxs.get(name)
     }
 
+    pub(crate) fn get_table_mut(&mut self, name: &String) -> Option<&mut TableCatalog> {
+        self.table_idxs.get_mut(name)
+    }
+
     pub(crate) fn add_table(
         &mut self,
         table_name: TableName,
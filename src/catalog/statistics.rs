@@ -0,0 +1,338 @@
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::Arc;
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use crate::types::{ColumnId, LogicalType};
+use crate::types::value::{DataValue, ValueRef};
+
+/// The system table with the statistics of the analyzed columns, one row per column.
+pub const STATISTICS_TABLE_NAME: &str = "kipsql_statistics";
+/// The primary key of the row of a column in the system table, e.g. `t1.a`.
+pub fn statistics_row_id(table_name: &str, column_name: &str) -> ValueRef {
+    Arc::new(DataValue::Utf8(Some(format!("{}.{}", table_name, column_name))))
+}
+
+/// Maximum number of buckets of a histogram.
+pub const DEFAULT_BUCKETS: usize = 100;
+/// Maximum number of values sampled from a column to build its histogram.
+pub const DEFAULT_SAMPLE_SIZE: usize = 30000;
+/// The sketch has `2^HLL_PRECISION` registers, the standard error is about 1.6%.
+const HLL_PRECISION: u32 = 12;
+
+/// Statistics of a table collected by `ANALYZE`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TableStatistics {
+    pub row_count: usize,
+    pub columns: BTreeMap<ColumnId, ColumnStatistics>,
+}
+
+impl TableStatistics {
+    pub fn column(&self, id: &ColumnId) -> Option<&ColumnStatistics> {
+        self.columns.get(id)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ColumnStatistics {
+    pub null_fraction: f64,
+    /// Estimated number of distinct non-null values
+    pub ndv: usize,
+    pub histogram: Histogram,
+}
+
+/// Equi-depth histogram of the non-null values, every bucket holds about the same number of
+/// values and equal values are never split across buckets.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(into = "RawHistogram", from = "RawHistogram")]
+pub struct Histogram {
+    ty: LogicalType,
+    buckets: Vec<Bucket>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket {
+    pub lower: ValueRef,
+    pub upper: ValueRef,
+    pub count: usize,
+}
+
+/// `DataValue` is stored in its raw encoding, which needs the type to decode it.
+#[derive(Serialize, Deserialize)]
+struct RawHistogram {
+    ty: LogicalType,
+    buckets: Vec<(Vec<u8>, Vec<u8>, usize)>,
+}
+
+impl From<Histogram> for RawHistogram {
+    fn from(Histogram { ty, buckets }: Histogram) -> Self {
+        RawHistogram {
+            ty,
+            buckets: buckets
+                .into_iter()
+                .map(|Bucket { lower, upper, count }| (lower.to_raw(), upper.to_raw(), count))
+                .collect_vec(),
+        }
+    }
+}
+
+impl From<RawHistogram> for Histogram {
+    fn from(RawHistogram { ty, buckets }: RawHistogram) -> Self {
+        Histogram {
+            ty,
+            buckets: buckets
+                .into_iter()
+                .map(|(lower, upper, count)| Bucket {
+                    lower: Arc::new(DataValue::from_raw(&lower, &ty)),
+                    upper: Arc::new(DataValue::from_raw(&upper, &ty)),
+                    count,
+                })
+                .collect_vec(),
+        }
+    }
+}
+
+impl Histogram {
+    /// Build the histogram from the sorted non-null values, or from a sorted sample of them whose
+    /// counts are scaled to `total` values.
+    fn new(ty: LogicalType, values: &[ValueRef], total: usize, max_buckets: usize) -> Self {
+        let max_buckets = max_buckets.max(1);
+        let mut buckets = Vec::with_capacity(max_buckets);
+        let depth = (values.len() + max_buckets - 1) / max_buckets;
+        let mut start = 0;
+
+        while start < values.len() {
+            let mut end = (start + depth).min(values.len());
+            while end < values.len() && values[end] == values[end - 1] {
+                end += 1;
+            }
+            buckets.push(Bucket {
+                lower: values[start].clone(),
+                upper: values[end - 1].clone(),
+                count: ((end - start) as f64 * total as f64 / values.len() as f64).round() as usize,
+            });
+            start = end;
+        }
+
+        Histogram { ty, buckets }
+    }
+
+    pub fn buckets(&self) -> &[Bucket] {
+        &self.buckets
+    }
+
+    /// Estimated fraction of the non-null values that are less than `value`, the values are
+    /// assumed to be spread evenly within a bucket.
+    pub fn less_than_fraction(&self, value: &DataValue) -> f64 {
+        let total: usize = self.buckets.iter().map(|bucket| bucket.count).sum();
+        if total == 0 {
+            return 0.0;
+        }
+        let less: f64 = self.buckets
+            .iter()
+            .map(|bucket| {
+                match (bucket.upper.as_ref().partial_cmp(value), bucket.lower.as_ref().partial_cmp(value)) {
+                    (Some(Ordering::Less), _) => bucket.count as f64,
+                    (_, Some(Ordering::Less)) => bucket.count as f64 / 2.0,
+                    _ => 0.0,
+                }
+            })
+            .sum();
+
+        less / total as f64
+    }
+}
+
+/// HyperLogLog sketch to estimate the number of distinct values in constant memory.
+struct HyperLogLog {
+    registers: Vec<u8>,
+}
+
+impl HyperLogLog {
+    fn new() -> Self {
+        HyperLogLog { registers: vec![0; 1 << HLL_PRECISION] }
+    }
+
+    fn insert(&mut self, value: &DataValue) {
+        // the default hasher has fixed keys, the sketch is the same on every run
+        let mut hasher = DefaultHasher::new();
+        value.hash(&mut hasher);
+        let hash = hasher.finish();
+
+        let index = (hash >> (64 - HLL_PRECISION)) as usize;
+        let rank = ((hash << HLL_PRECISION) | (1 << (HLL_PRECISION - 1))).leading_zeros() + 1;
+        self.registers[index] = self.registers[index].max(rank as u8);
+    }
+
+    fn estimate(&self) -> usize {
+        let m = self.registers.len() as f64;
+        let alpha = 0.7213 / (1.0 + 1.079 / m);
+        let sum: f64 = self.registers.iter().map(|rank| 2f64.powi(-(*rank as i32))).sum();
+        let estimate = alpha * m * m / sum;
+        let zeros = self.registers.iter().filter(|rank| **rank == 0).count();
+
+        // linear counting is more accurate for small cardinalities
+        if estimate <= 2.5 * m && zeros > 0 {
+            (m * (m / zeros as f64).ln()).round() as usize
+        } else {
+            estimate.round() as usize
+        }
+    }
+}
+
+/// Collects the statistics of a column from its values, the histogram is built from a uniform
+/// sample of at most `sample_size` of them, so the memory of a column is bounded.
+pub struct ColumnStatisticsBuilder {
+    ty: LogicalType,
+    null_count: usize,
+    non_null_count: usize,
+    sketch: HyperLogLog,
+    sample: Vec<ValueRef>,
+    sample_size: usize,
+    memory_size: usize,
+    rng: XorShift,
+}
+
+impl ColumnStatisticsBuilder {
+    pub fn new(ty: LogicalType) -> Self {
+        Self::with_sample_size(ty, DEFAULT_SAMPLE_SIZE)
+    }
+
+    pub fn with_sample_size(ty: LogicalType, sample_size: usize) -> Self {
+        ColumnStatisticsBuilder {
+            ty,
+            null_count: 0,
+            non_null_count: 0,
+            sketch: HyperLogLog::new(),
+            sample: vec![],
+            sample_size: sample_size.max(1),
+            memory_size: 0,
+            rng: XorShift::new(),
+        }
+    }
+
+    pub fn append(&mut self, value: &ValueRef) {
+        if value.is_null() {
+            self.null_count += 1;
+            return;
+        }
+        self.sketch.insert(value);
+        self.non_null_count += 1;
+
+        // reservoir sampling: the n-th value replaces a sampled one with probability size / n
+        if self.sample.len() < self.sample_size {
+            self.memory_size += value.memory_size();
+            self.sample.push(value.clone());
+        } else {
+            let index = self.rng.next_u64() % self.non_null_count as u64;
+
+            if let Some(sampled) = self.sample.get_mut(index as usize) {
+                self.memory_size = self.memory_size + value.memory_size() - sampled.memory_size();
+                *sampled = value.clone();
+            }
+        }
+    }
+
+    /// Approximate number of bytes of the sampled values.
+    pub fn memory_size(&self) -> usize {
+        self.memory_size
+    }
+
+    pub fn build(mut self, max_buckets: usize) -> ColumnStatistics {
+        let row_count = self.null_count + self.non_null_count;
+        self.sample.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        ColumnStatistics {
+            null_fraction: if row_count == 0 { 0.0 } else { self.null_count as f64 / row_count as f64 },
+            // the sketch may overestimate a handful of values
+            ndv: self.sketch.estimate().min(self.non_null_count),
+            histogram: Histogram::new(self.ty, &self.sample, self.non_null_count, max_buckets),
+        }
+    }
+}
+
+/// Pseudo-random numbers of the reservoir sampling, with a fixed seed the statistics are the
+/// same on every run.
+struct XorShift(u64);
+
+impl XorShift {
+    fn new() -> Self {
+        XorShift(0x2545_f491_4f6c_dd1d)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use crate::catalog::statistics::{ColumnStatisticsBuilder, Histogram};
+    use crate::types::LogicalType;
+    use crate::types::value::DataValue;
+
+    #[test]
+    fn test_column_statistics() {
+        let mut builder = ColumnStatisticsBuilder::new(LogicalType::Integer);
+        for i in 0..10000 {
+            builder.append(&Arc::new(DataValue::Int32(Some(i % 2000))));
+        }
+        for _ in 0..2500 {
+            builder.append(&Arc::new(DataValue::Int32(None)));
+        }
+        let statistics = builder.build(10);
+
+        assert_eq!(statistics.null_fraction, 0.2);
+        assert!((statistics.ndv as f64 - 2000.0).abs() < 100.0);
+
+        let buckets = statistics.histogram.buckets();
+        assert_eq!(buckets.len(), 10);
+        assert!(buckets.iter().all(|bucket| bucket.count == 1000));
+        assert_eq!(buckets[0].lower, Arc::new(DataValue::Int32(Some(0))));
+        assert_eq!(buckets[9].upper, Arc::new(DataValue::Int32(Some(1999))));
+
+        let fraction = statistics.histogram.less_than_fraction(&DataValue::Int32(Some(500)));
+        assert!((fraction - 0.25).abs() < 0.05);
+
+        let bytes = bincode::serialize(&statistics.histogram).unwrap();
+        assert_eq!(bincode::deserialize::<Histogram>(&bytes).unwrap(), statistics.histogram);
+    }
+
+    #[test]
+    fn test_histogram_keeps_equal_values_together() {
+        let mut builder = ColumnStatisticsBuilder::new(LogicalType::Varchar);
+        for value in ["a", "b", "b", "b", "b", "c"] {
+            builder.append(&Arc::new(DataValue::Utf8(Some(value.to_string()))));
+        }
+        let statistics = builder.build(3);
+        let buckets = statistics.histogram.buckets();
+
+        assert_eq!(statistics.ndv, 3);
+        assert_eq!(buckets.iter().map(|bucket| bucket.count).collect::<Vec<_>>(), vec![5, 1]);
+        assert_eq!(buckets[0].upper, Arc::new(DataValue::Utf8(Some("b".to_string()))));
+    }
+
+    #[test]
+    fn test_histogram_of_sample() {
+        let mut builder = ColumnStatisticsBuilder::with_sample_size(LogicalType::Integer, 1000);
+        for i in 0..100000 {
+            builder.append(&Arc::new(DataValue::Int32(Some(i))));
+        }
+        assert_eq!(builder.sample.len(), 1000);
+
+        let statistics = builder.build(10);
+        let buckets = statistics.histogram.buckets();
+        let total: usize = buckets.iter().map(|bucket| bucket.count).sum();
+
+        // the counts of the sampled buckets are scaled to all the values
+        assert!((total as f64 - 100000.0).abs() < 100.0);
+        let fraction = statistics.histogram.less_than_fraction(&DataValue::Int32(Some(25000)));
+        assert!((fraction - 0.25).abs() < 0.05);
+    }
+}
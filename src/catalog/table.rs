@@ -1,7 +1,7 @@
 use std::collections::BTreeMap;
 use std::sync::Arc;
 
-use crate::catalog::{CatalogError, ColumnCatalog, ColumnRef};
+use crate::catalog::{CatalogError, ColumnCatalog, ColumnRef, TableStatistics};
 use crate::types::ColumnId;
 
 pub type TableName = Arc<String>;
@@ -12,6 +12,8 @@ pub struct TableCatalog {
     /// Mapping from column names to column ids
     column_idxs: BTreeMap<String, ColumnId>,
     pub(crate) columns: BTreeMap<ColumnId, ColumnRef>,
+    /// Collected by `ANALYZE`
+    statistics: Option<TableStatistics>,
 }
 
 impl TableCatalog {
@@ -45,6 +47,27 @@ impl TableCatalog {
             .collect()
     }
 
+    pub fn statistics(&self) -> Option<&TableStatistics> {
+        self.statistics.as_ref()
+    }
+
+    pub(crate) fn set_statistics(&mut self, statistics: TableStatistics) {
+        self.statistics = Some(statistics);
+    }
+
+    /// The catalog of the table referenced by `alias` in a query, whose columns are bound to it.
+    pub(crate) fn with_binding(&self, alias: TableName) -> TableCatalog {
+        let mut table_catalog = self.clone();
+
+        for col in table_catalog.columns.values_mut() {
+            let mut new_col = ColumnCatalog::clone(col);
+            new_col.binding = Some(alias.clone());
+
+            *col = Arc::new(new_col);
+        }
+        table_catalog
+    }
+
     /// Add a column to the table catalog.
     pub(crate) fn add_column(
         &mut self,
@@ -71,6 +94,7 @@ impl TableCatalog {
             name,
             column_idxs: BTreeMap::new(),
             columns: BTreeMap::new(),
+            statistics: None,
         };
 
         for col_catalog in columns.into_iter() {
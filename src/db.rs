@@ -1,14 +1,23 @@
 use std::path::PathBuf;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering::Relaxed;
+use ahash::{HashMap, HashMapExt};
+use parking_lot::Mutex;
 use sqlparser::parser::ParserError;
 
 use crate::binder::{BindError, Binder, BinderContext};
 use crate::execution::ExecutorError;
-use crate::execution::executor::{build, try_collect};
+use crate::execution::executor::cancel::CancellationToken;
+use crate::execution::executor::{build, ExecutionContext, ExecutorConfig, try_collect_with_tracker};
+use crate::catalog::TableName;
+use crate::optimizer::cascades::optimizer::CascadesOptimizer;
 use crate::optimizer::heuristic::batch::HepBatchStrategy;
 use crate::optimizer::heuristic::optimizer::HepOptimizer;
+use crate::optimizer::OptimizerTrace;
 use crate::optimizer::rule::RuleImpl;
 use crate::parser::parse_sql;
 use crate::planner::LogicalPlan;
+use crate::planner::operator::Operator;
 use crate::storage::{Storage, StorageError};
 use crate::storage::kip::KipStorage;
 use crate::storage::memory::MemStorage;
@@ -16,6 +25,35 @@ use crate::types::tuple::Tuple;
 
 pub struct Database<S: Storage> {
     pub storage: S,
+    config: ExecutorConfig,
+    next_query_id: AtomicU64,
+    /// The tokens of the statements being run, by the id of their handles
+    running_queries: Mutex<HashMap<u64, CancellationToken>>,
+}
+
+/// Identifies a statement run by `Database::run_with_handle`, to cancel it while it's running.
+#[derive(Debug, Clone)]
+pub struct QueryHandle {
+    id: u64,
+    cancel: CancellationToken,
+}
+
+impl QueryHandle {
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+/// Unregisters the statement when it ends, or when its future is dropped.
+struct RunningQuery<'a> {
+    id: u64,
+    running_queries: &'a Mutex<HashMap<u64, CancellationToken>>,
+}
+
+impl Drop for RunningQuery<'_> {
+    fn drop(&mut self) {
+        let _ = self.running_queries.lock().remove(&self.id);
+    }
 }
 
 impl Database<MemStorage> {
@@ -23,7 +61,7 @@ impl Database<MemStorage> {
     pub async fn with_mem() -> Self {
         let storage = MemStorage::new();
 
-        Database { storage }
+        Self::from_storage(storage)
     }
 }
 
@@ -32,18 +70,62 @@ impl Database<KipStorage> {
     pub async fn with_kipdb(path: impl Into<PathBuf> + Send) -> Result<Self, DatabaseError> {
         let storage = KipStorage::new(path).await?;
 
-        Ok(Database { storage })
+        Ok(Self::from_storage(storage))
     }
 }
 
 impl<S: Storage> Database<S> {
     /// Create a new Database instance.
     pub fn new(storage: S) -> Result<Self, DatabaseError> {
-        Ok(Database { storage })
+        Ok(Self::from_storage(storage))
+    }
+
+    fn from_storage(storage: S) -> Self {
+        Database {
+            storage,
+            config: Default::default(),
+            next_query_id: AtomicU64::new(0),
+            running_queries: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Replace the settings of the executors, e.g. the memory budget before spilling to disk.
+    pub fn with_config(mut self, config: ExecutorConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    /// A handle for a statement, given to `run_with_handle`.
+    pub fn new_handle(&self) -> QueryHandle {
+        QueryHandle {
+            id: self.next_query_id.fetch_add(1, Relaxed),
+            cancel: CancellationToken::default(),
+        }
+    }
+
+    /// Cancel the running statement of the handle with the id, the statement fails with
+    /// `ExecutorError::Cancelled` and its transaction isn't committed. Returns false when no
+    /// statement of the handle is running.
+    pub fn cancel(&self, id: u64) -> bool {
+        self.running_queries
+            .lock()
+            .get(&id)
+            .map(CancellationToken::cancel)
+            .is_some()
     }
 
     /// Run SQL queries.
     pub async fn run(&self, sql: &str) -> Result<Vec<Tuple>, DatabaseError> {
+        self.run_with_handle(sql, self.new_handle()).await
+    }
+
+    /// Run SQL queries, which can be cancelled by `cancel` with the id of the handle.
+    pub async fn run_with_handle(&self, sql: &str, handle: QueryHandle) -> Result<Vec<Tuple>, DatabaseError> {
+        let QueryHandle { id, cancel } = handle;
+        let cancel = cancel.with_timeout(self.config.statement_timeout);
+        let _ = self.running_queries.lock().insert(id, cancel.clone());
+        let _running = RunningQuery { id, running_queries: &self.running_queries };
+
         // parse
         let stmts = parse_sql(sql)?;
 
@@ -61,26 +143,126 @@ impl<S: Storage> Database<S> {
         ///     Limit(1)
         ///       Project(a,b)
         let source_plan = binder.bind(&stmts[0]).await?;
-        // println!("source_plan plan: {:#?}", source_plan);
+        let cascades_optimizer = self.cascades_optimizer(&source_plan).await;
+        let mut best_plan = Self::optimize(source_plan, cascades_optimizer);
+        best_plan.resolve_column_refs().map_err(BindError::from)?;
+
+        let ctx = ExecutionContext {
+            cancel,
+            ..ExecutionContext::new(self.config.clone())
+        };
+        let mut stream = build(best_plan, &self.storage, &ctx);
+
+        Ok(try_collect_with_tracker(&mut stream, ctx.memory).await?)
+    }
+
+    /// The cost-based optimizer with what is known of the tables scanned by the plan.
+    async fn cascades_optimizer(&self, plan: &LogicalPlan) -> CascadesOptimizer {
+        let mut table_names = Vec::new();
+        plan_table_names(plan, &mut table_names);
+
+        let mut table_rows = HashMap::new();
+        let mut statistics = HashMap::new();
+        for table_name in table_names {
+            if let Some(rows) = self.storage.table_row_count(&table_name).await {
+                let _ = table_rows.insert(table_name.clone(), rows);
+            }
+            if let Some(table_statistics) = self.storage
+                .table_catalog(&table_name)
+                .await
+                .and_then(|catalog| catalog.statistics())
+            {
+                let _ = statistics.insert(table_name, table_statistics.clone());
+            }
+        }
+
+        CascadesOptimizer::new(table_rows, statistics)
+    }
+
+    /// Optimize the plan, for `EXPLAIN` the explained plan is optimized instead, and with
+    /// `VERBOSE` the plan after each step is recorded.
+    fn optimize(source_plan: LogicalPlan, cascades_optimizer: CascadesOptimizer) -> LogicalPlan {
+        let LogicalPlan { operator: Operator::Explain(mut op), mut childrens } = source_plan else {
+            return Self::optimize_with_trace(source_plan, cascades_optimizer, false).0;
+        };
+        let source_plan = childrens.remove(0);
+
+        let best_plan = if op.verbose {
+            let (best_plan, traces) = Self::optimize_with_trace(source_plan.clone(), cascades_optimizer, true);
+
+            op.source_plan = Some(Box::new(source_plan));
+            op.traces = traces;
+            best_plan
+        } else {
+            Self::optimize_with_trace(source_plan, cascades_optimizer, false).0
+        };
+
+        LogicalPlan {
+            operator: Operator::Explain(op),
+            childrens: vec![best_plan],
+        }
+    }
+
+    /// The heuristic rewrites, with the joins reordered by cost before the columns are pruned,
+    /// which would put projections between the joins.
+    fn optimize_with_trace(
+        source_plan: LogicalPlan,
+        mut cascades_optimizer: CascadesOptimizer,
+        trace: bool
+    ) -> (LogicalPlan, Vec<OptimizerTrace>) {
+        let mut traces = Vec::new();
 
-        let best_plan = Self::default_optimizer(source_plan)
-            .find_best();
-        // println!("best_plan plan: {:#?}", best_plan);
+        let plan = Self::hep_optimize(Self::pushdown_optimizer(source_plan), trace, &mut traces);
 
-        let mut stream = build(best_plan, &self.storage);
+        let plan = cascades_optimizer.find_best(plan);
+        if trace {
+            traces.push(OptimizerTrace {
+                name: "Join reorder".to_string(),
+                fired_rules: cascades_optimizer.fired_rules(),
+                plan: plan.clone(),
+            });
+        }
 
-        Ok(try_collect(&mut stream).await?)
+        let plan = Self::hep_optimize(Self::pruning_optimizer(plan), trace, &mut traces);
+
+        (plan, traces)
     }
 
-    fn default_optimizer(source_plan: LogicalPlan) -> HepOptimizer {
+    fn hep_optimize(mut optimizer: HepOptimizer, trace: bool, traces: &mut Vec<OptimizerTrace>) -> LogicalPlan {
+        if !trace {
+            return optimizer.find_best();
+        }
+        let (plan, batch_traces) = optimizer.find_best_with_trace();
+        traces.extend(batch_traces);
+
+        plan
+    }
+
+    fn pushdown_optimizer(source_plan: LogicalPlan) -> HepOptimizer {
         HepOptimizer::new(source_plan)
+            .batch(
+                "Simplification".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![
+                    RuleImpl::ConstantFolding,
+                    RuleImpl::PruneFilters,
+                ]
+            )
             .batch(
                 "Predicate pushdown".to_string(),
                 HepBatchStrategy::fix_point_topdown(10),
                 vec![
-                    RuleImpl::PushPredicateThroughJoin
+                    RuleImpl::SimplifyOuterJoin,
+                    RuleImpl::PushPredicateThroughJoin,
+                    RuleImpl::PushPredicateThroughNonJoin,
+                    RuleImpl::PushPredicateIntoScan,
                 ]
             )
+            .batch(
+                "Join elimination".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![RuleImpl::EliminateLeftJoin]
+            )
             .batch(
                 "Limit pushdown".to_string(),
                 HepBatchStrategy::fix_point_topdown(10),
@@ -88,9 +270,14 @@ impl<S: Storage> Database<S> {
                     RuleImpl::LimitProjectTranspose,
                     RuleImpl::PushLimitThroughJoin,
                     RuleImpl::PushLimitIntoTableScan,
+                    RuleImpl::PushLimitIntoSort,
                     RuleImpl::EliminateLimits,
                 ],
             )
+    }
+
+    fn pruning_optimizer(plan: LogicalPlan) -> HepOptimizer {
+        HepOptimizer::new(plan)
             .batch(
                 "Column pruning".to_string(),
                 HepBatchStrategy::fix_point_topdown(10),
@@ -110,6 +297,17 @@ impl<S: Storage> Database<S> {
     }
 }
 
+fn plan_table_names(plan: &LogicalPlan, table_names: &mut Vec<TableName>) {
+    if let Operator::Scan(op) = &plan.operator {
+        if !table_names.contains(&op.table_name) {
+            table_names.push(op.table_name.clone());
+        }
+    }
+    for child in plan.childrens.iter() {
+        plan_table_names(child, table_names);
+    }
+}
+
 #[derive(thiserror::Error, Debug)]
 pub enum DatabaseError {
     #[error("parse error: {0}")]
@@ -143,12 +341,19 @@ pub enum DatabaseError {
 #[cfg(test)]
 mod test {
     use std::sync::Arc;
+    use std::time::Duration;
+    use kip_db::kernel::Storage as _;
     use tempfile::TempDir;
+    use itertools::Itertools;
     use crate::catalog::{ColumnCatalog, ColumnDesc, TableName};
+    use crate::binder::BindError;
     use crate::db::{Database, DatabaseError};
     use crate::storage::{Storage, StorageError};
+    use crate::types::errors::TypeError;
     use crate::types::LogicalType;
-    use crate::types::tuple::create_table;
+    use crate::types::tuple::{create_table, Tuple};
+    use crate::execution::executor::ExecutorConfig;
+    use crate::execution::ExecutorError;
 
     async fn build_table(storage: &impl Storage) -> Result<TableName, StorageError> {
         let columns = vec![
@@ -211,6 +416,18 @@ mod test {
         let tuples_inner_join = kipsql.run("select * from t1 inner join t2 on a = c").await?;
         println!("{}", create_table(&tuples_inner_join));
 
+        println!("comma join:");
+        let tuples_comma_join = kipsql.run("select * from t1, t2 where k = d").await?;
+        println!("{}", create_table(&tuples_comma_join));
+
+        println!("cross join:");
+        let tuples_cross_join = kipsql.run("select * from t1, t2").await?;
+        println!("{}", create_table(&tuples_cross_join));
+
+        println!("non-equi left join:");
+        let tuples_non_equi_join = kipsql.run("select * from t1 left join t2 on a > c").await?;
+        println!("{}", create_table(&tuples_non_equi_join));
+
         println!("left join:");
         let tuples_left_join = kipsql.run("select * from t1 left join t2 on a = c").await?;
         println!("{}", create_table(&tuples_left_join));
@@ -223,6 +440,10 @@ mod test {
         let tuples_full_join = kipsql.run("select * from t1 full join t2 on a = c").await?;
         println!("{}", create_table(&tuples_full_join));
 
+        println!("join using:");
+        let tuples_join_using = kipsql.run("select * from t1 join t2 using (a)").await;
+        assert!(tuples_join_using.is_err());
+
         println!("count agg:");
         let tuples_count_agg = kipsql.run("select count(d) from t2").await?;
         println!("{}", create_table(&tuples_count_agg));
@@ -251,6 +472,28 @@ mod test {
         let tuples_min_max_agg = kipsql.run("select min(d), max(d) from t2").await?;
         println!("{}", create_table(&tuples_min_max_agg));
 
+        println!("filter agg:");
+        let tuples_filter_agg = kipsql.run("select count(*) filter (where c > 1), sum(d) filter (where c = 1) from t2").await?;
+        println!("{}", create_table(&tuples_filter_agg));
+        assert_eq!(tuples_filter_agg.len(), 1);
+        assert_eq!(tuples_filter_agg[0].values[0].to_string(), "1");
+        assert_eq!(tuples_filter_agg[0].values[1].to_string(), "2");
+
+        println!("string_agg with order by:");
+        let tuples_string_agg = kipsql.run("select string_agg(a, ',' order by a desc) from t1").await?;
+        println!("{}", create_table(&tuples_string_agg));
+        assert_eq!(tuples_string_agg.len(), 1);
+        assert_eq!(tuples_string_agg[0].values[0].to_string(), "5,-1,-99");
+
+        println!("string_agg with null delimiter:");
+        let tuples_string_agg_null = kipsql.run("select string_agg(a, null order by a desc) from t1").await?;
+        println!("{}", create_table(&tuples_string_agg_null));
+        assert_eq!(tuples_string_agg_null[0].values[0].to_string(), "5-1-99");
+        assert!(matches!(
+            kipsql.run("select string_agg(a, b) from t1").await,
+            Err(DatabaseError::Bind(BindError::AggMiss(_)))
+        ));
+
         println!("group agg:");
         let tuples_group_agg = kipsql.run("select c, max(d) from t2 group by c having c = 1").await?;
         println!("{}", create_table(&tuples_group_agg));
@@ -303,4 +546,954 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_join_using_and_lateral() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t3 (id int primary key, v int)").await?;
+        let _ = kipsql.run("create table t4 (id int primary key, w int)").await?;
+        let _ = kipsql.run("create table t5 (oid int primary key, uid int, amount int)").await?;
+        let _ = kipsql.run("insert into t3 (id, v) values (1, 10), (2, 20), (3, 30)").await?;
+        let _ = kipsql.run("insert into t4 (id, w) values (2, 200), (3, 300), (4, 400)").await?;
+        let _ = kipsql.run("insert into t5 (oid, uid, amount) values (1, 1, 5), (2, 1, 7), (3, 2, 1), (4, 1, 3)").await?;
+
+        println!("inner join using:");
+        let tuples_inner_using = kipsql.run("select * from t3 join t4 using (id)").await?;
+        println!("{}", create_table(&tuples_inner_using));
+        assert_eq!(tuples_inner_using.len(), 2);
+        assert_eq!(tuples_inner_using[0].columns.len(), 3);
+
+        println!("full join using:");
+        let tuples_full_using = kipsql.run("select * from t3 full join t4 using (id)").await?;
+        println!("{}", create_table(&tuples_full_using));
+        assert_eq!(tuples_full_using.len(), 4);
+        assert!(tuples_full_using.iter().all(|tuple| !tuple.values[0].is_null()));
+
+        println!("natural join:");
+        let tuples_natural = kipsql.run("select * from t3 natural join t4").await?;
+        println!("{}", create_table(&tuples_natural));
+        assert_eq!(tuples_natural.len(), 2);
+
+        println!("join using chain:");
+        let _ = kipsql.run("create table t6 (w int primary key, x int, id int)").await?;
+        let _ = kipsql.run("insert into t6 (w, x, id) values (200, 7, 2), (300, 9, 1)").await?;
+        let tuples_using_chain = kipsql.run(
+            "select t3.id, t6.w, x from t3 join t4 using (id) join t6 using (w) order by x asc"
+        ).await?;
+        println!("{}", create_table(&tuples_using_chain));
+        let rows = tuples_using_chain
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["2", "200", "7"], vec!["3", "300", "9"]]);
+
+        println!("natural join chain:");
+        // `id` is merged by the first join, so the second one joins on `id` and `w`
+        let tuples_natural_chain = kipsql.run("select t3.id, x from t3 natural join t4 natural join t6").await?;
+        println!("{}", create_table(&tuples_natural_chain));
+        let rows = tuples_natural_chain
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["2", "7"]]);
+
+        assert!(matches!(
+            kipsql.run("select * from t3 join t4 on t3.id = t4.id join t6 using (id)").await,
+            Err(DatabaseError::Bind(BindError::AmbiguousColumn(_)))
+        ));
+        assert!(matches!(
+            kipsql.run("select * from t3 join t4 using (id) join t6 using (v)").await,
+            Err(DatabaseError::Bind(BindError::InvalidColumn(_)))
+        ));
+
+                println!("left join using with merged column:");
+        let tuples_left_using = kipsql.run("select id, w from t3 left join t4 using (id)").await?;
+        println!("{}", create_table(&tuples_left_using));
+        assert_eq!(tuples_left_using.len(), 3);
+
+        println!("table alias:");
+        let tuples_alias = kipsql.run("select x.v from t3 as x where x.id = 1").await?;
+        println!("{}", create_table(&tuples_alias));
+        assert_eq!(tuples_alias.len(), 1);
+
+        println!("derived table:");
+        let tuples_derived = kipsql.run("select s.total from (select v + 1 as total from t3) as s").await?;
+        println!("{}", create_table(&tuples_derived));
+        assert_eq!(tuples_derived.len(), 3);
+
+        println!("lateral top-n:");
+        let tuples_lateral = kipsql.run(
+            "select t3.id, top.amount from t3, lateral (select amount from t5 where uid = t3.id order by amount desc limit 2) as top"
+        ).await?;
+        println!("{}", create_table(&tuples_lateral));
+        assert_eq!(tuples_lateral.len(), 3);
+
+        // the correlated predicate is pushed into the scan of the subquery, as a constant
+        let tuples_lateral_explain = kipsql.run(
+            "explain select t3.id, top.amount from t3, lateral (select amount from t5 where uid = t3.id order by amount desc limit 2) as top"
+        ).await?;
+        let lines = tuples_lateral_explain
+            .iter()
+            .map(|tuple| tuple.values[0].to_string())
+            .collect::<Vec<_>>();
+        println!("{}", lines.join("\n"));
+        assert!(
+            lines.iter().any(|line| line.trim_start().starts_with("Scan t5") && line.contains("where [(t5.uid = t3.id)]")),
+            "{:?}",
+            lines
+        );
+
+                println!("left join lateral:");
+        let tuples_left_lateral = kipsql.run(
+            "select t3.id, top.amount from t3 left join lateral (select amount from t5 where uid = t3.id order by amount desc limit 1) as top on true"
+        ).await?;
+        println!("{}", create_table(&tuples_left_lateral));
+        assert_eq!(tuples_left_lateral.len(), 3);
+        assert!(tuples_left_lateral[2].values[1].is_null());
+
+        println!("lateral with repeated correlated values:");
+        let tuples_repeated_lateral = kipsql.run(
+            "select t5.oid, u.v from t5, lateral (select v from t3 where id = t5.uid) as u"
+        ).await?;
+        println!("{}", create_table(&tuples_repeated_lateral));
+        let rows = tuples_repeated_lateral
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["1", "10"], vec!["2", "10"], vec!["3", "20"], vec!["4", "10"]]);
+
+        println!("lateral followed by join on:");
+        let tuples_lateral_join = kipsql.run(
+            "select t3.id, top.amount, t4.w from t3, lateral (select amount, uid from t5 where uid = t3.id order by amount desc limit 1) as top join t4 on top.uid = t4.id"
+        ).await?;
+        println!("{}", create_table(&tuples_lateral_join));
+        let rows = tuples_lateral_join
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["2", "1", "200"]]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_duplicate_column_names() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t3 (id int primary key, v int)").await?;
+        let _ = kipsql.run("create table t7 (id int primary key, v int)").await?;
+        let _ = kipsql.run("insert into t3 (id, v) values (1, 2), (2, 3), (3, 1)").await?;
+        let _ = kipsql.run("insert into t7 (id, v) values (1, 100), (2, 200), (3, 300)").await?;
+
+        println!("join on the columns with the same names:");
+        let tuples = kipsql.run(
+            "select t7.id, t3.id, t7.v, t3.v from t3 join t7 on t3.v = t7.id where t7.v > t3.id * 100 order by t7.id asc"
+        ).await?;
+        println!("{}", create_table(&tuples));
+        let rows = tuples
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["2", "1", "200", "2"], vec!["3", "2", "300", "3"]]);
+
+        println!("self join:");
+        let tuples = kipsql.run("select a.id, b.id, b.v from t3 a join t3 b on a.v = b.id order by a.id asc").await?;
+        println!("{}", create_table(&tuples));
+        let rows = tuples
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["1", "2", "3"], vec!["2", "3", "1"], vec!["3", "1", "2"]]);
+
+        println!("join with a derived table of the same table:");
+        let tuples = kipsql.run(
+            "select t3.v, s.v from (select id, v from t3 where id > 1) s join t3 on s.id = t3.v order by t3.v asc"
+        ).await?;
+        println!("{}", create_table(&tuples));
+        let rows = tuples
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec![vec!["2", "3"], vec!["3", "1"]]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_empty_side() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t3 (id int primary key, v int)").await?;
+        let _ = kipsql.run("create table t7 (id int primary key, v int)").await?;
+        let _ = kipsql.run("insert into t3 (id, v) values (1, 2), (2, 3)").await?;
+
+        // the nested loop, merge and hash joins, t7 is empty
+        for sql in [
+            "select t3.id, t7.id from t3 left join t7 on t3.id > t7.id",
+            "select t3.id, t7.id from t3 left join t7 on t3.id = t7.id",
+            "select t3.id, t7.id from t7 right join t3 on t7.id = t3.id",
+            "select t3.id, t7.id from t3 full join t7 on t3.v = t7.v",
+            "select t3.id, t7.id from t7 full join t3 on t7.v = t3.v",
+        ] {
+            let tuples = kipsql.run(sql).await?;
+            let rows = tuples
+                .iter()
+                .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>())
+                .sorted()
+                .collect::<Vec<_>>();
+
+            assert_eq!(rows, vec![vec!["1", "null"], vec!["2", "null"]], "{}", sql);
+        }
+        let tuples = kipsql.run("select * from t3 left join t7 on t3.v = t7.v").await?;
+        assert!(tuples.iter().all(|tuple| tuple.values.len() == 4 && tuple.columns.len() == 4));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_commuted_outer_join_columns() -> Result<(), DatabaseError> {
+        let kipsql = Database::with_mem().await;
+        let _ = kipsql.run("create table t3 (id int primary key, v int)").await?;
+        let _ = kipsql.run("create table t7 (id int primary key, w int)").await?;
+        let _ = kipsql.run("insert into t3 (id, v) values (1, 10), (2, 20), (3, 30), (4, 40)").await?;
+        let _ = kipsql.run("insert into t7 (id, w) values (1, 100)").await?;
+
+        let plan = kipsql.run("explain select * from t3 left join t7 on t3.id = t7.id").await?;
+        println!("{}", create_table(&plan));
+        assert!(plan.iter().any(|tuple| tuple.values[0].to_string().contains("Right Join")));
+
+        let tuples = kipsql.run("select * from t3 left join t7 on t3.id = t7.id").await?;
+        println!("{}", create_table(&tuples));
+        assert_eq!(
+            tuples[0].columns.iter().map(|column| (column.table_name.as_ref().unwrap().to_string(), column.name.clone())).collect_vec(),
+            vec![("t3", "id"), ("t3", "v"), ("t7", "id"), ("t7", "w")]
+                .into_iter()
+                .map(|(table, column)| (table.to_string(), column.to_string()))
+                .collect_vec()
+        );
+        assert_eq!(
+            tuples.iter().map(|tuple| tuple.values.iter().map(ToString::to_string).collect_vec()).sorted().collect_vec(),
+            vec![
+                vec!["1", "10", "1", "100"],
+                vec!["2", "20", "null", "null"],
+                vec!["3", "30", "null", "null"],
+                vec!["4", "40", "null", "null"],
+            ]
+        );
+
+        // the row counts of the tables are unknown before ANALYZE
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t3 (id int primary key, v int)").await?;
+        let _ = kipsql.run("create table t7 (id int primary key, w int)").await?;
+        let _ = kipsql.run("insert into t3 (id, v) values (1, 10), (2, 20), (3, 30), (4, 40)").await?;
+        let _ = kipsql.run("insert into t7 (id, w) values (1, 100)").await?;
+
+        let plan = kipsql.run("explain select * from t3 left join t7 on t3.id = t7.id").await?;
+        assert!(plan.iter().any(|tuple| tuple.values[0].to_string().contains("Left Join")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_in_and_exists_subquery() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t3 (id int primary key, v int)").await?;
+        let _ = kipsql.run("create table t5 (oid int primary key, uid int)").await?;
+        let _ = kipsql.run("create table t6 (id int primary key, x int null)").await?;
+        let _ = kipsql.run("insert into t3 (id, v) values (1, 10), (2, 20), (3, 30)").await?;
+        let _ = kipsql.run("insert into t5 (oid, uid) values (1, 1), (2, 1), (3, 2)").await?;
+        let _ = kipsql.run("insert into t6 (id, x) values (1, null), (2, 2)").await?;
+
+        println!("in subquery:");
+        let tuples_in = kipsql.run("select * from t3 where id in (select uid from t5)").await?;
+        println!("{}", create_table(&tuples_in));
+        assert_eq!(tuples_in.len(), 2);
+
+        println!("not in subquery:");
+        let tuples_not_in = kipsql.run("select * from t3 where v > 1 and id not in (select uid from t5)").await?;
+        println!("{}", create_table(&tuples_not_in));
+        assert_eq!(tuples_not_in.len(), 1);
+
+        println!("not in subquery with null:");
+        let tuples_not_in_null = kipsql.run("select * from t3 where id not in (select x from t6)").await?;
+        println!("{}", create_table(&tuples_not_in_null));
+        assert!(tuples_not_in_null.is_empty());
+
+        println!("exists:");
+        let tuples_exists = kipsql.run("select * from t3 where exists (select x from t6)").await?;
+        println!("{}", create_table(&tuples_exists));
+        assert_eq!(tuples_exists.len(), 3);
+
+        println!("not exists:");
+        let tuples_not_exists = kipsql.run("select * from t3 where not exists (select x from t6 where x > 5)").await?;
+        println!("{}", create_table(&tuples_not_exists));
+        assert_eq!(tuples_not_exists.len(), 3);
+
+        for sql in [
+            "select * from t3 where id in (select uid from t5) or v > 1",
+            "select * from t3 where v > 1 or not exists (select x from t6)",
+        ] {
+            assert!(
+                matches!(kipsql.run(sql).await, Err(DatabaseError::Bind(BindError::UnsupportedStmt(_)))),
+                "{}",
+                sql
+            );
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_null_predicates() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t8 (id int primary key, x int null, y int null, b boolean null)").await?;
+        let _ = kipsql.run(
+            "insert into t8 (id, x, y, b) values (1, null, null, true), (2, 1, null, false), (3, 1, 1, null), (4, 2, 1, true)"
+        ).await?;
+
+        let ids = |tuples: Vec<Tuple>| tuples
+            .iter()
+            .map(|tuple| tuple.values[0].to_string())
+            .collect::<Vec<_>>();
+        let cases = [
+            ("x is null", vec!["1"]),
+            ("x is not null", vec!["2", "3", "4"]),
+            ("not (x is null)", vec!["2", "3", "4"]),
+            ("x is distinct from y", vec!["2", "4"]),
+            ("x is not distinct from y", vec!["1", "3"]),
+            ("b is true", vec!["1", "4"]),
+            ("b is not true", vec!["2", "3"]),
+            ("b is false", vec!["2"]),
+            ("b is not false", vec!["1", "3", "4"]),
+            ("b is unknown", vec!["3"]),
+        ];
+
+        for (predicate, expected) in cases {
+            let tuples = kipsql.run(&format!("select id from t8 where {} order by id asc", predicate)).await?;
+            println!("{}:\n{}", predicate, create_table(&tuples));
+            assert_eq!(ids(tuples), expected, "{}", predicate);
+        }
+
+        let tuples = kipsql.run("select x is null, y is not null from t8 order by id asc").await?;
+        println!("{}", create_table(&tuples));
+        assert_eq!(tuples[0].values[0].to_string(), "true");
+        assert_eq!(tuples[0].values[1].to_string(), "false");
+        assert_eq!(tuples[3].values[1].to_string(), "true");
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_null_semantics() -> Result<(), DatabaseError> {
+        let queries = [
+            // arithmetic and comparisons with NULL are NULL, which filters don't accept
+            ("select id, a + b, a * 2 from t9", vec!["1,null,null", "2,null,2", "3,3,2", "4,null,null"]),
+            ("select id from t9 where a = 1", vec!["2", "3"]),
+            ("select id from t9 where a <> 1", vec![]),
+            ("select id from t9 where not (a = 1)", vec![]),
+            ("select id from t9 where a = null", vec![]),
+            ("select id from t9 where null", vec![]),
+            // three-valued logic
+            ("select id from t9 where a = 1 or b = 2", vec!["2", "3", "4"]),
+            ("select id from t9 where a = 1 or null", vec!["2", "3"]),
+            ("select id from t9 where not (a = 2 and b = 2)", vec!["2", "3"]),
+            ("select id from t9 where not (a = 1 or b = 1)", vec![]),
+            // aggregates skip NULLs, except count(*)
+            ("select count(*), count(a), count(b), sum(a), max(b) from t9", vec!["4,2,2,2,2"]),
+            ("select count(*), count(a), sum(a) from t9 where id = 1", vec!["1,0,null"]),
+            ("select a, count(*), count(b) from t9 group by a", vec!["1,2,1", "null,2,1"]),
+            // NULL keys never match
+            ("select t9.id, t10.id from t9 join t10 on t9.a = t10.a", vec!["2,2", "3,2"]),
+            ("select t9.id, t10.id from t9 left join t10 on t9.a = t10.a", vec!["1,null", "2,2", "3,2", "4,null"]),
+            ("select t9.id, t10.id from t9 full join t10 on t9.a = t10.a", vec!["1,null", "2,2", "3,2", "4,null", "null,1"]),
+            ("select id from t9 where a in (select a from t10)", vec!["2", "3"]),
+        ];
+
+        for vectorized in [false, true] {
+            let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+            let kipsql = Database::with_kipdb(temp_dir.path()).await?
+                .with_config(ExecutorConfig { vectorized, batch_size: 2, ..Default::default() });
+            let _ = kipsql.run("create table t9 (id int primary key, a int null, b int null)").await?;
+            let _ = kipsql.run("create table t10 (id int primary key, a int null)").await?;
+            let _ = kipsql.run("insert into t9 (id, a, b) values (1, null, null), (2, 1, null), (3, 1, 2), (4, null, 2)").await?;
+            let _ = kipsql.run("insert into t10 (id, a) values (1, null), (2, 1)").await?;
+
+            for (sql, expected) in queries.iter() {
+                let tuples = kipsql.run(sql).await?;
+                println!("{}\n{}", sql, create_table(&tuples));
+                let mut rows = tuples
+                    .iter()
+                    .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+                    .collect::<Vec<_>>();
+                rows.sort();
+                assert_eq!(&rows, expected, "{} (vectorized: {})", sql, vectorized);
+            }
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_checked_arithmetic() -> Result<(), DatabaseError> {
+        for vectorized in [false, true] {
+            let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+            let kipsql = Database::with_kipdb(temp_dir.path()).await?
+                .with_config(ExecutorConfig { vectorized, ..Default::default() });
+            let _ = kipsql.run("create table t11 (id int primary key, a int, b int)").await?;
+            let _ = kipsql.run("insert into t11 (id, a, b) values (1, 2147483647, 0), (2, 7, -2)").await?;
+
+            let tuples = kipsql.run("select a / b, a % b, a * b from t11 where id = 2").await?;
+            println!("{}", create_table(&tuples));
+            // truncated toward zero
+            assert_eq!(tuples[0].values[0].to_string(), "-3");
+            assert_eq!(tuples[0].values[1].to_string(), "1");
+            assert_eq!(tuples[0].values[2].to_string(), "-14");
+
+            let is_error = |result: Result<Vec<Tuple>, DatabaseError>, expected: fn(&TypeError) -> bool| matches!(
+                result,
+                Err(DatabaseError::ExecutorError(ExecutorError::TypeError(err))) if expected(&err)
+            );
+            assert!(is_error(kipsql.run("select a + 1 from t11 where id = 1").await, |err| matches!(err, TypeError::Overflow)));
+            assert!(is_error(kipsql.run("select sum(a) from t11").await, |err| matches!(err, TypeError::Overflow)));
+            assert!(is_error(kipsql.run("select a / b from t11 where id = 1").await, |err| matches!(err, TypeError::DivisionByZero)));
+            assert!(is_error(kipsql.run("select a % b from t11 where id = 1").await, |err| matches!(err, TypeError::DivisionByZero)));
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_decimal() -> Result<(), DatabaseError> {
+        let queries = [
+            ("select price from t12", vec!["19.99", "0.10", "-5.50", "null"]),
+            ("select price * qty, price + 0.2, price / 4 from t12 where id = 2", vec!["1.00,0.30,0.025000"]),
+            ("select id from t12 where price > 10 or price = 0.1", vec!["1", "2"]),
+            ("select sum(price), avg(price), min(price), max(price) from t12", vec!["14.59,4.863333,-5.50,19.99"]),
+            ("select cast(price as int), cast(price as varchar) from t12 where id = 3", vec!["-6,-5.50"]),
+            // the key encoding keeps the order of the values
+            ("select k from t13", vec!["-10.00", "-1.50", "0.00", "2.25"]),
+            // the literals stay floats for the float columns
+            ("select id from t14 where f > 1.5", vec!["2"]),
+            ("select f + 0.5, f = 2.5 from t14", vec!["2,false", "3,true"]),
+        ];
+
+        for vectorized in [false, true] {
+            let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+            let kipsql = Database::with_kipdb(temp_dir.path()).await?
+                .with_config(ExecutorConfig { vectorized, batch_size: 2, ..Default::default() });
+            let _ = kipsql.run("create table t12 (id int primary key, price decimal(10, 2) null, qty int)").await?;
+            let _ = kipsql.run("create table t13 (k numeric(5, 2) primary key, v int)").await?;
+            let _ = kipsql.run("insert into t12 (id, price, qty) values (1, 19.99, 3), (2, 0.1, 10), (3, -5.5, 1), (4, null, 2)").await?;
+            let _ = kipsql.run("insert into t13 (k, v) values (2.25, 1), (-1.5, 2), (0, 3), (-10, 4)").await?;
+            let _ = kipsql.run("create table t14 (id int primary key, f float)").await?;
+            let _ = kipsql.run("insert into t14 (id, f) values (1, 1.5), (2, 2.5)").await?;
+
+            for (sql, expected) in queries.iter() {
+                let tuples = kipsql.run(sql).await?;
+                println!("{}\n{}", sql, create_table(&tuples));
+                let rows = tuples
+                    .iter()
+                    .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+                    .collect::<Vec<_>>();
+                assert_eq!(&rows, expected, "{} (vectorized: {})", sql, vectorized);
+            }
+
+            assert!(matches!(
+                kipsql.run("insert into t12 (id, price, qty) values (5, 123456789.99, 1)").await,
+                Err(DatabaseError::Bind(BindError::TypeError(TypeError::Overflow)))
+            ));
+            let _ = kipsql.run("insert into t12 (id, price, qty) values (5, cast('1.5' as decimal(4, 1)), 1)").await?;
+            let tuples = kipsql.run("select price from t12 where id = 5").await?;
+            assert_eq!(tuples[0].values[0].to_string(), "1.50");
+
+            let _ = kipsql.run("update t12 set price = 0.3 where id = 2").await?;
+            let tuples = kipsql.run("select price from t12 where id = 2").await?;
+            assert_eq!(tuples[0].values[0].to_string(), "0.30");
+
+            let tuples = kipsql.run("select f + 0.5 from t14 where id = 1").await?;
+            assert_eq!(tuples[0].columns[0].datatype(), &LogicalType::Float);
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t1 (a int primary key, b int)").await?;
+        let _ = kipsql.run("create table t2 (c int primary key, d int)").await?;
+        let _ = kipsql.run("insert into t1 (a, b) values (1, 10), (2, 20)").await?;
+
+        let plan_text = |tuples: &[Tuple]| tuples
+            .iter()
+            .map(|tuple| tuple.values[0].to_string())
+            .collect::<Vec<_>>();
+
+        println!("explain:");
+        let tuples_explain = kipsql.run("explain select a, b + 1 from t1 where a > 1 limit 1").await?;
+        println!("{}", create_table(&tuples_explain));
+        assert_eq!(
+            plan_text(&tuples_explain),
+            vec![
+                "Project [t1.a, (t1.b + 1)]",
+                "  Scan t1 [t1.a, t1.b] where [(t1.a > 1)] limit 1 offset 0",
+            ]
+        );
+
+        println!("explain verbose:");
+        let tuples_verbose = kipsql.run("explain verbose select * from t1 join t2 on a = c where d > 1 limit 1").await?;
+        println!("{}", create_table(&tuples_verbose));
+        let lines = plan_text(&tuples_verbose);
+        assert_eq!(lines[0], "== Source Plan ==");
+        assert!(lines.contains(&"== Predicate pushdown: PushPredicateThroughJoin, PushPredicateIntoScan ==".to_string()));
+        assert!(lines.iter().any(|line| line.starts_with("== Limit pushdown: ")));
+        assert!(lines.contains(&"== Combine operators: unchanged ==".to_string()));
+        assert!(lines.contains(&"    Inner Join on [t1.a = t2.c]".to_string()));
+
+        // the always false filter is replaced with an empty relation
+        let tuples_empty = kipsql.run("explain select a from t1 where a > 0 and 1 > 1 + 1").await?;
+        assert_eq!(plan_text(&tuples_empty)[1], "  Values [t1.a, t1.b], 0 rows");
+        assert!(kipsql.run("select a from t1 where a > 0 and 1 > 1 + 1").await?.is_empty());
+        assert_eq!(kipsql.run("select a from t1 where 0 + 1 < a or false").await?.len(), 1);
+
+        // the explained statement is not executed
+        let _ = kipsql.run("explain insert into t1 (a, b) values (3, 30)").await?;
+        assert_eq!(kipsql.run("select * from t1").await?.len(), 2);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_reorder() -> Result<(), DatabaseError> {
+        let kipsql = Database::with_mem().await;
+        let _ = kipsql.run("create table orders (oid int primary key, cid int, pid int)").await?;
+        let _ = kipsql.run("create table customers (cid int primary key, region int)").await?;
+        let _ = kipsql.run("create table regions (rid int primary key, name varchar)").await?;
+        let _ = kipsql.run(
+            "insert into orders (oid, cid, pid) values (1, 1, 1), (2, 1, 2), (3, 2, 1), (4, 3, 3), (5, 2, 2), (6, 3, 1)"
+        ).await?;
+        let _ = kipsql.run("insert into customers (cid, region) values (1, 1), (2, 2), (3, 1)").await?;
+        let _ = kipsql.run("insert into regions (rid, name) values (1, 'east'), (2, 'west')").await?;
+
+        let plan_text = |tuples: &[Tuple]| tuples
+            .iter()
+            .map(|tuple| tuple.values[0].to_string())
+            .collect::<Vec<_>>();
+        let sql = "select oid, name from orders join customers on orders.cid = customers.cid \
+            join regions on customers.region = regions.rid";
+
+        println!("join reorder:");
+        let tuples = kipsql.run(sql).await?;
+        println!("{}", create_table(&tuples));
+        assert_eq!(tuples.len(), 6);
+
+        println!("explain join reorder:");
+        let tuples = kipsql.run(&format!("explain verbose {}", sql)).await?;
+        println!("{}", create_table(&tuples));
+        let lines = plan_text(&tuples);
+        assert!(lines.contains(&"== Join reorder: JoinReorder ==".to_string()));
+
+        // the smaller tables are joined first, the largest one is probed last
+        let scans = lines
+            .iter()
+            .skip_while(|line| !line.starts_with("== Combine operators:"))
+            .filter_map(|line| line.trim_start().strip_prefix("Scan "))
+            .map(|line| line.split(' ').next().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(scans, vec!["regions", "customers", "orders"]);
+        // the equality of the join filter becomes a key of the reordered join
+        assert!(lines.contains(&"    Inner Join on [regions.rid = customers.region]".to_string()));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_join_simplification() -> Result<(), DatabaseError> {
+        let kipsql = Database::with_mem().await;
+        let _ = kipsql.run("create table t1 (a int primary key, b int)").await?;
+        let _ = kipsql.run("create table t2 (c int primary key, d int)").await?;
+        let _ = kipsql.run("insert into t1 (a, b) values (1, 10), (2, 20), (3, 30)").await?;
+        let _ = kipsql.run("insert into t2 (c, d) values (1, 1), (3, 3)").await?;
+
+        let plan_text = |tuples: &[Tuple]| tuples
+            .iter()
+            .map(|tuple| tuple.values[0].to_string())
+            .collect::<Vec<_>>();
+
+        // `d > 1` rejects the rows padded with NULLs, so the left join is an inner join
+        let sql = "select * from t1 left join t2 on a = c where d > 1";
+        let tuples = kipsql.run(sql).await?;
+        println!("{}", create_table(&tuples));
+        assert_eq!(tuples.len(), 1);
+        let lines = plan_text(&kipsql.run(&format!("explain {}", sql)).await?);
+        assert!(lines.iter().any(|line| line.trim_start().starts_with("Inner Join")));
+
+        // t2 is unused and `c` is unique, every row of t1 is joined with at most one row
+        let sql = "select a, b from t1 left join t2 on a = c";
+        let tuples = kipsql.run(sql).await?;
+        println!("{}", create_table(&tuples));
+        assert_eq!(tuples.len(), 3);
+        let lines = plan_text(&kipsql.run(&format!("explain {}", sql)).await?);
+        assert!(lines.iter().all(|line| !line.contains("Join")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_external_sort() -> Result<(), DatabaseError> {
+        // every tuple is over the budget, so each one is spilled as a run of its own
+        let kipsql = Database::with_mem().await
+            .with_config(ExecutorConfig { memory_budget: 1, ..Default::default() });
+        let _ = kipsql.run("create table t1 (a int primary key, b int null, c varchar null)").await?;
+        let _ = kipsql.run(
+            "insert into t1 (a, b, c) values (1, 3, 'x'), (2, null, 'y'), (3, 1, 'z'), (4, 3, 'w'), (5, 2, null)"
+        ).await?;
+
+        let tuples = kipsql.run("select a, b, c from t1 order by b desc nulls last").await?;
+        println!("{}", create_table(&tuples));
+        let rows = tuples
+            .iter()
+            .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+            .collect::<Vec<_>>();
+        assert_eq!(rows, vec!["1,3,x", "4,3,w", "5,2,null", "3,1,z", "2,null,y"]);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_vectorized_execution() -> Result<(), DatabaseError> {
+        let queries = [
+            "select a, b + 1, c from t1 where b > 1 and a < 5",
+            "select b, count(*), sum(a) from t1 group by b",
+            "select c, max(a) filter (where b > 1) from t1 group by c",
+            "select t1.a, t2.e from t1 join t2 on t1.b = t2.d where t2.e > t1.a",
+            "select * from t1 where b = a - 2",
+        ];
+        let mut results = Vec::new();
+
+        for vectorized in [false, true] {
+            // small batches to run over several of them
+            let kipsql = Database::with_mem().await
+                .with_config(ExecutorConfig { vectorized, batch_size: 2, ..Default::default() });
+            let _ = kipsql.run("create table t1 (a int primary key, b int null, c varchar null)").await?;
+            let _ = kipsql.run("create table t2 (d int primary key, e int)").await?;
+            let _ = kipsql.run(
+                "insert into t1 (a, b, c) values (1, 3, 'x'), (2, null, 'y'), (3, 1, 'x'), (4, 3, null), (5, 2, 'y'), (6, 4, 'x')"
+            ).await?;
+            let _ = kipsql.run("insert into t2 (d, e) values (1, 10), (2, 0), (3, 4), (4, 20)").await?;
+            let _ = kipsql.run("delete from t1 where b > 3").await?;
+
+            let mut rows = Vec::new();
+            for sql in queries {
+                let tuples = kipsql.run(sql).await?;
+                println!("{}", create_table(&tuples));
+                let mut query_rows = tuples
+                    .iter()
+                    .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+                    .collect::<Vec<_>>();
+                query_rows.sort();
+                rows.push(query_rows);
+            }
+            results.push(rows);
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[1][0], vec!["1,4,x", "4,4,null"]);
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_parallel_execution() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t1 (a int primary key, b int)").await?;
+        let _ = kipsql.run("create table t2 (c int primary key, d int)").await?;
+        let values = (0..200).map(|i| format!("({}, {})", i, i % 7)).collect::<Vec<_>>().join(", ");
+        let _ = kipsql.run(&format!("insert into t1 (a, b) values {}", values)).await?;
+        let _ = kipsql.run("insert into t2 (c, d) values (0, 0), (1, 10), (3, 30), (5, 50), (7, 70)").await?;
+        let _ = kipsql.run("analyze table t1").await?;
+        let _ = kipsql.run("analyze table t2").await?;
+
+        let ranges = kipsql.storage.table_ranges(&"t1".to_string(), 4).await;
+        assert_eq!(ranges.len(), 4);
+
+        let queries = [
+            "select a, b from t1 where b > 2",
+            "select b, count(*), sum(a) from t1 group by b",
+            "select a, d from t1 join t2 on b = c where a > 100",
+            // both scans are ordered by their primary keys, but not once gathered from partitions
+            "select a, c from t1 left join t2 on a = c",
+        ];
+        let mut kipsql = kipsql;
+        let mut results = Vec::new();
+
+        for parallelism in [1, 4] {
+            kipsql = kipsql.with_config(ExecutorConfig { parallelism, ..Default::default() });
+
+            let mut rows = Vec::new();
+            for sql in queries {
+                let mut query_rows = kipsql.run(sql)
+                    .await?
+                    .iter()
+                    .map(|tuple| tuple.values.iter().map(|value| value.to_string()).collect::<Vec<_>>().join(","))
+                    .collect::<Vec<_>>();
+                query_rows.sort();
+                rows.push(query_rows);
+            }
+            results.push(rows);
+        }
+        assert_eq!(results[0], results[1]);
+        assert_eq!(results[1][0].len(), 113);
+        assert_eq!(results[1][1].len(), 7);
+        assert_eq!(results[1][3].len(), 200);
+        assert_eq!(results[1][3].iter().filter(|row| !row.ends_with("null")).count(), 5);
+
+        // the metrics of the partitions are added up
+        let tuples = kipsql.run("explain analyze select a from t1 where b > 2").await?;
+        let lines = tuples.iter().map(|tuple| tuple.values[0].to_string()).collect::<Vec<_>>();
+        println!("{}", lines.join("\n"));
+        assert!(lines.iter().any(|line| line.trim_start().starts_with("Scan t1") && line.contains("(rows=113")));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_memory_limit() -> Result<(), DatabaseError> {
+        let kipsql = Database::with_mem().await
+            .with_config(ExecutorConfig { memory_limit: Some(1024), ..Default::default() });
+        let _ = kipsql.run("create table t1 (a int primary key, b varchar)").await?;
+        let _ = kipsql.run("insert into t1 (a, b) values (1, 'x'), (2, 'y'), (3, 'z')").await?;
+
+        assert_eq!(kipsql.run("select * from t1 order by b desc").await?.len(), 3);
+
+        let values = (4..100)
+            .map(|i| format!("({}, '{}')", i, "x".repeat(32)))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = kipsql.run(&format!("insert into t1 (a, b) values {}", values)).await?;
+
+        let result = kipsql.run("select * from t1 order by b desc").await;
+        assert!(matches!(
+            result,
+            Err(DatabaseError::ExecutorError(ExecutorError::MemoryLimitExceeded { limit: 1024, .. }))
+        ));
+
+        // the limit is far below the budget, the hash join and the aggregation spill at the
+        // limit instead of failing
+        let _ = kipsql.run("create table t2 (c int primary key, d varchar)").await?;
+        let _ = kipsql.run("create table t3 (e int primary key, f varchar)").await?;
+        let values = (0..100)
+            .map(|i| format!("({}, '{:032}')", i, i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let _ = kipsql.run(&format!("insert into t2 (c, d) values {}", values)).await?;
+        let _ = kipsql.run(&format!("insert into t3 (e, f) values {}", values)).await?;
+
+        let tuples = kipsql.run("select count(*) from t2 join t3 on t2.d = t3.f").await?;
+        assert_eq!(tuples[0].values[0].to_string(), "100");
+        let tuples = kipsql.run("select d from t2 group by d having count(*) > 1").await?;
+        assert!(tuples.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_statement_timeout_and_cancel() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?
+            .with_config(ExecutorConfig { statement_timeout: Some(Duration::from_secs(60)), ..Default::default() });
+        let _ = kipsql.run("create table t1 (a int primary key, b int)").await?;
+        let _ = kipsql.run("insert into t1 (a, b) values (1, 1), (2, 2)").await?;
+
+        // the insert fails before it's committed, so none of its rows are kept
+        let handle = kipsql.new_handle();
+        handle.cancel.cancel();
+        let result = kipsql.run_with_handle("insert into t1 (a, b) values (3, 3), (4, 4)", handle).await;
+        assert!(matches!(result, Err(DatabaseError::ExecutorError(ExecutorError::Cancelled))));
+        assert_eq!(kipsql.run("select * from t1").await?.len(), 2);
+        assert!(!kipsql.cancel(0));
+
+        let kipsql = kipsql
+            .with_config(ExecutorConfig { statement_timeout: Some(Duration::ZERO), ..Default::default() });
+        let result = kipsql.run("select * from t1").await;
+        assert!(matches!(result, Err(DatabaseError::ExecutorError(ExecutorError::StatementTimeout(_)))));
+
+        Ok(())
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_cancel_running_query() -> Result<(), DatabaseError> {
+        let kipsql = Arc::new(Database::with_mem().await);
+        let _ = kipsql.run("create table t1 (a int primary key)").await?;
+        let values = (0..200).map(|i| format!("({})", i)).collect::<Vec<_>>().join(", ");
+        let _ = kipsql.run(&format!("insert into t1 (a) values {}", values)).await?;
+
+        let handle = kipsql.new_handle();
+        let id = handle.id();
+        let query = tokio::spawn({
+            let kipsql = kipsql.clone();
+            // 8 million rows
+            async move {
+                kipsql.run_with_handle("select count(*) from t1 x, t1 y, t1 z", handle).await
+            }
+        });
+        while !kipsql.cancel(id) {
+            tokio::task::yield_now().await;
+        }
+
+        let result = query.await.unwrap();
+        assert!(matches!(result, Err(DatabaseError::ExecutorError(ExecutorError::Cancelled))));
+        assert!(!kipsql.cancel(id));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_analyze() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t1 (a int primary key, b int null, c varchar)").await?;
+        let _ = kipsql.run(
+            "insert into t1 (a, b, c) values (1, 1, 'x'), (2, 1, 'y'), (3, 2, 'x'), (4, null, 'x'), (5, 3, 'z')"
+        ).await?;
+
+        let _ = kipsql.run("analyze table t1").await?;
+        let table_name = "t1".to_string();
+        let catalog = kipsql.storage.table_catalog(&table_name).await.unwrap();
+        let statistics = catalog.statistics().unwrap();
+        let column_b = catalog.get_column_by_name(&"b".to_string()).unwrap();
+        let statistics_b = statistics.column(&column_b.id).unwrap();
+
+        assert_eq!(statistics.row_count, 5);
+        assert_eq!(statistics.columns.len(), 3);
+        assert_eq!(statistics_b.null_fraction, 0.2);
+        assert_eq!(statistics_b.ndv, 3);
+        assert_eq!(statistics_b.histogram.buckets().iter().map(|bucket| bucket.count).sum::<usize>(), 4);
+
+        println!("statistics table:");
+        let tuples = kipsql.run("select column_name, row_count, ndv, histogram from kipsql_statistics").await?;
+        println!("{}", create_table(&tuples));
+        assert_eq!(tuples.len(), 3);
+        assert!(tuples.iter().any(|tuple| tuple.values[0].to_string() == "c" && tuple.values[2].to_string() == "3"));
+
+        // the statistics are persisted
+        // the flush on drop is asynchronous
+        kipsql.storage.inner.flush().await.map_err(StorageError::from)?;
+        drop(kipsql);
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let catalog = kipsql.storage.table_catalog(&table_name).await.unwrap();
+        assert_eq!(catalog.statistics().map(|statistics| statistics.row_count), Some(5));
+        assert_eq!(kipsql.storage.table_row_count(&table_name).await, Some(5));
+        let tuples = kipsql.run("select column_name, row_count from kipsql_statistics").await?;
+        assert_eq!(tuples.len(), 3);
+        assert!(tuples.iter().all(|tuple| tuple.values[1].to_string() == "5"));
+
+        let _ = kipsql.run("insert into t1 (a, b, c) values (6, 4, 'w')").await?;
+        let _ = kipsql.run("analyze table t1 for columns c").await?;
+        let catalog = kipsql.storage.table_catalog(&table_name).await.unwrap();
+        let statistics = catalog.statistics().unwrap();
+        assert_eq!(statistics.row_count, 6);
+        // the statistics of the other columns are kept
+        assert_eq!(statistics.columns.len(), 3);
+        let column_b = catalog.get_column_by_name(&"b".to_string()).unwrap();
+        assert_eq!(statistics.column(&column_b.id).map(|statistics| statistics.ndv), Some(3));
+        let tuples = kipsql.run("select column_name, row_count, ndv from kipsql_statistics").await?;
+        assert_eq!(tuples.len(), 3);
+        assert!(tuples.iter().all(|tuple| tuple.values[1].to_string() == "6"));
+        assert!(tuples.iter().any(|tuple| tuple.values[0].to_string() == "c" && tuple.values[2].to_string() == "4"));
+
+        assert!(kipsql.run("analyze table t1 for columns d").await.is_err());
+        assert!(kipsql.run("analyze table t9").await.is_err());
+        // the system table is read-only
+        for sql in [
+            "insert into kipsql_statistics (id) values ('t1.a')",
+            "update kipsql_statistics set ndv = 1",
+            "delete from kipsql_statistics",
+            "truncate kipsql_statistics",
+            "drop table kipsql_statistics",
+            "analyze table kipsql_statistics",
+            "create table kipsql_statistics (id int primary key)",
+        ] {
+            assert!(matches!(kipsql.run(sql).await, Err(DatabaseError::Bind(BindError::InvalidTable(_)))), "{}", sql);
+        }
+        assert_eq!(kipsql.run("select column_name from kipsql_statistics").await?.len(), 3);
+
+        let _ = kipsql.run("drop table t1").await?;
+        let tuples = kipsql.run("select column_name from kipsql_statistics").await?;
+        assert!(tuples.is_empty());
+        let _ = kipsql.run("create table t1 (a int primary key)").await?;
+        let catalog = kipsql.storage.table_catalog(&table_name).await.unwrap();
+        assert!(catalog.statistics().is_none());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_explain_analyze() -> Result<(), DatabaseError> {
+        let temp_dir = TempDir::new().expect("unable to create temporary working directory");
+        let kipsql = Database::with_kipdb(temp_dir.path()).await?;
+        let _ = kipsql.run("create table t1 (a int primary key, b int)").await?;
+        let _ = kipsql.run("create table t2 (c int primary key, d int)").await?;
+        let _ = kipsql.run("insert into t1 (a, b) values (1, 10), (2, 20), (3, 30)").await?;
+        let _ = kipsql.run("insert into t2 (c, d) values (1, 1), (3, 3)").await?;
+
+        let plan_text = |tuples: &[Tuple]| tuples
+            .iter()
+            .map(|tuple| tuple.values[0].to_string())
+            .collect::<Vec<_>>();
+        let line_of = |lines: &[String], operator: &str| lines
+            .iter()
+            .find(|line| line.trim_start().starts_with(operator))
+            .cloned()
+            .unwrap();
+
+        println!("explain analyze:");
+        let tuples = kipsql.run("explain analyze select * from t1 order by b desc").await?;
+        println!("{}", create_table(&tuples));
+        let lines = plan_text(&tuples);
+        assert!(line_of(&lines, "Sort").contains("(rows=3, time="));
+        assert!(line_of(&lines, "Sort").ends_with("peak buffered=3)"));
+        assert!(line_of(&lines, "Scan").contains("rows=3"));
+
+        println!("explain analyze top-n:");
+        let tuples = kipsql.run("explain analyze select * from t1 order by b desc limit 1 offset 1").await?;
+        println!("{}", create_table(&tuples));
+        let lines = plan_text(&tuples);
+        // only the first offset + limit tuples are kept
+        assert!(line_of(&lines, "Sort").contains("limit 2 (rows=2, time="));
+        assert!(line_of(&lines, "Sort").ends_with("peak buffered=2)"));
+        let tuples = kipsql.run("select b from t1 order by b desc limit 5 offset 1").await?;
+        assert_eq!(
+            tuples.iter().map(|tuple| tuple.values[0].to_string()).collect::<Vec<_>>(),
+            vec!["20", "10"]
+        );
+        // the heap is not allocated for the whole limit up front
+        let tuples = kipsql.run("select b from t1 order by b desc limit 9223372036854775807").await?;
+        assert_eq!(tuples.len(), 3);
+
+        println!("explain analyze join:");
+        let tuples = kipsql.run("explain analyze select * from t1 left join t2 on a = c where b > 10").await?;
+        println!("{}", create_table(&tuples));
+        let lines = plan_text(&tuples);
+        assert!(line_of(&lines, "Left Join").contains("rows=2"));
+        assert!(line_of(&lines, "Scan t1").contains("where [(t1.b > 10)] (rows=2"));
+        assert!(lines.iter().all(|line| line.contains("(rows=")));
+
+        println!("explain analyze verbose:");
+        let tuples = kipsql.run("explain analyze verbose select a from t1").await?;
+        let lines = plan_text(&tuples);
+        assert_eq!(lines[0], "== Source Plan ==");
+        assert!(lines.contains(&"== Analyzed Plan ==".to_string()));
+
+        // the explained statement is executed
+        let _ = kipsql.run("explain analyze insert into t1 (a, b) values (4, 40)").await?;
+        assert_eq!(kipsql.run("select * from t1").await?.len(), 4);
+
+        Ok(())
+    }
 }
@@ -0,0 +1,75 @@
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering::Relaxed;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use futures::{Stream, StreamExt};
+use futures::stream::BoxStream;
+use crate::execution::ExecutorError;
+
+/// Stops the executors of a statement once it's cancelled or past its timeout.
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken {
+    cancelled: Arc<AtomicBool>,
+    timeout: Option<(Duration, Instant)>,
+}
+
+impl CancellationToken {
+    /// Also stop the statement `timeout` from now, the clones are still cancelled together.
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout.map(|timeout| (timeout, Instant::now() + timeout));
+        self
+    }
+
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Relaxed)
+    }
+
+    pub fn check(&self) -> Result<(), ExecutorError> {
+        if self.is_cancelled() {
+            return Err(ExecutorError::Cancelled);
+        }
+        if let Some((timeout, deadline)) = self.timeout {
+            if Instant::now() >= deadline {
+                return Err(ExecutorError::StatementTimeout(timeout));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Wrap the executor to check the token before every poll, the stream ends after the error.
+pub fn cancellable<T: Send + 'static>(
+    input: BoxStream<'static, Result<T, ExecutorError>>,
+    token: CancellationToken
+) -> BoxStream<'static, Result<T, ExecutorError>> {
+    Cancellable { input, token, is_stopped: false }.boxed()
+}
+
+struct Cancellable<T> {
+    input: BoxStream<'static, Result<T, ExecutorError>>,
+    token: CancellationToken,
+    is_stopped: bool,
+}
+
+impl<T> Stream for Cancellable<T> {
+    type Item = Result<T, ExecutorError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.is_stopped {
+            return Poll::Ready(None);
+        }
+        if let Err(err) = self.token.check() {
+            self.is_stopped = true;
+
+            return Poll::Ready(Some(Err(err)));
+        }
+        self.input.poll_next_unpin(cx)
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::catalog::{ColumnCatalog, ColumnDesc, ColumnRef, ColumnStatistics, ColumnStatisticsBuilder, DEFAULT_BUCKETS, STATISTICS_TABLE_NAME, statistics_row_id, TableName, TableStatistics};
+use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::ExecutorError;
+use crate::planner::operator::analyze::AnalyzeOperator;
+use crate::storage::{Storage, Table};
+use crate::types::LogicalType;
+use crate::types::tuple::Tuple;
+use crate::types::value::DataValue;
+
+/// Collect the statistics of the columns, which are saved into the catalog of the table and
+/// into the system table `kipsql_statistics`. The sampled values are reserved from the memory
+/// tracker of the statement.
+pub struct Analyze {
+    table_name: TableName,
+    columns: Vec<ColumnRef>,
+    input: BoxedExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory: Arc<MemoryTracker>,
+}
+
+impl From<(AnalyzeOperator, BoxedExecutor)> for Analyze {
+    fn from((AnalyzeOperator { table_name, columns }, input): (AnalyzeOperator, BoxedExecutor)) -> Self {
+        Analyze {
+            table_name,
+            columns,
+            input,
+            metrics: Default::default(),
+            memory: Default::default(),
+        }
+    }
+}
+
+impl<S: Storage> Executor<S> for Analyze {
+    fn execute(self, storage: &S) -> BoxedExecutor {
+        self._execute(storage.clone())
+    }
+}
+
+impl Analyze {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    pub async fn _execute<S: Storage>(self, storage: S) {
+        let Analyze { table_name, columns, input, metrics, memory } = self;
+
+        let mut builders = columns
+            .iter()
+            .map(|col| ColumnStatisticsBuilder::new(*col.datatype()))
+            .collect_vec();
+        let mut positions = None;
+        let mut row_count = 0;
+        let mut reservation = MemoryReservation::new(memory);
+
+        #[for_await]
+        for tuple in input {
+            let tuple: Tuple = tuple?;
+            let positions = positions.get_or_insert_with(|| columns
+                .iter()
+                .map(|col| tuple.columns.iter().position(|tuple_col| tuple_col.id == col.id))
+                .collect_vec());
+
+            for (builder, position) in builders.iter_mut().zip(positions.iter()) {
+                if let Some(i) = position {
+                    let size = builder.memory_size();
+                    builder.append(&tuple.values[*i]);
+
+                    match builder.memory_size().checked_sub(size) {
+                        Some(grown) => reservation.try_grow(grown)?,
+                        None => reservation.shrink(size - builder.memory_size()),
+                    }
+                }
+            }
+            row_count += 1;
+            metrics.buffer(1);
+        }
+        let table_columns = storage.table_catalog(&table_name)
+            .await
+            .map(|catalog| catalog.all_columns())
+            .ok_or_else(|| ExecutorError::InternalError(format!("table {} not found", table_name)))?;
+        // the columns not analyzed this time keep their statistics
+        let mut statistics = storage.table_catalog(&table_name)
+            .await
+            .and_then(|catalog| catalog.statistics().cloned())
+            .unwrap_or_else(|| TableStatistics { row_count, columns: BTreeMap::new() });
+        statistics.row_count = row_count;
+        statistics.columns.extend(
+            columns
+                .iter()
+                .map(|col| col.id)
+                .zip(builders.into_iter().map(|builder| builder.build(DEFAULT_BUCKETS)))
+        );
+        statistics.columns.retain(|id, _| table_columns.iter().any(|col| &col.id == id));
+
+        save_to_statistics_table(&storage, &table_name, &table_columns, &statistics).await?;
+        storage.save_statistics(&table_name, statistics).await?;
+    }
+}
+
+/// Overwrite the rows of the analyzed columns in the system table, which is created on first
+/// use, in the transaction which writes the statistics of the table, so all of them share the
+/// row count of the table.
+async fn save_to_statistics_table<S: Storage>(
+    storage: &S,
+    table_name: &TableName,
+    columns: &[ColumnRef],
+    statistics: &TableStatistics
+) -> Result<(), ExecutorError> {
+    let system_table_name = STATISTICS_TABLE_NAME.to_string();
+
+    if storage.table_catalog(&system_table_name).await.is_none() {
+        let column = |name: &str, ty: LogicalType, is_primary: bool| {
+            ColumnCatalog::new(name.to_string(), false, ColumnDesc::new(ty, is_primary))
+        };
+        let _ = storage.create_table(
+            Arc::new(system_table_name.clone()),
+            vec![
+                column("id", LogicalType::Varchar, true),
+                column("table_name", LogicalType::Varchar, false),
+                column("column_name", LogicalType::Varchar, false),
+                column("row_count", LogicalType::UBigint, false),
+                column("null_fraction", LogicalType::Double, false),
+                column("ndv", LogicalType::UBigint, false),
+                column("histogram", LogicalType::Varchar, false),
+            ]
+        ).await?;
+    }
+    let system_columns = storage.table_catalog(&system_table_name)
+        .await
+        .map(|catalog| catalog.all_columns())
+        .ok_or_else(|| ExecutorError::InternalError(format!("table {} not found", system_table_name)))?;
+    let mut system_table = storage.table(&system_table_name)
+        .await
+        .ok_or_else(|| ExecutorError::InternalError(format!("table {} not found", system_table_name)))?;
+
+    for col in columns {
+        let Some(ColumnStatistics { null_fraction, ndv, histogram }) = statistics.column(&col.id) else {
+            continue;
+        };
+        let id = statistics_row_id(table_name, &col.name);
+        let histogram = histogram
+            .buckets()
+            .iter()
+            .map(|bucket| format!("[{}, {}]: {}", bucket.lower, bucket.upper, bucket.count))
+            .join(", ");
+        let values = system_columns
+            .iter()
+            .map(|system_col| Arc::new(match system_col.name.as_str() {
+                "id" => DataValue::clone(&id),
+                "table_name" => DataValue::Utf8(Some(table_name.to_string())),
+                "column_name" => DataValue::Utf8(Some(col.name.clone())),
+                "row_count" => DataValue::UInt64(Some(statistics.row_count as u64)),
+                "null_fraction" => DataValue::Float64(Some(*null_fraction)),
+                "ndv" => DataValue::UInt64(Some(*ndv as u64)),
+                "histogram" => DataValue::Utf8(Some(histogram.clone())),
+                _ => DataValue::none(system_col.datatype()),
+            }))
+            .collect_vec();
+
+        system_table.append(
+            Tuple {
+                id: Some(id),
+                columns: system_columns.clone(),
+                values,
+            },
+            true
+        )?;
+    }
+    system_table.save_statistics(table_name, statistics)?;
+    system_table.commit().await?;
+
+    Ok(())
+}
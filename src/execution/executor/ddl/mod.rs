@@ -1,3 +1,4 @@
+pub(crate) mod analyze;
 pub(crate) mod create_table;
 pub(crate) mod drop_table;
 pub(crate) mod truncate;
\ No newline at end of file
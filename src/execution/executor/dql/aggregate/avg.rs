@@ -2,20 +2,24 @@ use std::sync::Arc;
 use crate::execution::executor::dql::aggregate::Accumulator;
 use crate::execution::executor::dql::aggregate::sum::SumAccumulator;
 use crate::execution::ExecutorError;
+use crate::expression::agg::AggKind;
 use crate::expression::BinaryOperator;
 use crate::expression::value_compute::binary_op;
 use crate::types::LogicalType;
 use crate::types::value::{DataValue, ValueRef};
 
 pub struct AvgAccumulator {
+    ty: LogicalType,
     inner: SumAccumulator,
     count: usize
 }
 
 impl AvgAccumulator {
+    /// `ty` is the one of the values.
     pub fn new(ty: &LogicalType) -> Self {
         Self {
-            inner: SumAccumulator::new(ty),
+            ty: AggKind::Avg.return_type(*ty),
+            inner: SumAccumulator::new(&AggKind::Sum.return_type(*ty)),
             count: 0,
         }
     }
@@ -32,8 +36,16 @@ impl Accumulator for AvgAccumulator {
     }
 
     fn evaluate(&self) -> Result<ValueRef, ExecutorError> {
-        let value = self.inner
+        // the average of no values is NULL
+        if self.count == 0 {
+            return Ok(Arc::new(DataValue::none(&self.ty)));
+        }
+        let mut value = self.inner
             .evaluate()?;
+        // the division of integers is truncated, the average of them is a double
+        if !matches!(value.logical_type(), LogicalType::Decimal(..)) {
+            value = Arc::new(DataValue::clone(&value).cast(&LogicalType::Double)?);
+        }
 
         let quantity = if value.logical_type().is_signed_numeric() {
             DataValue::Int64(Some(self.count as i64))
@@ -31,20 +31,22 @@ impl Accumulator for CountAccumulator {
 
 pub struct DistinctCountAccumulator {
     distinct_values: HashSet<ValueRef, RandomState>,
+    memory_size: usize,
 }
 
 impl DistinctCountAccumulator {
     pub fn new() -> Self {
         Self {
             distinct_values: HashSet::default(),
+            memory_size: 0,
         }
     }
 }
 
 impl Accumulator for DistinctCountAccumulator {
     fn update_value(&mut self, value: &ValueRef) -> Result<(), ExecutorError> {
-        if !value.is_null() {
-            self.distinct_values.insert(value.clone());
+        if !value.is_null() && self.distinct_values.insert(value.clone()) {
+            self.memory_size += value.memory_size();
         }
 
         Ok(())
@@ -53,4 +55,8 @@ impl Accumulator for DistinctCountAccumulator {
     fn evaluate(&self) -> Result<ValueRef, ExecutorError> {
         Ok(Arc::new(DataValue::UInt32(Some(self.distinct_values.len() as u32))))
     }
+
+    fn memory_size(&self) -> usize {
+        self.memory_size
+    }
 }
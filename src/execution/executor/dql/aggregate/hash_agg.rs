@@ -1,8 +1,12 @@
+use std::sync::Arc;
 use ahash::{HashMap, HashMapExt};
 use futures_async_stream::try_stream;
 use itertools::Itertools;
-use crate::execution::executor::{BoxedExecutor, Executor};
-use crate::execution::executor::dql::aggregate::create_accumulators;
+use crate::execution::executor::{BoxedExecutor, DEFAULT_MEMORY_BUDGET, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::spill::{MAX_PARTITION_LEVEL, SpillPartitions};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::executor::dql::aggregate::{accumulators_memory_size, create_accumulators, update_accumulators};
 use crate::execution::ExecutorError;
 use crate::expression::ScalarExpression;
 use crate::planner::operator::aggregate::AggregateOperator;
@@ -14,6 +18,10 @@ pub struct HashAggExecutor {
     pub agg_calls: Vec<ScalarExpression>,
     pub groupby_exprs: Vec<ScalarExpression>,
     pub input: BoxedExecutor,
+    pub metrics: Arc<ExecutorMetrics>,
+    memory_budget: usize,
+    memory: Arc<MemoryTracker>,
+    level: usize,
 }
 
 impl From<(AggregateOperator, BoxedExecutor)> for HashAggExecutor {
@@ -22,6 +30,10 @@ impl From<(AggregateOperator, BoxedExecutor)> for HashAggExecutor {
             agg_calls,
             groupby_exprs,
             input,
+            metrics: Default::default(),
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            memory: Default::default(),
+            level: 0,
         }
     }
 }
@@ -33,52 +45,91 @@ impl<S: Storage> Executor<S> for HashAggExecutor {
 }
 
 impl HashAggExecutor {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Past `memory_budget` bytes of groups, or the memory limit of the tracker, the rows of new
+    /// groups are written to partitions in temporary files, which are aggregated after the
+    /// groups in memory. The bytes of a group
+    /// are its first row and the values kept by its accumulators, e.g. of `string_agg`.
+    ///
+    /// Like the grace hash join, the partitions are split at most `MAX_PARTITION_LEVEL` times,
+    /// past it all of their groups are kept in memory.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
     #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
     pub async fn _execute(self) {
+        let HashAggExecutor { agg_calls, groupby_exprs, input, metrics, memory_budget, memory, level } = self;
         let mut group_and_agg_columns_option = None;
         let mut group_hash_accs = HashMap::new();
+        let mut reservation = MemoryReservation::new(memory.clone());
+        let mut partitions: Option<SpillPartitions> = None;
 
         #[for_await]
-        for tuple in self.input {
+        for tuple in input {
             let tuple = tuple?;
 
             // 1. build group and agg columns for hash_agg columns.
             // Tips: AggCall First
             group_and_agg_columns_option.get_or_insert_with(|| {
-                self.agg_calls
+                agg_calls
                     .iter()
-                    .chain(self.groupby_exprs.iter())
+                    .chain(groupby_exprs.iter())
                     .map(|expr| expr.output_columns(&tuple))
                     .collect_vec()
             });
 
-            // 2.1 evaluate agg exprs and collect the result values for later accumulators.
-            let values: Vec<ValueRef> = self.agg_calls
-                .iter()
-                .map(|expr| {
-                    if let ScalarExpression::AggCall { args, .. } = expr {
-                        args[0].eval_column(&tuple)
-                    } else {
-                        unreachable!()
-                    }
-                })
-                .try_collect()?;
-
-            let group_keys: Vec<ValueRef> = self.groupby_exprs
+            // 2. evaluate the group keys and feed the row into the accumulators of its group.
+            let group_keys: Vec<ValueRef> = groupby_exprs
                 .iter()
                 .map(|expr| expr.eval_column(&tuple))
                 .try_collect()?;
 
-            for (acc, value) in group_hash_accs
-                .entry(group_keys)
-                .or_insert_with(|| create_accumulators(&self.agg_calls))
-                .iter_mut()
-                .zip_eq(values.iter())
-            {
-                acc.update_value(value)?;
+            // 3. once a new group is over the budget or the memory limit, only the groups in
+            // memory are still accumulated and the rows of the others are spilled, which keeps
+            // every group in a single place.
+            if partitions.is_none() && !group_hash_accs.contains_key(&group_keys) {
+                let size = tuple.memory_size();
+
+                if !reservation.try_grow_within(size, memory_budget) {
+                    if !group_hash_accs.is_empty() && level < MAX_PARTITION_LEVEL {
+                        partitions = Some(SpillPartitions::new(level));
+                    } else {
+                        reservation.try_grow(size)?;
+                    }
+                }
             }
+            let accs = match (group_hash_accs.get_mut(&group_keys), &mut partitions) {
+                (Some(accs), _) => accs,
+                (None, Some(partitions)) => {
+                    partitions.append(&group_keys, &tuple)?;
+                    continue;
+                }
+                (None, None) => {
+                    metrics.buffer(1);
+
+                    group_hash_accs
+                        .entry(group_keys)
+                        .or_insert_with(|| create_accumulators(&agg_calls))
+                }
+            };
+            let accs_size = accumulators_memory_size(accs);
+            update_accumulators(accs, &agg_calls, &tuple)?;
+            reservation.try_grow(accumulators_memory_size(accs).saturating_sub(accs_size))?;
         }
 
+        let groups_len = group_hash_accs.len();
+
         if let Some(group_and_agg_columns) = group_and_agg_columns_option {
             for (group_keys, accs) in group_hash_accs {
                 // Tips: Accumulator First
@@ -96,6 +147,25 @@ impl HashAggExecutor {
                 };
             }
         }
+        metrics.release(groups_len);
+        reservation.free();
+
+        for file in partitions.into_iter().flat_map(SpillPartitions::into_files).flatten() {
+            let executor = HashAggExecutor {
+                agg_calls: agg_calls.clone(),
+                groupby_exprs: groupby_exprs.clone(),
+                input: file.reader()?.into_executor(),
+                metrics: metrics.clone(),
+                memory_budget,
+                memory: memory.clone(),
+                level: level + 1,
+            };
+
+            #[for_await]
+            for tuple in executor._execute() {
+                yield tuple?;
+            }
+        }
     }
 }
 
@@ -108,6 +178,7 @@ mod test {
     use crate::execution::executor::dql::values::Values;
     use crate::execution::executor::{Executor, try_collect};
     use crate::execution::executor::dql::test::build_integers;
+    use crate::execution::executor::memory::MemoryTracker;
     use crate::execution::ExecutorError;
     use crate::expression::agg::AggKind;
     use crate::expression::ScalarExpression;
@@ -142,37 +213,38 @@ mod test {
                         ScalarExpression::ColumnRef(t1_columns[1].clone())
                     ],
                     ty: LogicalType::Integer,
+                    filter: None,
+                    order_by: vec![],
                 }
             ],
         };
 
-        let input = Values::from(ValuesOperator {
-            rows: vec![
-                vec![
-                    Arc::new(DataValue::Int32(Some(0))),
-                    Arc::new(DataValue::Int32(Some(2))),
-                    Arc::new(DataValue::Int32(Some(4))),
-                ],
-                vec![
-                    Arc::new(DataValue::Int32(Some(1))),
-                    Arc::new(DataValue::Int32(Some(3))),
-                    Arc::new(DataValue::Int32(Some(5))),
-                ],
-                vec![
-                    Arc::new(DataValue::Int32(Some(0))),
-                    Arc::new(DataValue::Int32(Some(1))),
-                    Arc::new(DataValue::Int32(Some(2))),
-                ],
-                vec![
-                    Arc::new(DataValue::Int32(Some(1))),
-                    Arc::new(DataValue::Int32(Some(2))),
-                    Arc::new(DataValue::Int32(Some(3))),
-                ]
+        let rows = vec![
+            vec![
+                Arc::new(DataValue::Int32(Some(0))),
+                Arc::new(DataValue::Int32(Some(2))),
+                Arc::new(DataValue::Int32(Some(4))),
+            ],
+            vec![
+                Arc::new(DataValue::Int32(Some(1))),
+                Arc::new(DataValue::Int32(Some(3))),
+                Arc::new(DataValue::Int32(Some(5))),
+            ],
+            vec![
+                Arc::new(DataValue::Int32(Some(0))),
+                Arc::new(DataValue::Int32(Some(1))),
+                Arc::new(DataValue::Int32(Some(2))),
             ],
-            columns: t1_columns,
-        }).execute(&mem_storage);
+            vec![
+                Arc::new(DataValue::Int32(Some(1))),
+                Arc::new(DataValue::Int32(Some(2))),
+                Arc::new(DataValue::Int32(Some(3))),
+            ]
+        ];
+        let input = Values::from(ValuesOperator { rows: rows.clone(), columns: t1_columns.clone() })
+            .execute(&mem_storage);
 
-        let tuples = try_collect(&mut HashAggExecutor::from((operator, input)).execute(&mem_storage)).await?;
+        let tuples = try_collect(&mut HashAggExecutor::from((operator.clone(), input)).execute(&mem_storage)).await?;
 
         println!("hash_agg_test: \n{}", create_table(&tuples));
 
@@ -186,6 +258,60 @@ mod test {
         assert!(vec_values.contains(&build_integers(vec![Some(3), Some(0)])));
         assert!(vec_values.contains(&build_integers(vec![Some(5), Some(1)])));
 
+        // only the first group fits in the budget, the rows of the other one are spilled
+        let input = Values::from(ValuesOperator { rows, columns: t1_columns }).execute(&mem_storage);
+        let executor = HashAggExecutor::from((operator, input)).with_memory_budget(1);
+        let tuples = try_collect(&mut executor.execute(&mem_storage)).await?;
+
+        println!("spilled_hash_agg_test: \n{}", create_table(&tuples));
+
+        assert_eq!(
+            tuples.into_iter().map(|tuple| tuple.values).collect_vec(),
+            vec![build_integers(vec![Some(3), Some(0)]), build_integers(vec![Some(5), Some(1)])]
+        );
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_hash_agg_reserves_accumulators() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let desc = ColumnDesc::new(LogicalType::Integer, false);
+
+        let t1_columns = vec![
+            Arc::new(ColumnCatalog::new("c1".to_string(), true, desc.clone())),
+            Arc::new(ColumnCatalog::new("c2".to_string(), true, desc.clone())),
+        ];
+        let operator = AggregateOperator {
+            groupby_exprs: vec![
+                ScalarExpression::ColumnRef(t1_columns[0].clone())
+            ],
+            agg_calls: vec![
+                ScalarExpression::AggCall {
+                    distinct: true,
+                    kind: AggKind::Count,
+                    args: vec![
+                        ScalarExpression::ColumnRef(t1_columns[1].clone())
+                    ],
+                    ty: LogicalType::UInteger,
+                    filter: None,
+                    order_by: vec![],
+                }
+            ],
+        };
+        // a single group, whose distinct values are kept by its accumulator
+        let rows = (0..100)
+            .map(|i| build_integers(vec![Some(0), Some(i)]))
+            .collect_vec();
+        let input = Values::from(ValuesOperator { rows, columns: t1_columns }).execute(&mem_storage);
+        let memory = Arc::new(MemoryTracker::new(None));
+        let executor = HashAggExecutor::from((operator, input)).with_memory_tracker(memory.clone());
+        let tuples = try_collect(&mut executor.execute(&mem_storage)).await?;
+
+        assert_eq!(tuples.len(), 1);
+        assert!(memory.peak() >= 100 * std::mem::size_of::<DataValue>());
+        assert_eq!(memory.used(), 0);
+
         Ok(())
     }
 }
\ No newline at end of file
@@ -4,15 +4,20 @@ mod sum;
 mod min_max;
 mod avg;
 pub mod hash_agg;
+mod string_agg;
+mod ordered;
 
 use crate::execution::executor::dql::aggregate::avg::AvgAccumulator;
 use crate::execution::executor::dql::aggregate::count::{CountAccumulator, DistinctCountAccumulator};
 use crate::execution::executor::dql::aggregate::min_max::MinMaxAccumulator;
+use crate::execution::executor::dql::aggregate::ordered::OrderedAccumulator;
+use crate::execution::executor::dql::aggregate::string_agg::StringAggAccumulator;
 use crate::execution::executor::dql::aggregate::sum::{DistinctSumAccumulator, SumAccumulator};
 use crate::execution::ExecutorError;
 use crate::expression::agg::AggKind;
 use crate::expression::ScalarExpression;
-use crate::types::value::ValueRef;
+use crate::types::tuple::Tuple;
+use crate::types::value::{DataValue, ValueRef};
 
 /// Tips: Idea for sqlrs
 /// An accumulator represents a stateful object that lives throughout the evaluation of multiple
@@ -21,12 +26,28 @@ pub trait Accumulator: Send + Sync {
     /// updates the accumulator's state from a vector of arrays.
     fn update_value(&mut self, value: &ValueRef) -> Result<(), ExecutorError>;
 
+    /// updates the accumulator's state with a value and the keys of the `ORDER BY` inside the call.
+    /// only accumulators that care about the input order need to look at the keys.
+    fn update_value_with_keys(&mut self, value: &ValueRef, _keys: Vec<ValueRef>) -> Result<(), ExecutorError> {
+        self.update_value(value)
+    }
+
     /// returns its value based on its current state.
     fn evaluate(&self) -> Result<ValueRef, ExecutorError>;
+
+    /// approximate number of bytes of the values kept in its state, e.g. by `DISTINCT` or by
+    /// the `ORDER BY` inside the call, the accumulators of a single value have none.
+    fn memory_size(&self) -> usize {
+        0
+    }
 }
 
 fn create_accumulator(expr: &ScalarExpression) -> Box<dyn Accumulator> {
-    if let ScalarExpression::AggCall { kind, ty, distinct, .. } = expr {
+    if let ScalarExpression::AggCall { kind, ty, distinct, args, order_by, .. } = expr {
+        if !order_by.is_empty() {
+            return Box::new(OrderedAccumulator::new(expr.clone()));
+        }
+
         match (kind, distinct) {
             (AggKind::Count, false) => Box::new(CountAccumulator::new()),
             (AggKind::Count, true) => Box::new(DistinctCountAccumulator::new()),
@@ -34,7 +55,20 @@ fn create_accumulator(expr: &ScalarExpression) -> Box<dyn Accumulator> {
             (AggKind::Sum, true) => Box::new(DistinctSumAccumulator::new(ty)),
             (AggKind::Min, _) => Box::new(MinMaxAccumulator::new(ty, false)),
             (AggKind::Max, _) => Box::new(MinMaxAccumulator::new(ty, true)),
-            (AggKind::Avg, _) => Box::new(AvgAccumulator::new(ty)),
+            (AggKind::Avg, _) => Box::new(AvgAccumulator::new(&args[0].return_type())),
+            (AggKind::StringAgg, _) => {
+                // a NULL delimiter concatenates the values without a separator
+                let delimiter = match &args[1] {
+                    ScalarExpression::Constant(value) => match value.as_ref() {
+                        DataValue::Utf8(Some(delimiter)) => delimiter.clone(),
+                        value if value.is_null() => String::new(),
+                        value => value.to_string(),
+                    }
+                    expr => unreachable!("string_agg delimiter must be bound as a constant: {:?}", expr),
+                };
+
+                Box::new(StringAggAccumulator::new(delimiter, *distinct))
+            }
         }
     } else {
         unreachable!(
@@ -44,6 +78,44 @@ fn create_accumulator(expr: &ScalarExpression) -> Box<dyn Accumulator> {
     }
 }
 
-fn create_accumulators(exprs: &[ScalarExpression]) -> Vec<Box<dyn Accumulator>> {
+pub(crate) fn create_accumulators(exprs: &[ScalarExpression]) -> Vec<Box<dyn Accumulator>> {
     exprs.iter().map(create_accumulator).collect()
-}
\ No newline at end of file
+}
+
+pub(crate) fn accumulators_memory_size(accs: &[Box<dyn Accumulator>]) -> usize {
+    accs.iter().map(|acc| acc.memory_size()).sum()
+}
+
+/// Feeds one input row into the accumulators of the given agg calls, skipping the calls
+/// whose `FILTER (WHERE ...)` does not evaluate to true for this row.
+fn update_accumulators(
+    accs: &mut [Box<dyn Accumulator>],
+    agg_calls: &[ScalarExpression],
+    tuple: &Tuple,
+) -> Result<(), ExecutorError> {
+    for (acc, expr) in accs.iter_mut().zip(agg_calls) {
+        if let ScalarExpression::AggCall { args, filter, order_by, .. } = expr {
+            if let Some(filter) = filter {
+                if !matches!(filter.eval_column(tuple)?.as_ref(), DataValue::Boolean(Some(true))) {
+                    continue;
+                }
+            }
+            let value = args[0].eval_column(tuple)?;
+
+            if order_by.is_empty() {
+                acc.update_value(&value)?;
+            } else {
+                let keys = order_by
+                    .iter()
+                    .map(|field| field.expr.eval_column(tuple))
+                    .try_collect()?;
+
+                acc.update_value_with_keys(&value, keys)?;
+            }
+        } else {
+            unreachable!()
+        }
+    }
+
+    Ok(())
+}
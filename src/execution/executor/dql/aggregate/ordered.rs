@@ -0,0 +1,63 @@
+use crate::execution::executor::dql::aggregate::{Accumulator, create_accumulator};
+use crate::execution::executor::dql::sort::compare_sort_keys;
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::sort::SortField;
+use crate::types::value::ValueRef;
+
+/// Buffers the input of an aggregate with `ORDER BY` (e.g. `string_agg(x, ',' ORDER BY y)`)
+/// and feeds it to the inner accumulator in the requested order when evaluated.
+pub struct OrderedAccumulator {
+    agg_call: ScalarExpression,
+    sort_fields: Vec<SortField>,
+    buffer: Vec<(Vec<ValueRef>, ValueRef)>,
+    memory_size: usize,
+}
+
+impl OrderedAccumulator {
+    pub fn new(mut agg_call: ScalarExpression) -> Self {
+        let sort_fields = match &mut agg_call {
+            ScalarExpression::AggCall { order_by, .. } => std::mem::take(order_by),
+            _ => unreachable!(),
+        };
+
+        Self {
+            agg_call,
+            sort_fields,
+            buffer: vec![],
+            memory_size: 0,
+        }
+    }
+}
+
+impl Accumulator for OrderedAccumulator {
+    fn update_value(&mut self, value: &ValueRef) -> Result<(), ExecutorError> {
+        self.update_value_with_keys(value, vec![])
+    }
+
+    fn update_value_with_keys(&mut self, value: &ValueRef, keys: Vec<ValueRef>) -> Result<(), ExecutorError> {
+        self.memory_size += value.memory_size() + keys.iter().map(|key| key.memory_size()).sum::<usize>();
+        self.buffer.push((keys, value.clone()));
+
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ValueRef, ExecutorError> {
+        let mut buffer = self.buffer.iter().collect::<Vec<_>>();
+        // stable sort: rows with equal keys keep their input order
+        buffer.sort_by(|(keys_1, _), (keys_2, _)| {
+            compare_sort_keys(&self.sort_fields, keys_1, keys_2)
+        });
+
+        let mut inner = create_accumulator(&self.agg_call);
+        for (_, value) in buffer {
+            inner.update_value(value)?;
+        }
+
+        inner.evaluate()
+    }
+
+    fn memory_size(&self) -> usize {
+        self.memory_size
+    }
+}
@@ -1,7 +1,9 @@
+use std::sync::Arc;
 use futures_async_stream::try_stream;
 use itertools::Itertools;
 use crate::execution::executor::{BoxedExecutor, Executor};
-use crate::execution::executor::dql::aggregate::create_accumulators;
+use crate::execution::executor::dql::aggregate::{accumulators_memory_size, create_accumulators, update_accumulators};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
 use crate::execution::ExecutorError;
 use crate::expression::ScalarExpression;
 use crate::planner::operator::aggregate::AggregateOperator;
@@ -12,6 +14,7 @@ use crate::types::value::ValueRef;
 pub struct SimpleAggExecutor {
     pub agg_calls: Vec<ScalarExpression>,
     pub input: BoxedExecutor,
+    memory: Arc<MemoryTracker>,
 }
 
 impl From<(AggregateOperator, BoxedExecutor)> for SimpleAggExecutor {
@@ -19,6 +22,7 @@ impl From<(AggregateOperator, BoxedExecutor)> for SimpleAggExecutor {
         SimpleAggExecutor {
             agg_calls,
             input,
+            memory: Default::default(),
         }
     }
 }
@@ -30,10 +34,17 @@ impl<S: Storage> Executor<S> for SimpleAggExecutor {
 }
 
 impl SimpleAggExecutor {
+    /// The values kept by the accumulators, e.g. of `count(DISTINCT ...)`, are reserved from it.
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
     #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
     pub async fn _execute(self) {
         let mut accs = create_accumulators(&self.agg_calls);
         let mut columns_option = None;
+        let mut reservation = MemoryReservation::new(self.memory.clone());
 
         #[for_await]
         for tuple in self.input {
@@ -46,19 +57,9 @@ impl SimpleAggExecutor {
                     .collect_vec()
             });
 
-            let values: Vec<ValueRef> = self.agg_calls
-                .iter()
-                .map(|expr| match expr {
-                    ScalarExpression::AggCall { args, .. } => {
-                        args[0].eval_column(&tuple)
-                    }
-                    _ => unreachable!()
-                })
-                .try_collect()?;
-
-            for (acc, value) in accs.iter_mut().zip_eq(values.iter()) {
-                acc.update_value(value)?;
-            }
+            let accs_size = accumulators_memory_size(&accs);
+            update_accumulators(&mut accs, &self.agg_calls, &tuple)?;
+            reservation.try_grow(accumulators_memory_size(&accs).saturating_sub(accs_size))?;
         }
 
         if let Some(columns) = columns_option {
@@ -0,0 +1,59 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use ahash::RandomState;
+use crate::execution::executor::dql::aggregate::Accumulator;
+use crate::execution::ExecutorError;
+use crate::types::LogicalType;
+use crate::types::value::{DataValue, ValueRef};
+
+pub struct StringAggAccumulator {
+    delimiter: String,
+    distinct_values: Option<HashSet<ValueRef, RandomState>>,
+    distinct_memory_size: usize,
+    result: Option<String>,
+}
+
+impl StringAggAccumulator {
+    pub fn new(delimiter: String, distinct: bool) -> Self {
+        Self {
+            delimiter,
+            distinct_values: distinct.then(HashSet::default),
+            distinct_memory_size: 0,
+            result: None,
+        }
+    }
+}
+
+impl Accumulator for StringAggAccumulator {
+    fn update_value(&mut self, value: &ValueRef) -> Result<(), ExecutorError> {
+        if value.is_null() {
+            return Ok(());
+        }
+        if let Some(distinct_values) = &mut self.distinct_values {
+            if !distinct_values.insert(value.clone()) {
+                return Ok(());
+            }
+            self.distinct_memory_size += value.memory_size();
+        }
+
+        if let DataValue::Utf8(Some(string)) = DataValue::clone(value).cast(&LogicalType::Varchar)? {
+            match &mut self.result {
+                Some(result) => {
+                    result.push_str(&self.delimiter);
+                    result.push_str(&string);
+                }
+                None => self.result = Some(string),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn evaluate(&self) -> Result<ValueRef, ExecutorError> {
+        Ok(Arc::new(DataValue::Utf8(self.result.clone())))
+    }
+
+    fn memory_size(&self) -> usize {
+        self.distinct_memory_size + self.result.as_ref().map_or(0, String::capacity)
+    }
+}
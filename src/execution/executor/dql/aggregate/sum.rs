@@ -9,38 +9,47 @@ use crate::types::LogicalType;
 use crate::types::value::{DataValue, ValueRef};
 
 pub struct SumAccumulator {
-    result: DataValue,
+    ty: LogicalType,
+    /// `None` until a value which isn't NULL, the sum of no values is NULL
+    result: Option<DataValue>,
 }
 
 impl SumAccumulator {
     pub fn new(ty: &LogicalType) -> Self {
         assert!(ty.is_numeric());
 
-        Self { result: DataValue::init(&ty) }
+        Self { ty: *ty, result: None }
     }
 }
 
 impl Accumulator for SumAccumulator {
     fn update_value(&mut self, value: &ValueRef) -> Result<(), ExecutorError> {
         if !value.is_null() {
-            self.result  = binary_op(
-                &self.result,
+            let result = self.result
+                .take()
+                .unwrap_or_else(|| DataValue::init(&self.ty));
+
+            self.result = Some(binary_op(
+                &result,
                 value,
                 &BinaryOperator::Plus
-            )?;
+            )?);
         }
 
         Ok(())
     }
 
     fn evaluate(&self) -> Result<ValueRef, ExecutorError> {
-        Ok(Arc::new(self.result.clone()))
+        Ok(Arc::new(self.result
+            .clone()
+            .unwrap_or_else(|| DataValue::none(&self.ty))))
     }
 }
 
 pub struct DistinctSumAccumulator {
     distinct_values: HashSet<ValueRef, RandomState>,
-    inner: SumAccumulator
+    inner: SumAccumulator,
+    memory_size: usize,
 }
 
 impl DistinctSumAccumulator {
@@ -48,6 +57,7 @@ impl DistinctSumAccumulator {
         Self {
             distinct_values: HashSet::default(),
             inner: SumAccumulator::new(ty),
+            memory_size: 0,
         }
     }
 }
@@ -57,6 +67,7 @@ impl Accumulator for DistinctSumAccumulator {
         if !self.distinct_values.contains(value) {
             self.distinct_values.insert(value.clone());
             self.inner.update_value(value)?;
+            self.memory_size += value.memory_size();
         }
 
         Ok(())
@@ -65,4 +76,8 @@ impl Accumulator for DistinctSumAccumulator {
     fn evaluate(&self) -> Result<ValueRef, ExecutorError> {
         self.inner.evaluate()
     }
+
+    fn memory_size(&self) -> usize {
+        self.memory_size
+    }
 }
\ No newline at end of file
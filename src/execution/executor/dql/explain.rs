@@ -0,0 +1,114 @@
+use std::sync::Arc;
+use futures_async_stream::try_stream;
+use crate::catalog::{ColumnCatalog, ColumnDesc};
+use crate::execution::executor::{BoxedExecutor, build_with_metrics, Executor, ExecutionContext, PlanMetrics};
+use crate::execution::ExecutorError;
+use crate::optimizer::OptimizerTrace;
+use crate::planner::LogicalPlan;
+use crate::planner::operator::explain::ExplainOperator;
+use crate::storage::Storage;
+use crate::types::LogicalType;
+use crate::types::tuple::Tuple;
+use crate::types::value::DataValue;
+
+/// Outputs the plan as rows of a single `PLAN` column, one line per row. The plan itself is
+/// only executed for `EXPLAIN ANALYZE`, whose lines carry the metrics of each operator.
+pub struct Explain {
+    op: ExplainOperator,
+    plan: LogicalPlan,
+    ctx: ExecutionContext,
+}
+
+impl From<(ExplainOperator, LogicalPlan)> for Explain {
+    fn from((op, plan): (ExplainOperator, LogicalPlan)) -> Self {
+        Explain {
+            op,
+            plan,
+            ctx: Default::default(),
+        }
+    }
+}
+
+impl<S: Storage> Executor<S> for Explain {
+    fn execute(self, storage: &S) -> BoxedExecutor {
+        self._execute(storage.clone())
+    }
+}
+
+impl Explain {
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    pub async fn _execute<S: Storage>(self, storage: S) {
+        let column = Arc::new(ColumnCatalog::new(
+            "PLAN".to_string(),
+            false,
+            ColumnDesc::new(LogicalType::Varchar, false)
+        ));
+        let mut plan_metrics = None;
+
+        if self.op.analyze {
+            let mut plan = self.plan.clone();
+            plan.resolve_column_refs()?;
+            let (executor, metrics) = build_with_metrics(plan, &storage, &self.ctx);
+
+            #[for_await]
+            for tuple in executor {
+                let _ = tuple?;
+            }
+            plan_metrics = Some(metrics);
+        }
+
+        for line in self.explain(plan_metrics).lines() {
+            yield Tuple {
+                id: None,
+                columns: vec![column.clone()],
+                values: vec![Arc::new(DataValue::Utf8(Some(line.to_string())))],
+            };
+        }
+    }
+
+    fn explain(self, plan_metrics: Option<PlanMetrics>) -> String {
+        let Explain { op: ExplainOperator { verbose, source_plan, traces, .. }, plan, .. } = self;
+        let mut output = String::new();
+
+        if verbose {
+            if let Some(source_plan) = source_plan {
+                output.push_str("== Source Plan ==\n");
+                output.push_str(&source_plan.explain(0));
+            }
+            for OptimizerTrace { name, fired_rules, plan } in traces {
+                let rules = if fired_rules.is_empty() {
+                    "unchanged".to_string()
+                } else {
+                    fired_rules.join(", ")
+                };
+
+                output.push_str(&format!("== {}: {} ==\n", name, rules));
+                output.push_str(&plan.explain(0));
+            }
+        }
+        if let Some(plan_metrics) = plan_metrics {
+            let mut metrics_iter = plan_metrics.into_iter();
+
+            if verbose {
+                output.push_str("== Analyzed Plan ==\n");
+            }
+            output.push_str(&plan.explain_with(0, &mut |_| match metrics_iter.next().flatten() {
+                Some(metrics) => format!(
+                    " (rows={}, time={:?}, peak buffered={})",
+                    metrics.rows(),
+                    metrics.elapsed(),
+                    metrics.peak_buffered()
+                ),
+                None => String::new(),
+            }));
+        } else if !verbose {
+            output.push_str(&plan.explain(0));
+        }
+        output
+    }
+}
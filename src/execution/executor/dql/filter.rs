@@ -35,14 +35,9 @@ impl Filter {
         #[for_await]
         for tuple in input {
             let tuple = tuple?;
-            if let DataValue::Boolean(option) = predicate.eval_column(&tuple)?.as_ref() {
-                if let Some(true) = option{
-                    yield tuple;
-                } else {
-                    continue
-                }
-            } else {
-                unreachable!("only bool");
+            // NULL is not true, e.g. `WHERE NULL`
+            if let DataValue::Boolean(Some(true)) = predicate.eval_column(&tuple)?.as_ref() {
+                yield tuple;
             }
         }
     }
@@ -0,0 +1,169 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use ahash::{HashMap, HashMapExt};
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::catalog::{ColumnCatalog, ColumnRef};
+use crate::execution::executor::dql::join::{columns_filling, compare_join_keys, eval_keys, joins_nullable};
+use crate::execution::executor::{BoxedExecutor, build, Executor, ExecutionContext, try_collect_with_tracker};
+use crate::execution::executor::memory::MemoryReservation;
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::LogicalPlan;
+use crate::planner::operator::apply::ApplyOperator;
+use crate::planner::operator::join::{JoinCondition, JoinType};
+use crate::storage::Storage;
+use crate::types::tuple::Tuple;
+use crate::types::value::{DataValue, ValueRef};
+
+/// Runs the right plan once for every row of the left input, with the correlated columns
+/// replaced by the values of that row, then joins the row with the produced rows.
+///
+/// The right plan is optimized once with the plan of the statement, where the correlated
+/// columns are constants, e.g. `uid = t.id` is pushed into the scan of the subquery.
+///
+/// The rows of the right plan are cached by the values of the correlated columns, so the plan
+/// is only run once for the left rows with the same values. The cache holds at most the memory
+/// budget of the statement and is cleared past it.
+pub struct Apply {
+    on: JoinCondition,
+    ty: JoinType,
+    correlated_columns: Vec<ColumnRef>,
+    right_columns: Vec<ColumnRef>,
+    left_input: BoxedExecutor,
+    right_plan: LogicalPlan,
+    ctx: ExecutionContext,
+}
+
+impl From<(ApplyOperator, BoxedExecutor, LogicalPlan)> for Apply {
+    fn from((ApplyOperator { on, join_type, correlated_columns, right_columns }, left_input, right_plan): (ApplyOperator, BoxedExecutor, LogicalPlan)) -> Self {
+        Apply {
+            on,
+            ty: join_type,
+            correlated_columns,
+            right_columns,
+            left_input,
+            right_plan,
+            ctx: Default::default(),
+        }
+    }
+}
+
+impl<S: Storage> Executor<S> for Apply {
+    fn execute(self, storage: &S) -> BoxedExecutor {
+        self._execute(storage.clone())
+    }
+}
+
+impl Apply {
+    pub fn with_context(mut self, ctx: ExecutionContext) -> Self {
+        self.ctx = ctx;
+        self
+    }
+
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    pub async fn _execute<S: Storage>(self, storage: S) {
+        let Apply { on, ty, correlated_columns, right_columns, left_input, right_plan, ctx } = self;
+
+        if matches!(ty, JoinType::Right | JoinType::Full) {
+            unreachable!("The right side of Apply depends on the left side")
+        }
+        let ((on_left_keys, on_right_keys), filter): ((Vec<ScalarExpression>, Vec<ScalarExpression>), _) = match on {
+            JoinCondition::On { on, filter } => (on.into_iter().unzip(), filter),
+            JoinCondition::None => ((vec![], vec![]), None),
+        };
+        let (left_force_nullable, right_force_nullable) = joins_nullable(&ty);
+        let right_columns = right_columns
+            .iter()
+            .map(|col| {
+                let mut new_catalog = ColumnCatalog::clone(col);
+                new_catalog.nullable = right_force_nullable;
+
+                Arc::new(new_catalog)
+            })
+            .collect_vec();
+
+        let mut cache: HashMap<Vec<ValueRef>, Arc<Vec<Tuple>>> = HashMap::new();
+        let mut reservation = MemoryReservation::new(ctx.memory.clone());
+
+        let mut join_columns_option: Option<Vec<ColumnRef>> = None;
+        #[for_await]
+        for tuple in left_input {
+            let left_tuple: Tuple = tuple?;
+            let mut left_matched = false;
+
+            let join_columns = join_columns_option.get_or_insert_with(|| {
+                let mut join_columns = Vec::new();
+                columns_filling(&left_tuple, &mut join_columns, left_force_nullable);
+                join_columns.extend(right_columns.iter().cloned());
+
+                join_columns
+            });
+
+            let correlated_values: Vec<ValueRef> = correlated_columns
+                .iter()
+                .map(|col| ScalarExpression::ColumnRef(col.clone()).eval_column(&left_tuple))
+                .try_collect()?;
+            let right_tuples = match cache.get(&correlated_values) {
+                Some(right_tuples) => right_tuples.clone(),
+                None => {
+                    let mut plan = right_plan.clone();
+                    plan.replace_column_refs(&|col| {
+                        correlated_columns
+                            .iter()
+                            .position(|correlated| correlated.is_same_column(col))
+                            .map(|i| ScalarExpression::Constant(correlated_values[i].clone()))
+                    });
+                    plan.resolve_column_refs()?;
+                    let mut executor = build(plan, &storage, &ctx);
+                    let right_tuples = Arc::new(try_collect_with_tracker(&mut executor, ctx.memory.clone()).await?);
+                    let size = right_tuples.iter().map(Tuple::memory_size).sum::<usize>();
+
+                    // the cached rows are dropped to make room for the new ones
+                    if reservation.size() + size > ctx.config.memory_budget {
+                        cache.clear();
+                        reservation.free();
+                    }
+                    if size <= ctx.config.memory_budget && reservation.try_grow(size).is_ok() {
+                        let _ = cache.insert(correlated_values, right_tuples.clone());
+                    }
+                    right_tuples
+                }
+            };
+
+            let left_keys = eval_keys(&on_left_keys, &left_tuple)?;
+
+            for right_tuple in right_tuples.iter() {
+                let values = left_tuple.values
+                    .iter()
+                    .chain(right_tuple.values.iter())
+                    .cloned()
+                    .collect_vec();
+                let tuple = Tuple { id: None, columns: join_columns.clone(), values };
+
+                if compare_join_keys(&left_keys, &eval_keys(&on_right_keys, &tuple)?) != Some(Ordering::Equal) {
+                    continue;
+                }
+                if let Some(expr) = &filter {
+                    if !matches!(expr.eval_column(&tuple)?.as_ref(), DataValue::Boolean(Some(true))) {
+                        continue;
+                    }
+                }
+                left_matched = true;
+
+                yield tuple;
+            }
+
+            if !left_matched && matches!(ty, JoinType::Left) {
+                let values = left_tuple.values
+                    .into_iter()
+                    .chain(right_columns
+                        .iter()
+                        .map(|col| Arc::new(DataValue::none(col.datatype()))))
+                    .collect_vec();
+
+                yield Tuple { id: None, columns: join_columns.clone(), values };
+            }
+        }
+    }
+}
@@ -1,23 +1,34 @@
 use std::sync::Arc;
 use ahash::{HashMap, HashMapExt, HashSet, HashSetExt, RandomState};
+use futures::StreamExt;
 use futures_async_stream::try_stream;
 use itertools::Itertools;
-use crate::execution::executor::dql::join::joins_nullable;
-use crate::catalog::{ColumnCatalog, ColumnRef};
-use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::catalog::ColumnRef;
+use crate::execution::executor::dql::join::{columns_filling, join_side_columns, joins_nullable};
+use crate::execution::executor::{BoxedExecutor, DEFAULT_MEMORY_BUDGET, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::spill::{MAX_PARTITION_LEVEL, SpillFile, SpillPartitions};
+use crate::execution::executor::metrics::ExecutorMetrics;
 use crate::execution::ExecutorError;
 use crate::expression::ScalarExpression;
 use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
 use crate::storage::Storage;
 use crate::types::errors::TypeError;
 use crate::types::tuple::Tuple;
-use crate::types::value::DataValue;
+use crate::types::value::{DataValue, ValueRef};
 
 pub struct HashJoin {
     on: JoinCondition,
     ty: JoinType,
     left_input: BoxedExecutor,
-    right_input: BoxedExecutor
+    right_input: BoxedExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory_budget: usize,
+    memory: Arc<MemoryTracker>,
+    level: usize,
+    /// Given by the plan or to the joins of the partitions, which may have no tuples of one
+    /// side, otherwise taken from the first tuples
+    join_columns: Vec<ColumnRef>,
 }
 
 impl From<(JoinOperator, BoxedExecutor, BoxedExecutor)> for HashJoin {
@@ -27,6 +38,11 @@ impl From<(JoinOperator, BoxedExecutor, BoxedExecutor)> for HashJoin {
             ty: join_type,
             left_input,
             right_input,
+            metrics: Default::default(),
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            memory: Default::default(),
+            level: 0,
+            join_columns: vec![],
         }
     }
 }
@@ -38,21 +54,50 @@ impl<S: Storage> Executor<S> for HashJoin {
 }
 
 impl HashJoin {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Past `memory_budget` bytes of the build side, or the memory limit of the tracker, both
+    /// sides are written to partitions in temporary files by their join keys, and the pairs of
+    /// partitions are joined one by one.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// The columns of the left and the right input, the rows of a side without tuples are
+    /// padded with NULLs to them.
+    pub fn with_schemas(mut self, left_columns: Vec<ColumnRef>, right_columns: Vec<ColumnRef>) -> Self {
+        let (left_force_nullable, right_force_nullable) = joins_nullable(&self.ty);
+
+        self.join_columns = join_side_columns(&left_columns, left_force_nullable);
+        self.join_columns.append(&mut join_side_columns(&right_columns, right_force_nullable));
+        self
+    }
+
     #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
     pub async fn _execute(self) {
-        let HashJoin { on, ty, left_input, right_input } = self;
+        let HashJoin { on, ty, left_input, right_input, metrics, memory_budget, memory, level, mut join_columns } = self;
 
         if ty == JoinType::Cross {
             unreachable!("Cross join should not be in HashJoinExecutor");
         }
-        let ((on_left_keys, on_right_keys), filter): ((Vec<ScalarExpression>, Vec<ScalarExpression>), _) = match on {
-            JoinCondition::On { on, filter } => (on.into_iter().unzip(), filter),
+        let ((on_left_keys, on_right_keys), filter): ((Vec<ScalarExpression>, Vec<ScalarExpression>), _) = match &on {
+            JoinCondition::On { on, filter } => (on.iter().cloned().unzip(), filter.clone()),
             JoinCondition::None => unreachable!("HashJoin must has on condition")
         };
 
-        let mut join_columns = Vec::new();
         let mut used_set = HashSet::<u64>::new();
         let mut left_map = HashMap::new();
+        let mut reservation = MemoryReservation::new(memory.clone());
+        let mut left_partitions: Option<SpillPartitions> = None;
 
         let hash_random_state = RandomState::with_seeds(0, 0, 0, 0);
         let (left_force_nullable, right_force_nullable) = joins_nullable(&ty);
@@ -60,37 +105,116 @@ impl HashJoin {
         // build phase:
         // 1.construct hashtable, one hash key may contains multiple rows indices.
         // 2.merged all left tuples.
-        let mut left_init_flag = false;
+        // 3.past the memory budget or the memory limit, partition all left tuples instead.
+        let has_schemas = !join_columns.is_empty();
+        let mut left_init_flag = has_schemas;
         #[for_await]
         for tuple in left_input {
             let tuple: Tuple = tuple?;
-            let hash = Self::hash_row(&on_left_keys, &hash_random_state, &tuple)?;
 
             if !left_init_flag {
-                Self::columns_filling(&tuple, &mut join_columns, left_force_nullable);
+                columns_filling(&tuple, &mut join_columns, left_force_nullable);
                 left_init_flag = true;
             }
+            if let Some(partitions) = &mut left_partitions {
+                partitions.append(&Self::eval_keys(&on_left_keys, &tuple)?, &tuple)?;
+                continue;
+            }
+            let size = tuple.memory_size();
+
+            if !reservation.try_grow_within(size, memory_budget) {
+                if !left_map.is_empty() && level < MAX_PARTITION_LEVEL {
+                    let mut partitions = SpillPartitions::new(level);
+
+                    for tuple in left_map.drain().flat_map(|(_, tuples)| tuples) {
+                        partitions.append(&Self::eval_keys(&on_left_keys, &tuple)?, &tuple)?;
+                        metrics.release(1);
+                    }
+                    partitions.append(&Self::eval_keys(&on_left_keys, &tuple)?, &tuple)?;
+                    reservation.free();
+                    left_partitions = Some(partitions);
+                    continue;
+                }
+                reservation.try_grow(size)?;
+            }
+            let hash = Self::hash_row(&on_left_keys, &hash_random_state, &tuple)?;
 
             left_map
                 .entry(hash)
                 .or_insert(Vec::new())
                 .push(tuple);
+            metrics.buffer(1);
+        }
+
+        // grace hash join: the right side is partitioned alike, tuples with the same keys are
+        // in the partitions of the same index
+        if let Some(left_partitions) = left_partitions {
+            let mut right_partitions = SpillPartitions::new(level);
+            let mut right_init_flag = has_schemas;
+
+            #[for_await]
+            for tuple in right_input {
+                let tuple: Tuple = tuple?;
+
+                if !right_init_flag {
+                    columns_filling(&tuple, &mut join_columns, right_force_nullable);
+                    right_init_flag = true;
+                }
+                right_partitions.append(&Self::eval_keys(&on_right_keys, &tuple)?, &tuple)?;
+            }
+
+            for (left_file, right_file) in left_partitions.into_files().into_iter().zip(right_partitions.into_files()) {
+                let is_needed = match (&left_file, &right_file) {
+                    (Some(_), Some(_)) => true,
+                    (Some(_), None) => matches!(ty, JoinType::Left | JoinType::Full),
+                    (None, Some(_)) => matches!(ty, JoinType::Right | JoinType::Full),
+                    (None, None) => false,
+                };
+                if !is_needed {
+                    continue;
+                }
+                let executor = HashJoin {
+                    on: on.clone(),
+                    ty,
+                    left_input: Self::partition_input(left_file)?,
+                    right_input: Self::partition_input(right_file)?,
+                    metrics: metrics.clone(),
+                    memory_budget,
+                    memory: memory.clone(),
+                    level: level + 1,
+                    join_columns: join_columns.clone(),
+                };
+
+                #[for_await]
+                for tuple in executor._execute() {
+                    yield tuple?;
+                }
+            }
+            return Ok(());
         }
 
         // probe phase
-        let mut right_init_flag = false;
+        let mut right_init_flag = has_schemas;
         #[for_await]
         for tuple in right_input {
             let tuple: Tuple = tuple?;
             let right_cols_len = tuple.columns.len();
-            let hash = Self::hash_row(&on_right_keys, &hash_random_state, &tuple)?;
+            let right_keys = Self::eval_keys(&on_right_keys, &tuple)?;
+            // a NULL key never equals another key, the left rows with NULL keys are never probed
+            let left_tuples = if right_keys.iter().any(|value| value.is_null()) {
+                None
+            } else {
+                let hash = hash_random_state.hash_one(right_keys);
+
+                left_map.get(&hash).map(|tuples| (hash, tuples))
+            };
 
             if !right_init_flag {
-                Self::columns_filling(&tuple, &mut join_columns, right_force_nullable);
+                columns_filling(&tuple, &mut join_columns, right_force_nullable);
                 right_init_flag = true;
             }
 
-            let mut join_tuples = if let Some(tuples) = left_map.get(&hash) {
+            let mut join_tuples = if let Some((hash, tuples)) = left_tuples {
                 let _ = used_set.insert(hash);
 
                 tuples
@@ -183,18 +307,14 @@ impl HashJoin {
         }
     }
 
-    fn columns_filling(tuple: &Tuple, join_columns: &mut Vec<ColumnRef>, force_nullable: bool) {
-        let mut new_columns = tuple.columns.iter()
-            .cloned()
-            .map(|col| {
-                let mut new_catalog = ColumnCatalog::clone(&col);
-                new_catalog.nullable = force_nullable;
+    fn eval_keys(on_keys: &[ScalarExpression], tuple: &Tuple) -> Result<Vec<ValueRef>, TypeError> {
+        let mut values = Vec::with_capacity(on_keys.len());
 
-                Arc::new(new_catalog)
-            })
-            .collect_vec();
+        for expr in on_keys {
+            values.push(expr.eval_column(tuple)?);
+        }
 
-        join_columns.append(&mut new_columns);
+        Ok(values)
     }
 
     fn hash_row(
@@ -202,18 +322,19 @@ impl HashJoin {
         hash_random_state: &RandomState,
         tuple: &Tuple
     ) -> Result<u64, TypeError> {
-        let mut values = Vec::with_capacity(on_keys.len());
+        Ok(hash_random_state.hash_one(Self::eval_keys(on_keys, tuple)?))
+    }
 
-        for expr in on_keys {
-            values.push(expr.eval_column(tuple)?);
+    fn partition_input(file: Option<SpillFile>) -> Result<BoxedExecutor, ExecutorError> {
+        match file {
+            Some(file) => Ok(file.reader()?.into_executor()),
+            None => Ok(futures::stream::empty().boxed()),
         }
-
-        Ok(hash_random_state.hash_one(values))
     }
 }
 
 #[cfg(test)]
-mod test {
+pub(crate) mod test {
     use std::sync::Arc;
     use crate::catalog::{ColumnCatalog, ColumnDesc};
     use crate::execution::executor::{BoxedExecutor, Executor, try_collect};
@@ -230,7 +351,7 @@ mod test {
     use crate::types::tuple::create_table;
     use crate::types::value::DataValue;
 
-    fn build_join_values<S: Storage>(_s: &S) -> (Vec<(ScalarExpression, ScalarExpression)>, BoxedExecutor, BoxedExecutor) {
+    pub(crate) fn build_join_values<S: Storage>(_s: &S) -> (Vec<(ScalarExpression, ScalarExpression)>, BoxedExecutor, BoxedExecutor) {
         let desc = ColumnDesc::new(LogicalType::Integer, false);
 
         let t1_columns = vec![
@@ -396,4 +517,34 @@ mod test {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_grace_full_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (keys, left, right) = build_join_values(&mem_storage);
+
+        let op = JoinOperator {
+            on: JoinCondition::On { on: keys, filter: None },
+            join_type: JoinType::Full,
+        };
+        // every left tuple is over the budget, so both sides are partitioned down to the last level
+        let mut executor = HashJoin::from((op, left, right))
+            .with_memory_budget(1)
+            .execute(&mem_storage);
+        let tuples = try_collect(&mut executor).await?;
+
+        println!("grace_full_test: \n{}", create_table(&tuples));
+
+        assert_eq!(tuples.len(), 5);
+
+        let vec_values = tuples.into_iter().map(|tuple| tuple.values).collect::<Vec<_>>();
+
+        assert!(vec_values.contains(&build_integers(vec![Some(0), Some(2), Some(4), Some(0), Some(2), Some(4)])));
+        assert!(vec_values.contains(&build_integers(vec![Some(1), Some(3), Some(5), Some(1), Some(3), Some(5)])));
+        assert!(vec_values.contains(&build_integers(vec![None, None, None, Some(4), Some(6), Some(8)])));
+        assert!(vec_values.contains(&build_integers(vec![Some(1), Some(3), Some(5), Some(1), Some(1), Some(1)])));
+        assert!(vec_values.contains(&build_integers(vec![Some(3), Some(5), Some(7), None, None, None])));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,303 @@
+use std::sync::Arc;
+use std::cmp::Ordering;
+use ahash::{HashMap, HashMapExt, RandomState};
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::catalog::ColumnRef;
+use crate::execution::executor::dql::join::{compare_join_keys, eval_keys};
+use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::storage::Storage;
+use crate::types::errors::TypeError;
+use crate::types::tuple::Tuple;
+use crate::types::value::{DataValue, ValueRef};
+
+/// Hash join for `LeftSemi`, `LeftAnti` and `NullAwareLeftAnti`, only the left columns are output.
+///
+/// The right input is built into the hash table, and each left row stops probing after
+/// its first match, so it is output at most once. The hash table is reserved from the memory
+/// tracker of the statement.
+pub struct HashSemiJoin {
+    on: JoinCondition,
+    ty: JoinType,
+    left_input: BoxedExecutor,
+    right_input: BoxedExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory: Arc<MemoryTracker>,
+}
+
+impl From<(JoinOperator, BoxedExecutor, BoxedExecutor)> for HashSemiJoin {
+    fn from((JoinOperator { on, join_type }, left_input, right_input): (JoinOperator, BoxedExecutor, BoxedExecutor)) -> Self {
+        HashSemiJoin {
+            on,
+            ty: join_type,
+            left_input,
+            right_input,
+            metrics: Default::default(),
+            memory: Default::default(),
+        }
+    }
+}
+
+impl<S: Storage> Executor<S> for HashSemiJoin {
+    fn execute(self, _: &S) -> BoxedExecutor {
+        self._execute()
+    }
+}
+
+/// A tuple with its evaluated join keys.
+type KeyedTuple = (Vec<ValueRef>, Tuple);
+
+impl HashSemiJoin {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let HashSemiJoin { on, ty, left_input, right_input, metrics, memory } = self;
+
+        if !matches!(ty, JoinType::LeftSemi | JoinType::LeftAnti | JoinType::NullAwareLeftAnti) {
+            unreachable!("{:?} join should not be in HashSemiJoinExecutor", ty);
+        }
+        let ((on_left_keys, on_right_keys), filter): ((Vec<ScalarExpression>, Vec<ScalarExpression>), _) = match on {
+            JoinCondition::On { on, filter } => (on.into_iter().unzip(), filter),
+            JoinCondition::None => unreachable!("HashSemiJoin must has on condition")
+        };
+        let hash_random_state = RandomState::with_seeds(0, 0, 0, 0);
+
+        // build phase: rows with a NULL key never match, they are only kept for `NOT IN`
+        let mut right_map: HashMap<u64, Vec<KeyedTuple>> = HashMap::new();
+        let mut right_null_keys: Vec<KeyedTuple> = Vec::new();
+        let mut right_is_empty = true;
+        let mut right_columns: Vec<ColumnRef> = Vec::new();
+        let mut reservation = MemoryReservation::new(memory);
+
+        #[for_await]
+        for tuple in right_input {
+            let tuple: Tuple = tuple?;
+            let keys = eval_keys(&on_right_keys, &tuple)?;
+
+            if right_is_empty {
+                right_columns = tuple.columns.clone();
+                right_is_empty = false;
+            }
+            reservation.try_grow(tuple.memory_size())?;
+            metrics.buffer(1);
+            if keys.iter().any(|value| value.is_null()) {
+                right_null_keys.push((keys, tuple));
+            } else {
+                right_map
+                    .entry(hash_random_state.hash_one(&keys))
+                    .or_insert(Vec::new())
+                    .push((keys, tuple));
+            }
+        }
+
+        // probe phase
+        let mut join_columns_option: Option<Vec<ColumnRef>> = None;
+        #[for_await]
+        for tuple in left_input {
+            let left_tuple: Tuple = tuple?;
+            let left_keys = eval_keys(&on_left_keys, &left_tuple)?;
+            let left_has_null = left_keys.iter().any(|value| value.is_null());
+
+            let join_columns = join_columns_option.get_or_insert_with(|| left_tuple.columns
+                .iter()
+                .chain(right_columns.iter())
+                .cloned()
+                .collect_vec());
+
+            let mut matched = false;
+            if !left_has_null {
+                if let Some(bucket) = right_map.get(&hash_random_state.hash_one(&left_keys)) {
+                    matched = Self::any_matched(bucket.iter(), &left_tuple, &left_keys, join_columns, &filter, false)?;
+                }
+            }
+
+            let output = match ty {
+                JoinType::LeftSemi => matched,
+                JoinType::LeftAnti => !matched,
+                JoinType::NullAwareLeftAnti if matched || right_is_empty => !matched,
+                JoinType::NullAwareLeftAnti => {
+                    // `x NOT IN (..., NULL)` is unknown unless `x` equals one of the values,
+                    // and so is `NULL NOT IN (...)` with a non-empty list.
+                    let candidates = if left_has_null {
+                        right_map.values().flatten().chain(right_null_keys.iter()).collect_vec()
+                    } else {
+                        right_null_keys.iter().collect_vec()
+                    };
+
+                    !Self::any_matched(candidates.into_iter(), &left_tuple, &left_keys, join_columns, &filter, true)?
+                }
+                _ => unreachable!(),
+            };
+
+            if output {
+                yield left_tuple;
+            }
+        }
+    }
+
+    /// Whether any of the right rows matches the left row, stops at the first match.
+    ///
+    /// `maybe_equal`: a NULL key or an unknown filter result counts as a match, for `NOT IN`.
+    fn any_matched<'a>(
+        right_tuples: impl Iterator<Item = &'a KeyedTuple>,
+        left_tuple: &Tuple,
+        left_keys: &[ValueRef],
+        join_columns: &[ColumnRef],
+        filter: &Option<ScalarExpression>,
+        maybe_equal: bool,
+    ) -> Result<bool, TypeError> {
+        for (right_keys, right_tuple) in right_tuples {
+            let is_equal = if maybe_equal {
+                Self::maybe_equal(left_keys, right_keys)
+            } else {
+                compare_join_keys(left_keys, right_keys) == Some(Ordering::Equal)
+            };
+            if !is_equal {
+                continue;
+            }
+            let Some(expr) = filter else {
+                return Ok(true);
+            };
+            let values = left_tuple.values
+                .iter()
+                .chain(right_tuple.values.iter())
+                .cloned()
+                .collect_vec();
+            let tuple = Tuple { id: None, columns: join_columns.to_vec(), values };
+
+            match expr.eval_column(&tuple)?.as_ref() {
+                DataValue::Boolean(Some(true)) => return Ok(true),
+                DataValue::Boolean(None) if maybe_equal => return Ok(true),
+                _ => (),
+            }
+        }
+
+        Ok(false)
+    }
+
+    /// Whether the keys may be equal, a NULL on either side is unknown instead of false.
+    fn maybe_equal(left_keys: &[ValueRef], right_keys: &[ValueRef]) -> bool {
+        for (left_value, right_value) in left_keys.iter().zip(right_keys) {
+            if left_value.is_null() || right_value.is_null() {
+                continue;
+            }
+            if compare_join_keys(&[left_value.clone()], &[right_value.clone()]) != Some(Ordering::Equal) {
+                return false;
+            }
+        }
+
+        true
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use crate::catalog::{ColumnCatalog, ColumnDesc};
+    use crate::execution::executor::{BoxedExecutor, Executor, try_collect};
+    use crate::execution::executor::dql::join::hash_join::test::build_join_values;
+    use crate::execution::executor::dql::join::hash_semi_join::HashSemiJoin;
+    use crate::execution::executor::dql::test::build_integers;
+    use crate::execution::executor::dql::values::Values;
+    use crate::execution::ExecutorError;
+    use crate::expression::ScalarExpression;
+    use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+    use crate::planner::operator::values::ValuesOperator;
+    use crate::storage::memory::MemStorage;
+    use crate::types::LogicalType;
+    use crate::types::tuple::{create_table, Tuple};
+
+    fn build_nullable_values(mem_storage: &MemStorage, left: Vec<Option<i32>>, right: Vec<Option<i32>>) -> (Vec<(ScalarExpression, ScalarExpression)>, BoxedExecutor, BoxedExecutor) {
+        let desc = ColumnDesc::new(LogicalType::Integer, false);
+        let left_column = Arc::new(ColumnCatalog::new("c1".to_string(), true, desc.clone()));
+        let right_column = Arc::new(ColumnCatalog::new("c2".to_string(), true, desc));
+
+        let on_keys = vec![
+            (ScalarExpression::ColumnRef(left_column.clone()), ScalarExpression::ColumnRef(right_column.clone()))
+        ];
+        let values_left = Values::from(ValuesOperator {
+            rows: left.into_iter().map(|v| build_integers(vec![v])).collect(),
+            columns: vec![left_column],
+        });
+        let values_right = Values::from(ValuesOperator {
+            rows: right.into_iter().map(|v| build_integers(vec![v])).collect(),
+            columns: vec![right_column],
+        });
+
+        (on_keys, values_left.execute(mem_storage), values_right.execute(mem_storage))
+    }
+
+    async fn run(mem_storage: &MemStorage, join_type: JoinType, keys: Vec<(ScalarExpression, ScalarExpression)>, left: BoxedExecutor, right: BoxedExecutor) -> Result<Vec<Tuple>, ExecutorError> {
+        let op = JoinOperator {
+            on: JoinCondition::On { on: keys, filter: None },
+            join_type,
+        };
+        let mut executor = HashSemiJoin::from((op, left, right)).execute(mem_storage);
+
+        try_collect(&mut executor).await
+    }
+
+    #[tokio::test]
+    async fn test_left_semi_and_anti_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (keys, left, right) = build_join_values(&mem_storage);
+        let tuples = run(&mem_storage, JoinType::LeftSemi, keys, left, right).await?;
+
+        println!("left_semi_test: \n{}", create_table(&tuples));
+
+        // the key 1 matches twice, but the left row is output once
+        assert_eq!(tuples.len(), 2);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(0), Some(2), Some(4)]));
+        assert_eq!(tuples[1].values, build_integers(vec![Some(1), Some(3), Some(5)]));
+
+        let (keys, left, right) = build_join_values(&mem_storage);
+        let tuples = run(&mem_storage, JoinType::LeftAnti, keys, left, right).await?;
+
+        assert_eq!(tuples.len(), 1);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(3), Some(5), Some(7)]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_null_aware_anti_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+
+        // 2 NOT IN (1), NULL NOT IN (1) is unknown
+        let (keys, left, right) = build_nullable_values(&mem_storage, vec![Some(1), Some(2), None], vec![Some(1)]);
+        let tuples = run(&mem_storage, JoinType::NullAwareLeftAnti, keys, left, right).await?;
+        assert_eq!(tuples.len(), 1);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(2)]));
+
+        // x NOT IN (1, NULL) is never true
+        let (keys, left, right) = build_nullable_values(&mem_storage, vec![Some(1), Some(2), None], vec![Some(1), None]);
+        let tuples = run(&mem_storage, JoinType::NullAwareLeftAnti, keys, left, right).await?;
+        assert!(tuples.is_empty());
+
+        // while NOT EXISTS keeps the rows without a match
+        let (keys, left, right) = build_nullable_values(&mem_storage, vec![Some(1), Some(2), None], vec![Some(1), None]);
+        let tuples = run(&mem_storage, JoinType::LeftAnti, keys, left, right).await?;
+        assert_eq!(tuples.len(), 2);
+
+        // x NOT IN (empty) is always true
+        let (keys, left, right) = build_nullable_values(&mem_storage, vec![Some(1), None], vec![]);
+        let tuples = run(&mem_storage, JoinType::NullAwareLeftAnti, keys, left, right).await?;
+        assert_eq!(tuples.len(), 2);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,351 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use futures::TryStreamExt;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::catalog::ColumnRef;
+use crate::execution::executor::dql::join::{columns_filling, compare_join_keys, eval_keys, join_side_columns, joins_nullable};
+use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::storage::Storage;
+use crate::types::tuple::Tuple;
+use crate::types::value::{DataValue, ValueRef};
+
+/// Sort-merge join, both inputs must be sorted in ascending order on their equi-join keys.
+///
+/// Rows with the same keys are collected into a group on each side, and the groups are joined
+/// with each other, so only one group per side is buffered at a time, reserved from the memory
+/// tracker of the statement.
+pub struct MergeJoin {
+    on: JoinCondition,
+    ty: JoinType,
+    left_input: BoxedExecutor,
+    right_input: BoxedExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory: Arc<MemoryTracker>,
+    schemas: Option<(Vec<ColumnRef>, Vec<ColumnRef>)>,
+}
+
+impl From<(JoinOperator, BoxedExecutor, BoxedExecutor)> for MergeJoin {
+    fn from((JoinOperator { on, join_type }, left_input, right_input): (JoinOperator, BoxedExecutor, BoxedExecutor)) -> Self {
+        MergeJoin {
+            on,
+            ty: join_type,
+            left_input,
+            right_input,
+            metrics: Default::default(),
+            memory: Default::default(),
+            schemas: None,
+        }
+    }
+}
+
+impl<S: Storage> Executor<S> for MergeJoin {
+    fn execute(self, _: &S) -> BoxedExecutor {
+        self._execute()
+    }
+}
+
+/// A tuple with its evaluated join keys.
+type KeyedTuple = (Vec<ValueRef>, Tuple);
+
+impl MergeJoin {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// The columns of the left and the right input, the rows of a side without tuples are
+    /// padded with NULLs to them. Without them the columns are taken from the first tuples.
+    pub fn with_schemas(mut self, left_columns: Vec<ColumnRef>, right_columns: Vec<ColumnRef>) -> Self {
+        self.schemas = Some((left_columns, right_columns));
+        self
+    }
+
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let MergeJoin { on, ty, mut left_input, mut right_input, metrics, memory, schemas } = self;
+
+        if matches!(ty, JoinType::Cross) {
+            unreachable!("Cross join should not be in MergeJoinExecutor");
+        }
+        let ((on_left_keys, on_right_keys), filter): ((Vec<ScalarExpression>, Vec<ScalarExpression>), _) = match on {
+            JoinCondition::On { on, filter } => (on.into_iter().unzip(), filter),
+            JoinCondition::None => unreachable!("MergeJoin must has on condition")
+        };
+        let (left_force_nullable, right_force_nullable) = joins_nullable(&ty);
+        let mut reservation = MemoryReservation::new(memory);
+
+        let mut left_next = Self::next_keyed(&mut left_input, &on_left_keys).await?;
+        let mut right_next = Self::next_keyed(&mut right_input, &on_right_keys).await?;
+
+        let mut left_columns = Vec::new();
+        let mut right_columns = Vec::new();
+        if let Some((left_schema, right_schema)) = schemas {
+            left_columns = join_side_columns(&left_schema, left_force_nullable);
+            right_columns = join_side_columns(&right_schema, right_force_nullable);
+        } else {
+            if let Some((_, tuple)) = &left_next {
+                columns_filling(tuple, &mut left_columns, left_force_nullable);
+            }
+            if let Some((_, tuple)) = &right_next {
+                columns_filling(tuple, &mut right_columns, right_force_nullable);
+            }
+        }
+        let join_columns = left_columns
+            .iter()
+            .chain(right_columns.iter())
+            .cloned()
+            .collect_vec();
+
+        loop {
+            let ordering = match (&left_next, &right_next) {
+                (None, None) => break,
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (Some((left_keys, _)), Some((right_keys, _))) => {
+                    match compare_join_keys(left_keys, right_keys) {
+                        Some(ordering) => ordering,
+                        // a NULL key never matches, emit the side which holds it, as a key of
+                        // the right side which can't be casted to the type of the left one
+                        None => if left_keys.iter().any(|value| value.is_null()) {
+                            Ordering::Less
+                        } else {
+                            Ordering::Greater
+                        }
+                    }
+                }
+            };
+
+            match ordering {
+                Ordering::Less => {
+                    let (_, tuple) = left_next.take().unwrap();
+
+                    if matches!(ty, JoinType::Left | JoinType::Full) {
+                        yield Self::pad_right(tuple, &join_columns, &right_columns);
+                    }
+                    left_next = Self::next_keyed(&mut left_input, &on_left_keys).await?;
+                }
+                Ordering::Greater => {
+                    let (_, tuple) = right_next.take().unwrap();
+
+                    if matches!(ty, JoinType::Right | JoinType::Full) {
+                        yield Self::pad_left(tuple, &join_columns, &left_columns);
+                    }
+                    right_next = Self::next_keyed(&mut right_input, &on_right_keys).await?;
+                }
+                Ordering::Equal => {
+                    // collect the rows with the same keys on both sides
+                    let mut left_group = vec![left_next.take().unwrap()];
+                    reservation.try_grow(left_group[0].1.memory_size())?;
+                    loop {
+                        left_next = Self::next_keyed(&mut left_input, &on_left_keys).await?;
+
+                        match left_next.take() {
+                            Some(keyed) if keyed.0 == left_group[0].0 => {
+                                reservation.try_grow(keyed.1.memory_size())?;
+                                left_group.push(keyed);
+                            }
+                            keyed => {
+                                left_next = keyed;
+                                break;
+                            }
+                        }
+                    }
+                    let mut right_group = vec![right_next.take().unwrap()];
+                    reservation.try_grow(right_group[0].1.memory_size())?;
+                    loop {
+                        right_next = Self::next_keyed(&mut right_input, &on_right_keys).await?;
+
+                        match right_next.take() {
+                            Some(keyed) if keyed.0 == right_group[0].0 => {
+                                reservation.try_grow(keyed.1.memory_size())?;
+                                right_group.push(keyed);
+                            }
+                            keyed => {
+                                right_next = keyed;
+                                break;
+                            }
+                        }
+                    }
+
+                    let group_len = left_group.len() + right_group.len();
+                    metrics.buffer(group_len);
+
+                    let mut right_matched = vec![false; right_group.len()];
+                    for (_, left_tuple) in left_group {
+                        let mut left_matched = false;
+
+                        for (i, (_, right_tuple)) in right_group.iter().enumerate() {
+                            let values = left_tuple.values
+                                .iter()
+                                .chain(right_tuple.values.iter())
+                                .cloned()
+                                .collect_vec();
+                            let tuple = Tuple { id: None, columns: join_columns.clone(), values };
+
+                            if let Some(expr) = &filter {
+                                if !matches!(expr.eval_column(&tuple)?.as_ref(), DataValue::Boolean(Some(true))) {
+                                    continue;
+                                }
+                            }
+                            left_matched = true;
+                            right_matched[i] = true;
+
+                            yield tuple;
+                        }
+
+                        if !left_matched && matches!(ty, JoinType::Left | JoinType::Full) {
+                            yield Self::pad_right(left_tuple, &join_columns, &right_columns);
+                        }
+                    }
+
+                    if matches!(ty, JoinType::Right | JoinType::Full) {
+                        for ((_, right_tuple), matched) in right_group.into_iter().zip(right_matched) {
+                            if !matched {
+                                yield Self::pad_left(right_tuple, &join_columns, &left_columns);
+                            }
+                        }
+                    }
+                    metrics.release(group_len);
+                    reservation.free();
+                }
+            }
+        }
+    }
+
+    async fn next_keyed(input: &mut BoxedExecutor, keys: &[ScalarExpression]) -> Result<Option<KeyedTuple>, ExecutorError> {
+        match input.try_next().await? {
+            Some(tuple) => Ok(Some((eval_keys(keys, &tuple)?, tuple))),
+            None => Ok(None),
+        }
+    }
+
+    fn pad_right(tuple: Tuple, join_columns: &[ColumnRef], right_columns: &[ColumnRef]) -> Tuple {
+        let values = tuple.values
+            .into_iter()
+            .chain(right_columns
+                .iter()
+                .map(|col| Arc::new(DataValue::none(col.datatype()))))
+            .collect_vec();
+
+        Tuple { id: None, columns: join_columns.to_vec(), values }
+    }
+
+    fn pad_left(tuple: Tuple, join_columns: &[ColumnRef], left_columns: &[ColumnRef]) -> Tuple {
+        let values = left_columns
+            .iter()
+            .map(|col| Arc::new(DataValue::none(col.datatype())))
+            .chain(tuple.values)
+            .collect_vec();
+
+        Tuple { id: None, columns: join_columns.to_vec(), values }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use crate::catalog::{ColumnCatalog, ColumnDesc};
+    use crate::execution::executor::{BoxedExecutor, Executor, try_collect};
+    use crate::execution::executor::dql::join::merge_join::MergeJoin;
+    use crate::execution::executor::dql::test::build_integers;
+    use crate::execution::executor::dql::values::Values;
+    use crate::execution::ExecutorError;
+    use crate::expression::ScalarExpression;
+    use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+    use crate::planner::operator::values::ValuesOperator;
+    use crate::storage::memory::MemStorage;
+    use crate::types::LogicalType;
+    use crate::types::tuple::create_table;
+    use crate::types::value::DataValue;
+
+    fn build_sorted_values(mem_storage: &MemStorage) -> (Vec<(ScalarExpression, ScalarExpression)>, BoxedExecutor, BoxedExecutor) {
+        let desc = ColumnDesc::new(LogicalType::Integer, false);
+
+        let t1_columns = vec![
+            Arc::new(ColumnCatalog::new("c1".to_string(), true, desc.clone())),
+            Arc::new(ColumnCatalog::new("c2".to_string(), true, desc.clone())),
+        ];
+        let t2_columns = vec![
+            Arc::new(ColumnCatalog::new("c3".to_string(), true, desc.clone())),
+            Arc::new(ColumnCatalog::new("c4".to_string(), true, desc.clone())),
+        ];
+        let on_keys = vec![
+            (ScalarExpression::ColumnRef(t1_columns[0].clone()), ScalarExpression::ColumnRef(t2_columns[0].clone()))
+        ];
+        let build_rows = |rows: Vec<(i32, i32)>| rows
+            .into_iter()
+            .map(|(v1, v2)| vec![
+                Arc::new(DataValue::Int32(Some(v1))),
+                Arc::new(DataValue::Int32(Some(v2))),
+            ])
+            .collect();
+
+        let values_t1 = Values::from(ValuesOperator {
+            rows: build_rows(vec![(0, 0), (1, 1), (1, 2), (3, 3)]),
+            columns: t1_columns,
+        });
+        let values_t2 = Values::from(ValuesOperator {
+            rows: build_rows(vec![(1, 10), (1, 11), (2, 12), (3, 13)]),
+            columns: t2_columns,
+        });
+
+        (on_keys, values_t1.execute(mem_storage), values_t2.execute(mem_storage))
+    }
+
+    #[tokio::test]
+    async fn test_inner_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (keys, left, right) = build_sorted_values(&mem_storage);
+
+        let op = JoinOperator {
+            on: JoinCondition::On { on: keys, filter: None },
+            join_type: JoinType::Inner,
+        };
+        let mut executor = MergeJoin::from((op, left, right)).execute(&mem_storage);
+        let tuples = try_collect(&mut executor).await?;
+
+        println!("merge_inner_test: \n{}", create_table(&tuples));
+
+        assert_eq!(tuples.len(), 5);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(1), Some(1), Some(1), Some(10)]));
+        assert_eq!(tuples[1].values, build_integers(vec![Some(1), Some(1), Some(1), Some(11)]));
+        assert_eq!(tuples[2].values, build_integers(vec![Some(1), Some(2), Some(1), Some(10)]));
+        assert_eq!(tuples[3].values, build_integers(vec![Some(1), Some(2), Some(1), Some(11)]));
+        assert_eq!(tuples[4].values, build_integers(vec![Some(3), Some(3), Some(3), Some(13)]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_full_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (keys, left, right) = build_sorted_values(&mem_storage);
+
+        let op = JoinOperator {
+            on: JoinCondition::On { on: keys, filter: None },
+            join_type: JoinType::Full,
+        };
+        let mut executor = MergeJoin::from((op, left, right)).execute(&mem_storage);
+        let tuples = try_collect(&mut executor).await?;
+
+        println!("merge_full_test: \n{}", create_table(&tuples));
+
+        assert_eq!(tuples.len(), 7);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(0), Some(0), None, None]));
+        assert_eq!(tuples[5].values, build_integers(vec![None, None, Some(2), Some(12)]));
+        assert_eq!(tuples[6].values, build_integers(vec![Some(3), Some(3), Some(3), Some(13)]));
+
+        Ok(())
+    }
+}
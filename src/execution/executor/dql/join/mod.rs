@@ -1,6 +1,18 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use itertools::Itertools;
+use crate::catalog::{ColumnCatalog, ColumnRef};
+use crate::expression::ScalarExpression;
 use crate::planner::operator::join::JoinType;
+use crate::types::errors::TypeError;
+use crate::types::tuple::Tuple;
+use crate::types::value::{DataValue, ValueRef};
 
+pub(crate) mod apply;
 pub(crate) mod hash_join;
+pub(crate) mod hash_semi_join;
+pub(crate) mod nested_loop_join;
+pub(crate) mod merge_join;
 
 pub fn joins_nullable(join_type: &JoinType) -> (bool, bool) {
     match join_type {
@@ -8,6 +20,78 @@ pub fn joins_nullable(join_type: &JoinType) -> (bool, bool) {
         JoinType::Left => (false, true),
         JoinType::Right => (true, false),
         JoinType::Full => (true, true),
-        JoinType::Cross => (true, true),
+        JoinType::Cross => (false, false),
+        // only the left columns are output
+        JoinType::LeftSemi | JoinType::LeftAnti | JoinType::NullAwareLeftAnti => (false, false),
     }
-}
\ No newline at end of file
+}
+
+fn columns_filling(tuple: &Tuple, join_columns: &mut Vec<ColumnRef>, force_nullable: bool) {
+    join_columns.append(&mut join_side_columns(&tuple.columns, force_nullable));
+}
+
+/// The columns of one side of the join as they are output by the join.
+pub(crate) fn join_side_columns(columns: &[ColumnRef], force_nullable: bool) -> Vec<ColumnRef> {
+    columns.iter()
+        .map(|col| {
+            let mut new_catalog = ColumnCatalog::clone(col);
+            new_catalog.nullable = force_nullable;
+
+            Arc::new(new_catalog)
+        })
+        .collect_vec()
+}
+
+fn eval_keys(keys: &[ScalarExpression], tuple: &Tuple) -> Result<Vec<ValueRef>, TypeError> {
+    keys.iter()
+        .map(|expr| expr.eval_column(tuple))
+        .try_collect()
+}
+
+/// Compare the join keys of both sides, returns `None` if any of the keys is NULL,
+/// because NULL never equals to anything.
+///
+/// The right key is casted to the type of the left one when they are different,
+/// e.g. `INT` and `INT UNSIGNED`, a key which can't be casted never equals either,
+/// e.g. `'a'` and an `INT`.
+fn compare_join_keys(left_keys: &[ValueRef], right_keys: &[ValueRef]) -> Option<Ordering> {
+    for (left_value, right_value) in left_keys.iter().zip(right_keys) {
+        if left_value.is_null() || right_value.is_null() {
+            return None;
+        }
+        let left_type = left_value.logical_type();
+        let ordering = if right_value.logical_type() == left_type {
+            left_value.partial_cmp(right_value)
+        } else {
+            left_value.as_ref().partial_cmp(&DataValue::clone(right_value).cast(&left_type).ok()?)
+        };
+
+        match ordering {
+            Some(Ordering::Equal) => (),
+            ordering => return ordering,
+        }
+    }
+
+    Some(Ordering::Equal)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+    use std::sync::Arc;
+    use crate::execution::executor::dql::join::compare_join_keys;
+    use crate::types::value::DataValue;
+
+    #[test]
+    fn test_compare_join_keys() {
+        let int = |v: i32| Arc::new(DataValue::Int32(Some(v)));
+        let utf8 = |v: &str| Arc::new(DataValue::Utf8(Some(v.to_string())));
+
+        assert_eq!(compare_join_keys(&[int(1)], &[Arc::new(DataValue::UInt32(Some(1)))]), Some(Ordering::Equal));
+        assert_eq!(compare_join_keys(&[int(1)], &[utf8("2")]), Some(Ordering::Less));
+        assert_eq!(compare_join_keys(&[int(1)], &[Arc::new(DataValue::Int32(None))]), None);
+        // the key which can't be casted doesn't match instead of failing the join
+        assert_eq!(compare_join_keys(&[int(1)], &[utf8("a")]), None);
+        assert_eq!(compare_join_keys(&[Arc::new(DataValue::UInt32(Some(1)))], &[int(-1)]), None);
+    }
+}
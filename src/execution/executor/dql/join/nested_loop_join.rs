@@ -0,0 +1,300 @@
+use std::cmp::Ordering;
+use std::sync::Arc;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::catalog::ColumnRef;
+use crate::execution::executor::dql::join::{columns_filling, compare_join_keys, eval_keys, join_side_columns, joins_nullable};
+use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::storage::Storage;
+use crate::types::tuple::Tuple;
+use crate::types::value::{DataValue, ValueRef};
+
+/// Block nested loop join: the right input is buffered as the inner block, then every row of
+/// the left input is compared with all rows of the block. The block is reserved from the memory
+/// tracker of the statement, so a cross join of large inputs fails past its memory limit.
+///
+/// Used for joins without equi-join keys, e.g. `CROSS JOIN` or `ON t1.a < t2.b`. Semi and anti
+/// joins stop at the first match and only output the left row.
+pub struct NestedLoopJoin {
+    on: JoinCondition,
+    ty: JoinType,
+    left_input: BoxedExecutor,
+    right_input: BoxedExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory: Arc<MemoryTracker>,
+    schemas: Option<(Vec<ColumnRef>, Vec<ColumnRef>)>,
+}
+
+impl From<(JoinOperator, BoxedExecutor, BoxedExecutor)> for NestedLoopJoin {
+    fn from((JoinOperator { on, join_type }, left_input, right_input): (JoinOperator, BoxedExecutor, BoxedExecutor)) -> Self {
+        NestedLoopJoin {
+            on,
+            ty: join_type,
+            left_input,
+            right_input,
+            metrics: Default::default(),
+            memory: Default::default(),
+            schemas: None,
+        }
+    }
+}
+
+impl<S: Storage> Executor<S> for NestedLoopJoin {
+    fn execute(self, _: &S) -> BoxedExecutor {
+        self._execute()
+    }
+}
+
+impl NestedLoopJoin {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// The columns of the left and the right input, the rows of a side without tuples are
+    /// padded with NULLs to them. Without them the columns are taken from the first tuples.
+    pub fn with_schemas(mut self, left_columns: Vec<ColumnRef>, right_columns: Vec<ColumnRef>) -> Self {
+        self.schemas = Some((left_columns, right_columns));
+        self
+    }
+
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let NestedLoopJoin { on, ty, left_input, right_input, metrics, memory, schemas } = self;
+
+        let ((on_left_keys, on_right_keys), filter): ((Vec<ScalarExpression>, Vec<ScalarExpression>), _) = match on {
+            JoinCondition::On { on, filter } => (on.into_iter().unzip(), filter),
+            JoinCondition::None => ((vec![], vec![]), None),
+        };
+        let (left_force_nullable, right_force_nullable) = joins_nullable(&ty);
+        // `NullAwareLeftAnti` only differs on NULL keys, without keys it is a plain anti join
+        let is_semi_or_anti = matches!(ty, JoinType::LeftSemi | JoinType::LeftAnti | JoinType::NullAwareLeftAnti);
+
+        // build phase: buffer the inner block.
+        let mut right_tuples = Vec::new();
+        let mut reservation = MemoryReservation::new(memory);
+        #[for_await]
+        for tuple in right_input {
+            let tuple: Tuple = tuple?;
+
+            reservation.try_grow(tuple.memory_size())?;
+            right_tuples.push(tuple);
+            metrics.buffer(1);
+        }
+        let right_keys: Vec<Vec<ValueRef>> = right_tuples
+            .iter()
+            .map(|tuple| eval_keys(&on_right_keys, tuple))
+            .try_collect()?;
+        let mut right_matched = vec![false; right_tuples.len()];
+        let mut right_columns = Vec::new();
+        let mut join_columns_option: Option<Vec<ColumnRef>> = None;
+
+        if let Some((left_schema, right_schema)) = schemas {
+            right_columns = join_side_columns(&right_schema, right_force_nullable);
+            join_columns_option = Some(
+                join_side_columns(&left_schema, left_force_nullable)
+                    .into_iter()
+                    .chain(right_columns.iter().cloned())
+                    .collect_vec()
+            );
+        } else if let Some(tuple) = right_tuples.first() {
+            columns_filling(tuple, &mut right_columns, right_force_nullable);
+        }
+
+        // probe phase
+        #[for_await]
+        for tuple in left_input {
+            let left_tuple: Tuple = tuple?;
+            let mut left_matched = false;
+
+            let join_columns = join_columns_option.get_or_insert_with(|| {
+                let mut join_columns = Vec::new();
+                columns_filling(&left_tuple, &mut join_columns, left_force_nullable);
+                join_columns.extend(right_columns.iter().cloned());
+
+                join_columns
+            });
+
+            let left_keys = eval_keys(&on_left_keys, &left_tuple)?;
+
+            for (i, (right_tuple, right_keys)) in right_tuples.iter().zip(right_keys.iter()).enumerate() {
+                if compare_join_keys(&left_keys, right_keys) != Some(Ordering::Equal) {
+                    continue;
+                }
+                let values = left_tuple.values
+                    .iter()
+                    .chain(right_tuple.values.iter())
+                    .cloned()
+                    .collect_vec();
+                let tuple = Tuple { id: None, columns: join_columns.clone(), values };
+
+                if let Some(expr) = &filter {
+                    if !matches!(expr.eval_column(&tuple)?.as_ref(), DataValue::Boolean(Some(true))) {
+                        continue;
+                    }
+                }
+                left_matched = true;
+                right_matched[i] = true;
+
+                if is_semi_or_anti {
+                    break;
+                }
+                yield tuple;
+            }
+
+            if is_semi_or_anti {
+                if left_matched == matches!(ty, JoinType::LeftSemi) {
+                    yield left_tuple;
+                }
+            } else if !left_matched && matches!(ty, JoinType::Left | JoinType::Full) {
+                let values = left_tuple.values
+                    .into_iter()
+                    .chain(right_columns
+                        .iter()
+                        .map(|col| Arc::new(DataValue::none(col.datatype()))))
+                    .collect_vec();
+
+                yield Tuple { id: None, columns: join_columns.clone(), values };
+            }
+        }
+
+        if matches!(ty, JoinType::Right | JoinType::Full) {
+            let join_columns = join_columns_option.unwrap_or_else(|| right_columns.clone());
+            let left_len = join_columns.len() - right_columns.len();
+
+            for (right_tuple, matched) in right_tuples.into_iter().zip(right_matched) {
+                if matched {
+                    continue;
+                }
+                let values = join_columns[..left_len]
+                    .iter()
+                    .map(|col| Arc::new(DataValue::none(col.datatype())))
+                    .chain(right_tuple.values)
+                    .collect_vec();
+
+                yield Tuple { id: None, columns: join_columns.clone(), values };
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use crate::execution::executor::{Executor, try_collect};
+    use crate::execution::executor::dql::join::hash_join::test::build_join_values;
+    use crate::execution::executor::dql::join::nested_loop_join::NestedLoopJoin;
+    use crate::execution::executor::dql::test::build_integers;
+    use crate::execution::executor::memory::MemoryTracker;
+    use crate::execution::ExecutorError;
+    use crate::expression::{BinaryOperator, ScalarExpression};
+    use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+    use crate::storage::memory::MemStorage;
+    use crate::types::LogicalType;
+    use crate::types::tuple::create_table;
+
+    #[tokio::test]
+    async fn test_cross_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (_, left, right) = build_join_values(&mem_storage);
+
+        let op = JoinOperator {
+            on: JoinCondition::None,
+            join_type: JoinType::Cross,
+        };
+        let mut executor = NestedLoopJoin::from((op, left, right)).execute(&mem_storage);
+        let tuples = try_collect(&mut executor).await?;
+
+        println!("cross_test: \n{}", create_table(&tuples));
+
+        assert_eq!(tuples.len(), 12);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(0), Some(2), Some(4), Some(0), Some(2), Some(4)]));
+        assert_eq!(tuples[11].values, build_integers(vec![Some(3), Some(5), Some(7), Some(1), Some(1), Some(1)]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_non_equi_full_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (keys, left, right) = build_join_values(&mem_storage);
+        let (left_key, right_key) = keys[0].clone();
+
+        // t1.c1 > t2.c4
+        let op = JoinOperator {
+            on: JoinCondition::On {
+                on: vec![],
+                filter: Some(ScalarExpression::Binary {
+                    op: BinaryOperator::Gt,
+                    left_expr: Box::new(left_key),
+                    right_expr: Box::new(right_key),
+                    ty: LogicalType::Boolean,
+                }),
+            },
+            join_type: JoinType::Full,
+        };
+        let mut executor = NestedLoopJoin::from((op, left, right)).execute(&mem_storage);
+        let tuples = try_collect(&mut executor).await?;
+
+        println!("non_equi_full_test: \n{}", create_table(&tuples));
+
+        assert_eq!(tuples.len(), 6);
+        assert_eq!(tuples[0].values, build_integers(vec![Some(0), Some(2), Some(4), None, None, None]));
+        assert_eq!(tuples[1].values, build_integers(vec![Some(1), Some(3), Some(5), Some(0), Some(2), Some(4)]));
+        assert_eq!(tuples[2].values, build_integers(vec![Some(3), Some(5), Some(7), Some(0), Some(2), Some(4)]));
+        assert_eq!(tuples[3].values, build_integers(vec![Some(3), Some(5), Some(7), Some(1), Some(3), Some(5)]));
+        assert_eq!(tuples[4].values, build_integers(vec![Some(3), Some(5), Some(7), Some(1), Some(1), Some(1)]));
+        assert_eq!(tuples[5].values, build_integers(vec![None, None, None, Some(4), Some(6), Some(8)]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_left_join() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (keys, left, right) = build_join_values(&mem_storage);
+
+        let op = JoinOperator {
+            on: JoinCondition::On { on: keys, filter: None },
+            join_type: JoinType::Left,
+        };
+        let mut executor = NestedLoopJoin::from((op, left, right)).execute(&mem_storage);
+        let tuples = try_collect(&mut executor).await?;
+
+        assert_eq!(tuples.len(), 4);
+        assert_eq!(tuples[3].values, build_integers(vec![Some(3), Some(5), Some(7), None, None, None]));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_cross_join_memory_limit() -> Result<(), ExecutorError> {
+        let mem_storage = MemStorage::new();
+        let (_, left, right) = build_join_values(&mem_storage);
+
+        let op = JoinOperator {
+            on: JoinCondition::None,
+            join_type: JoinType::Cross,
+        };
+        let mut executor = NestedLoopJoin::from((op, left, right))
+            .with_memory_tracker(Arc::new(MemoryTracker::new(Some(1))))
+            .execute(&mem_storage);
+
+        assert!(matches!(
+            try_collect(&mut executor).await,
+            Err(ExecutorError::MemoryLimitExceeded { limit: 1, .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -1,18 +1,21 @@
 use futures_async_stream::try_stream;
 use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::execution::executor::exchange::ScanPartition;
 use crate::execution::ExecutorError;
 use crate::planner::operator::scan::ScanOperator;
 use crate::storage::{Table, Transaction, Storage};
 use crate::types::tuple::Tuple;
 
 pub(crate) struct SeqScan {
-    op: ScanOperator
+    op: ScanOperator,
+    partition: Option<ScanPartition>,
 }
 
 impl From<ScanOperator> for SeqScan {
     fn from(op: ScanOperator) -> Self {
         SeqScan {
             op,
+            partition: None,
         }
     }
 }
@@ -24,15 +27,29 @@ impl<S: Storage> Executor<S> for SeqScan {
 }
 
 impl SeqScan {
+    /// Only read the key range of the partition, the scan has no bounds.
+    pub fn with_partition(mut self, partition: Option<ScanPartition>) -> Self {
+        self.partition = partition;
+        self
+    }
+
     #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
     pub async fn _execute<S: Storage>(self, storage: S) {
-        let ScanOperator { table_name,  columns, limit, .. } = self.op;
+        let SeqScan { op: ScanOperator { table_name,  columns, limit, pre_where, .. }, partition } = self;
+
+        let range = match &partition {
+            Some(partition) => match partition.range(&storage, &table_name).await {
+                Some(range) => Some(range),
+                None => return Ok(()),
+            },
+            None => None,
+        };
 
         if let Some(table) = storage.table(&table_name).await {
-            let mut transaction = table.read(
-                limit,
-                columns
-            )?;
+            let mut transaction = match range {
+                Some(range) => table.read_range(range, columns, pre_where)?,
+                None => table.read(limit, columns, pre_where)?,
+            };
 
             while let Some(tuple) =  transaction.next_tuple()? {
                 yield tuple;
@@ -1,15 +1,52 @@
-use std::cmp::Ordering;
+use std::cmp::{Ordering, Reverse};
+use std::collections::BinaryHeap;
+use std::mem;
+use std::sync::Arc;
 use futures_async_stream::try_stream;
-use crate::execution::executor::{BoxedExecutor, Executor};
+use crate::execution::executor::{BoxedExecutor, DEFAULT_MEMORY_BUDGET, Executor};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::spill::SpillFile;
+use crate::execution::executor::metrics::ExecutorMetrics;
 use crate::execution::ExecutorError;
 use crate::planner::operator::sort::{SortField, SortOperator};
 use crate::storage::Storage;
 use crate::types::tuple::Tuple;
+use crate::types::value::ValueRef;
+
+/// Slots of the top-n heap allocated up front, it grows past them up to the limit, which may be
+/// far more than the rows of the input.
+const TOP_N_INITIAL_CAPACITY: usize = 1024;
+
+pub(crate) fn compare_value(value_1: &ValueRef, value_2: &ValueRef, desc: bool, nulls_first: bool) -> Ordering {
+    let ordering = value_1.partial_cmp(value_2)
+        .unwrap_or_else(|| match (value_1.is_null(), value_2.is_null()) {
+            (false, true) => if nulls_first { Ordering::Less } else { Ordering::Greater },
+            (true, false) => if nulls_first { Ordering::Greater } else { Ordering::Less },
+            _ => Ordering::Equal,
+        });
+
+    if desc { ordering.reverse() } else { ordering }
+}
+
+/// Compares two rows of already evaluated sort keys, one key per `SortField`.
+pub(crate) fn compare_sort_keys(sort_fields: &[SortField], keys_1: &[ValueRef], keys_2: &[ValueRef]) -> Ordering {
+    sort_fields
+        .iter()
+        .zip(keys_1.iter().zip(keys_2.iter()))
+        .map(|(SortField { desc, nulls_first, .. }, (value_1, value_2))| {
+            compare_value(value_1, value_2, *desc, *nulls_first)
+        })
+        .find(|ordering| ordering != &Ordering::Equal)
+        .unwrap_or(Ordering::Equal)
+}
 
 pub struct Sort {
     sort_fields: Vec<SortField>,
     limit: Option<usize>,
-    input: BoxedExecutor
+    input: BoxedExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory_budget: usize,
+    memory: Arc<MemoryTracker>,
 }
 
 impl From<(SortOperator, BoxedExecutor)> for Sort {
@@ -18,6 +55,9 @@ impl From<(SortOperator, BoxedExecutor)> for Sort {
             sort_fields,
             limit,
             input,
+            metrics: Default::default(),
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            memory: Default::default(),
         }
     }
 }
@@ -29,46 +69,179 @@ impl<S: Storage> Executor<S> for Sort {
 }
 
 impl Sort {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    /// Past `memory_budget` bytes of buffered tuples, or the memory limit of the tracker, they
+    /// are sorted and written to a temporary file as a run, the runs are merged at the end.
+    pub fn with_memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = memory_budget;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
     #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
     pub async fn _execute(self) {
-        let Sort { sort_fields, limit, input } = self;
-        let mut tuples: Vec<Tuple> = vec![];
+        let Sort { sort_fields, limit, input, metrics, memory_budget, memory } = self;
+        let sort_fields = Arc::new(sort_fields);
+
+        if let Some(limit) = limit {
+            #[for_await]
+            for tuple in Self::top_n(sort_fields, limit, input, metrics, memory) {
+                yield tuple?;
+            }
+            return Ok(());
+        }
+        let mut entries: Vec<SortEntry> = vec![];
+        let mut reservation = MemoryReservation::new(memory);
+        let mut runs = vec![];
 
         #[for_await]
         for tuple in input {
-            tuples.push(tuple?);
+            let tuple = tuple?;
+            let size = tuple.memory_size();
+
+            // the buffered tuples are spilled before they would exceed the budget or the memory
+            // limit, a single tuple is always kept
+            if !reservation.try_grow_within(size, memory_budget) {
+                if !entries.is_empty() {
+                    runs.push(Self::spill_run(&mut entries)?);
+                    metrics.release(runs.last().map_or(0, SpillFile::len));
+                    reservation.free();
+                }
+                reservation.try_grow(size)?;
+            }
+            entries.push(SortEntry::new(&sort_fields, entries.len(), tuple)?);
+            metrics.buffer(1);
         }
+        entries.sort();
 
-        tuples.sort_by(|tuple_1, tuple_2| {
-            let mut ordering = Ordering::Equal;
+        if runs.is_empty() {
+            for SortEntry { tuple, .. } in entries {
+                yield tuple;
+            }
+            return Ok(());
+        }
+        // the tuples in memory are the last run, merged without being written
+        let mut sources: Vec<Box<dyn Iterator<Item = Result<Tuple, ExecutorError>> + Send>> = runs
+            .into_iter()
+            .map(|run| run.reader().map(|reader| Box::new(reader) as Box<dyn Iterator<Item = _> + Send>))
+            .collect::<Result<_, _>>()?;
+        sources.push(Box::new(entries.into_iter().map(|SortEntry { tuple, .. }| Ok(tuple))));
 
-            for SortField { expr, desc, nulls_first } in &sort_fields {
-                let value_1 = expr.eval_column(tuple_1).unwrap();
-                let value_2 = expr.eval_column(tuple_2).unwrap();
+        // k-way merge, ties are taken from the earlier run to keep the sort stable
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (i, source) in sources.iter_mut().enumerate() {
+            if let Some(tuple) = source.next() {
+                heap.push(Reverse(SortEntry::new(&sort_fields, i, tuple?)?));
+            }
+        }
+        while let Some(Reverse(SortEntry { seq, tuple, .. })) = heap.pop() {
+            if let Some(next) = sources[seq].next() {
+                heap.push(Reverse(SortEntry::new(&sort_fields, seq, next?)?));
+            }
+            yield tuple;
+        }
+    }
 
-                ordering = value_1.partial_cmp(&value_2)
-                    .unwrap_or_else(|| match (value_1.is_null(), value_2.is_null()) {
-                        (false, true) => if *nulls_first { Ordering::Less } else { Ordering::Greater },
-                        (true, false) => if *nulls_first { Ordering::Greater } else { Ordering::Less },
-                        _ => Ordering::Equal,
-                    });
+    /// Sort the entries and write them to a temporary file.
+    fn spill_run(entries: &mut Vec<SortEntry>) -> Result<SpillFile, ExecutorError> {
+        entries.sort();
+        let mut run = SpillFile::new()?;
 
-                if *desc {
-                    ordering = ordering.reverse();
-                }
+        for SortEntry { tuple, .. } in entries.drain(..) {
+            run.append(&tuple)?;
+        }
 
-                if ordering != Ordering::Equal {
-                   break
-                }
-            }
+        Ok(run)
+    }
 
-            ordering
-        });
+    /// Keeps the first `limit` tuples in a bounded max-heap, whose top is the last of them. The
+    /// slots of the heap and the tuples in it are reserved from the memory tracker.
+    #[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+    async fn top_n(
+        sort_fields: Arc<Vec<SortField>>,
+        limit: usize,
+        input: BoxedExecutor,
+        metrics: Arc<ExecutorMetrics>,
+        memory: Arc<MemoryTracker>
+    ) {
+        let mut heap: BinaryHeap<SortEntry> = BinaryHeap::with_capacity(limit.min(TOP_N_INITIAL_CAPACITY));
+        let mut reservation = MemoryReservation::new(memory);
+        reservation.try_grow(heap.capacity() * mem::size_of::<SortEntry>())?;
+        let mut seq = 0;
+
+        #[for_await]
+        for tuple in input {
+            let entry = SortEntry::new(&sort_fields, seq, tuple?)?;
+            seq += 1;
 
-        let len = limit.unwrap_or(tuples.len());
+            if heap.len() < limit {
+                if heap.len() == heap.capacity() {
+                    let capacity = heap.capacity();
+                    heap.reserve(1);
+                    reservation.try_grow((heap.capacity() - capacity) * mem::size_of::<SortEntry>())?;
+                }
+                reservation.try_grow(entry.tuple.memory_size())?;
+                heap.push(entry);
+                metrics.buffer(1);
+            } else if matches!(heap.peek(), Some(last) if entry < *last) {
+                reservation.try_grow(entry.tuple.memory_size())?;
+                if let Some(last) = heap.pop() {
+                    reservation.shrink(last.tuple.memory_size());
+                }
+                heap.push(entry);
+            }
+        }
 
-        for tuple in tuples.drain(..len) {
+        for SortEntry { tuple, .. } in heap.into_sorted_vec() {
             yield tuple;
         }
     }
 }
+
+/// A tuple with its sort keys, ties are ordered by `seq` to keep the sort stable.
+struct SortEntry {
+    sort_fields: Arc<Vec<SortField>>,
+    keys: Vec<ValueRef>,
+    seq: usize,
+    tuple: Tuple,
+}
+
+impl SortEntry {
+    fn new(sort_fields: &Arc<Vec<SortField>>, seq: usize, tuple: Tuple) -> Result<Self, ExecutorError> {
+        let keys = sort_fields
+            .iter()
+            .map(|field| field.expr.eval_column(&tuple))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(SortEntry { sort_fields: sort_fields.clone(), keys, seq, tuple })
+    }
+}
+
+impl Ord for SortEntry {
+    fn cmp(&self, other: &Self) -> Ordering {
+        compare_sort_keys(&self.sort_fields, &self.keys, &other.keys)
+            .then(self.seq.cmp(&other.seq))
+    }
+}
+
+impl PartialOrd for SortEntry {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for SortEntry {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for SortEntry {}
@@ -0,0 +1,319 @@
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use ahash::RandomState;
+use futures::{FutureExt, StreamExt, TryStreamExt};
+use futures::lock::Mutex;
+use itertools::Itertools;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tokio::sync::mpsc::error::SendError;
+use crate::catalog::TableName;
+use crate::execution::executor::{_build, BoxedExecutor, ExecutionContext, Executor, PlanMetrics};
+use crate::execution::executor::cancel::cancellable;
+use crate::execution::executor::dql::aggregate::hash_agg::HashAggExecutor;
+use crate::execution::executor::dql::join::hash_join::HashJoin;
+use crate::execution::executor::metrics::{ExecutorMetrics, instrument};
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::LogicalPlan;
+use crate::planner::operator::join::{JoinCondition, JoinType};
+use crate::planner::operator::Operator;
+use crate::storage::{KeyRange, Storage};
+use crate::types::tuple::Tuple;
+
+/// Tuples buffered between the tasks of the exchanges.
+const CHANNEL_CAPACITY: usize = 1024;
+
+/// One of the disjoint key ranges of a table scanned in parallel. The ranges are split once for
+/// all the partitions of the scan, by the first of them to start.
+#[derive(Debug, Clone)]
+pub struct ScanPartition {
+    index: usize,
+    count: usize,
+    ranges: Arc<Mutex<Option<Vec<KeyRange>>>>,
+}
+
+impl ScanPartition {
+    pub fn split(count: usize) -> Vec<ScanPartition> {
+        let ranges = Arc::new(Mutex::new(None));
+
+        (0..count)
+            .map(|index| ScanPartition { index, count, ranges: ranges.clone() })
+            .collect_vec()
+    }
+
+    /// `None` when the table is split into fewer ranges than partitions.
+    pub async fn range<S: Storage>(&self, storage: &S, table_name: &TableName) -> Option<KeyRange> {
+        let mut ranges = self.ranges.lock().await;
+
+        if ranges.is_none() {
+            *ranges = Some(storage.table_ranges(table_name, self.count).await);
+        }
+        ranges.as_ref().and_then(|ranges| ranges.get(self.index).cloned())
+    }
+}
+
+/// The number of the partitions of the parallel executors, only run in the tasks of a tokio
+/// runtime.
+pub fn parallelism<S: Storage>(ctx: &ExecutionContext, storage: &S) -> usize {
+    if ctx.partition.is_none() && storage.supports_parallel_scan() && Handle::try_current().is_ok() {
+        ctx.config.parallelism.max(1)
+    } else {
+        1
+    }
+}
+
+/// Whether the plan is run by parallel executors with exchanges between them:
+/// - filters and projections over a scan without bounds, which are run once per key range of
+/// the table.
+/// - hash aggregations and inner hash joins, which are run once per hash partition of the keys.
+///
+/// Outer hash joins are not, a partition with an empty side would lose the columns of the side.
+pub fn is_parallel(plan: &LogicalPlan) -> bool {
+    match &plan.operator {
+        Operator::Aggregate(op) => !op.groupby_exprs.is_empty(),
+        Operator::Join(op) => op.join_type == JoinType::Inner && op.has_equi_keys(),
+        _ => is_partitionable(plan),
+    }
+}
+
+fn is_partitionable(plan: &LogicalPlan) -> bool {
+    match &plan.operator {
+        Operator::Scan(op) => op.limit == (None, None),
+        Operator::Filter(_) | Operator::Project(_) => is_partitionable(&plan.childrens[0]),
+        _ => false,
+    }
+}
+
+/// Build the plan accepted by `is_parallel` into `parallelism` executors run concurrently,
+/// whose tuples are gathered in no particular order.
+pub(crate) fn build_parallel<S: Storage>(
+    plan: LogicalPlan,
+    storage: &S,
+    ctx: &ExecutionContext,
+    metrics_list: &mut Option<PlanMetrics>,
+    parallelism: usize,
+) -> BoxedExecutor {
+    if is_partitionable(&plan) {
+        return gather(build_partitions(plan, storage, ctx, metrics_list, parallelism));
+    }
+    let LogicalPlan { operator, mut childrens } = plan;
+    let metrics = Arc::new(ExecutorMetrics::default());
+    let is_instrumented = metrics_list.is_some();
+
+    if let Some(metrics_list) = metrics_list {
+        metrics_list.push(Some(metrics.clone()));
+    }
+    // the metrics of the first instance are the ones of the plan
+    let instance_metrics = |i: usize| {
+        if i == 0 {
+            metrics.clone()
+        } else {
+            let instance_metrics = Arc::new(ExecutorMetrics::default());
+            metrics.merge_partition(instance_metrics.clone());
+
+            instance_metrics
+        }
+    };
+    let finish = |executor: BoxedExecutor, metrics: Arc<ExecutorMetrics>| {
+        let executor = cancellable(executor, ctx.cancel.clone());
+
+        if is_instrumented { instrument(executor, metrics) } else { executor }
+    };
+    let memory_budget = (ctx.config.memory_budget / parallelism).max(1);
+
+    let instances = match operator {
+        Operator::Aggregate(op) => {
+            let input = build_input(childrens.remove(0), storage, ctx, metrics_list, parallelism);
+
+            repartition(input, op.groupby_exprs.clone(), parallelism)
+                .into_iter()
+                .enumerate()
+                .map(|(i, input)| {
+                    let metrics = instance_metrics(i);
+                    let executor = HashAggExecutor::from((op.clone(), input))
+                        .with_metrics(metrics.clone())
+                        .with_memory_budget(memory_budget)
+                        .with_memory_tracker(ctx.memory.clone())
+                        .execute(storage);
+
+                    finish(executor, metrics)
+                })
+                .collect_vec()
+        }
+        Operator::Join(op) => {
+            let JoinCondition::On { on, .. } = &op.on else {
+                unreachable!("HashJoin must has on condition")
+            };
+            let (left_keys, right_keys): (Vec<ScalarExpression>, Vec<ScalarExpression>) = on.iter().cloned().unzip();
+            let (left_columns, right_columns) = (childrens[0].output_columns(), childrens[1].output_columns());
+            let left_input = build_input(childrens.remove(0), storage, ctx, metrics_list, parallelism);
+            let right_input = build_input(childrens.remove(0), storage, ctx, metrics_list, parallelism);
+
+            repartition(left_input, left_keys, parallelism)
+                .into_iter()
+                .zip(repartition(right_input, right_keys, parallelism))
+                .enumerate()
+                .map(|(i, (left_input, right_input))| {
+                    let metrics = instance_metrics(i);
+                    let executor = HashJoin::from((op.clone(), left_input, right_input))
+                        .with_metrics(metrics.clone())
+                        .with_memory_budget(memory_budget)
+                        .with_memory_tracker(ctx.memory.clone())
+                        .with_schemas(left_columns.clone(), right_columns.clone())
+                        .execute(storage);
+
+                    finish(executor, metrics)
+                })
+                .collect_vec()
+        }
+        operator => unreachable!("no parallel executor for {:?}", operator),
+    };
+
+    gather(instances)
+}
+
+/// The partitions of the input if it can be partitioned, otherwise the input as a whole.
+fn build_input<S: Storage>(
+    plan: LogicalPlan,
+    storage: &S,
+    ctx: &ExecutionContext,
+    metrics_list: &mut Option<PlanMetrics>,
+    parallelism: usize,
+) -> Vec<BoxedExecutor> {
+    if is_partitionable(&plan) {
+        build_partitions(plan, storage, ctx, metrics_list, parallelism)
+    } else {
+        vec![_build(plan, storage, ctx, metrics_list)]
+    }
+}
+
+/// Build the plan once per partition of its scan, the metrics of the other partitions are
+/// merged into the ones of the first.
+fn build_partitions<S: Storage>(
+    plan: LogicalPlan,
+    storage: &S,
+    ctx: &ExecutionContext,
+    metrics_list: &mut Option<PlanMetrics>,
+    parallelism: usize,
+) -> Vec<BoxedExecutor> {
+    let start = metrics_list.as_ref().map_or(0, Vec::len);
+    let mut executors = Vec::with_capacity(parallelism);
+
+    for (i, partition) in ScanPartition::split(parallelism).into_iter().enumerate() {
+        let ctx = ExecutionContext { partition: Some(partition), ..ctx.clone() };
+
+        if i == 0 {
+            executors.push(_build(plan.clone(), storage, &ctx, metrics_list));
+            continue;
+        }
+        let mut partition_metrics = metrics_list.as_ref().map(|_| Vec::new());
+        executors.push(_build(plan.clone(), storage, &ctx, &mut partition_metrics));
+
+        if let (Some(metrics_list), Some(partition_metrics)) = (metrics_list.as_ref(), partition_metrics) {
+            for (metrics, partition_metrics) in metrics_list[start..].iter().zip(partition_metrics) {
+                if let (Some(metrics), Some(partition_metrics)) = (metrics, partition_metrics) {
+                    metrics.merge_partition(partition_metrics);
+                }
+            }
+        }
+    }
+
+    executors
+}
+
+/// Run the inputs concurrently in tasks and yield their tuples as they arrive.
+pub fn gather(mut inputs: Vec<BoxedExecutor>) -> BoxedExecutor {
+    if inputs.len() == 1 {
+        return inputs.remove(0);
+    }
+    let (sender, receiver) = channel(CHANNEL_CAPACITY);
+
+    for mut input in inputs {
+        let output = sender.clone();
+
+        spawn(
+            async move {
+                while let Some(tuple) = input.try_next().await? {
+                    // the output is dropped, e.g. after an error
+                    if output.send(Ok(tuple)).await.is_err() {
+                        break;
+                    }
+                }
+                Ok(())
+            },
+            vec![sender.clone()],
+        );
+    }
+
+    receiver_stream(receiver)
+}
+
+/// Route the tuples of the inputs to `count` outputs by the hash of their keys, so the tuples
+/// with equal keys are in the same output.
+pub fn repartition(inputs: Vec<BoxedExecutor>, keys: Vec<ScalarExpression>, count: usize) -> Vec<BoxedExecutor> {
+    let (senders, receivers): (Vec<_>, Vec<_>) = (0..count)
+        .map(|_| channel(CHANNEL_CAPACITY))
+        .unzip();
+    let keys = Arc::new(keys);
+
+    for mut input in inputs {
+        let outputs = senders.clone();
+        let keys = keys.clone();
+
+        spawn(
+            async move {
+                let hash_random_state = RandomState::with_seeds(0, 0, 0, 0);
+
+                while let Some(tuple) = input.try_next().await? {
+                    let values: Vec<_> = keys
+                        .iter()
+                        .map(|expr| expr.eval_column(&tuple))
+                        .try_collect()?;
+                    let output = &outputs[(hash_random_state.hash_one(values) % count as u64) as usize];
+
+                    if output.send(Ok(tuple)).await.is_err() && outputs.iter().all(Sender::is_closed) {
+                        break;
+                    }
+                }
+                Ok(())
+            },
+            senders.clone(),
+        );
+    }
+
+    receivers.into_iter().map(receiver_stream).collect_vec()
+}
+
+/// Spawn the producer of an exchange, its error or panic is sent to the first of the outputs
+/// which is still open.
+fn spawn<F>(producer: F, outputs: Vec<Sender<Result<Tuple, ExecutorError>>>)
+where
+    F: Future<Output = Result<(), ExecutorError>> + Send + 'static
+{
+    // detached, it ends once its input or all of its outputs are done
+    drop(tokio::spawn(async move {
+        let result = AssertUnwindSafe(producer)
+            .catch_unwind()
+            .await
+            .unwrap_or_else(|_| Err(ExecutorError::InternalError("parallel executor panicked".to_string())));
+
+        if let Err(err) = result {
+            let mut error = Err(err);
+
+            for output in outputs {
+                match output.send(error).await {
+                    Ok(()) => break,
+                    Err(SendError(returned)) => error = returned,
+                }
+            }
+        }
+    }));
+}
+
+fn receiver_stream(receiver: Receiver<Result<Tuple, ExecutorError>>) -> BoxedExecutor {
+    futures::stream::unfold(receiver, |mut receiver| async move {
+        receiver.recv().await.map(|item| (item, receiver))
+    }).boxed()
+}
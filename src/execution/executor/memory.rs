@@ -0,0 +1,137 @@
+use std::sync::Arc;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::Relaxed;
+use crate::execution::ExecutorError;
+
+/// Bytes of memory held by the executors of a statement, which fails the statement once they
+/// would exceed the limit.
+#[derive(Debug, Default)]
+pub struct MemoryTracker {
+    limit: Option<usize>,
+    used: AtomicUsize,
+    peak: AtomicUsize,
+}
+
+impl MemoryTracker {
+    pub fn new(limit: Option<usize>) -> Self {
+        MemoryTracker {
+            limit,
+            used: AtomicUsize::new(0),
+            peak: AtomicUsize::new(0),
+        }
+    }
+
+    pub fn try_reserve(&self, bytes: usize) -> Result<(), ExecutorError> {
+        let used = self.used.fetch_add(bytes, Relaxed) + bytes;
+
+        if let Some(limit) = self.limit.filter(|limit| used > *limit) {
+            self.used.fetch_sub(bytes, Relaxed);
+
+            return Err(ExecutorError::MemoryLimitExceeded {
+                limit,
+                used: used - bytes,
+                requested: bytes,
+            });
+        }
+        self.peak.fetch_max(used, Relaxed);
+
+        Ok(())
+    }
+
+    pub fn release(&self, bytes: usize) {
+        self.used.fetch_sub(bytes, Relaxed);
+    }
+
+    pub fn used(&self) -> usize {
+        self.used.load(Relaxed)
+    }
+
+    pub fn peak(&self) -> usize {
+        self.peak.load(Relaxed)
+    }
+}
+
+/// The bytes held by a single executor, they are given back to the tracker when it's dropped,
+/// e.g. when a stream isn't polled to the end.
+#[derive(Debug)]
+pub struct MemoryReservation {
+    tracker: Arc<MemoryTracker>,
+    size: usize,
+}
+
+impl MemoryReservation {
+    pub fn new(tracker: Arc<MemoryTracker>) -> Self {
+        MemoryReservation { tracker, size: 0 }
+    }
+
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    pub fn try_grow(&mut self, bytes: usize) -> Result<(), ExecutorError> {
+        self.tracker.try_reserve(bytes)?;
+        self.size += bytes;
+
+        Ok(())
+    }
+
+    /// Grows by `bytes` only if the reservation stays within `budget` and the tracker within its
+    /// limit, for the executors which spill to disk instead, otherwise nothing is reserved.
+    pub fn try_grow_within(&mut self, bytes: usize, budget: usize) -> bool {
+        self.size + bytes <= budget && self.try_grow(bytes).is_ok()
+    }
+
+    pub fn shrink(&mut self, bytes: usize) {
+        let bytes = bytes.min(self.size);
+
+        self.tracker.release(bytes);
+        self.size -= bytes;
+    }
+
+    pub fn free(&mut self) {
+        self.shrink(self.size)
+    }
+}
+
+impl Drop for MemoryReservation {
+    fn drop(&mut self) {
+        self.free()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+    use crate::execution::ExecutorError;
+
+    #[test]
+    fn test_memory_reservation() -> Result<(), ExecutorError> {
+        let tracker = Arc::new(MemoryTracker::new(Some(100)));
+        let mut reservation_1 = MemoryReservation::new(tracker.clone());
+        let mut reservation_2 = MemoryReservation::new(tracker.clone());
+
+        reservation_1.try_grow(60)?;
+        reservation_2.try_grow(40)?;
+        assert!(matches!(
+            reservation_2.try_grow(1),
+            Err(ExecutorError::MemoryLimitExceeded { limit: 100, used: 100, requested: 1 })
+        ));
+        assert_eq!(reservation_2.size(), 40);
+
+        reservation_1.shrink(50);
+        reservation_2.try_grow(1)?;
+        assert_eq!(tracker.used(), 51);
+
+        assert!(!reservation_2.try_grow_within(10, 40));
+        assert!(!reservation_2.try_grow_within(50, 100));
+        assert!(reservation_2.try_grow_within(49, 100));
+        assert_eq!(reservation_2.size(), 90);
+
+        drop(reservation_2);
+        assert_eq!(tracker.used(), 10);
+        assert_eq!(tracker.peak(), 100);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,106 @@
+use std::iter::Sum;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, AtomicUsize};
+use std::sync::atomic::Ordering::Relaxed;
+use std::task::{Context, Poll};
+use std::time::{Duration, Instant};
+use futures::{Stream, StreamExt};
+use futures::stream::BoxStream;
+use parking_lot::Mutex;
+use crate::execution::ExecutorError;
+use crate::types::batch::RecordBatch;
+use crate::types::tuple::Tuple;
+
+/// Runtime statistics of a single executor, collected for `EXPLAIN ANALYZE`.
+#[derive(Debug, Default)]
+pub struct ExecutorMetrics {
+    rows: AtomicUsize,
+    elapsed_nanos: AtomicU64,
+    buffered: AtomicUsize,
+    peak_buffered: AtomicUsize,
+    /// The metrics of the other instances of the executor when it runs in parallel.
+    partitions: Mutex<Vec<Arc<ExecutorMetrics>>>,
+}
+
+impl ExecutorMetrics {
+    /// The executor holds `count` more tuples in memory, e.g. the build side of a hash join.
+    pub fn buffer(&self, count: usize) {
+        let buffered = self.buffered.fetch_add(count, Relaxed) + count;
+
+        self.peak_buffered.fetch_max(buffered, Relaxed);
+    }
+
+    /// The executor no longer holds `count` of its buffered tuples.
+    pub fn release(&self, count: usize) {
+        self.buffered.fetch_sub(count, Relaxed);
+    }
+
+    /// Count the metrics of another instance of the executor in these ones.
+    pub fn merge_partition(&self, metrics: Arc<ExecutorMetrics>) {
+        self.partitions.lock().push(metrics);
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows.load(Relaxed) + self.sum_partitions(ExecutorMetrics::rows)
+    }
+
+    /// Time spent producing the rows, including the time spent in the children. The time of
+    /// the instances run in parallel is added up.
+    pub fn elapsed(&self) -> Duration {
+        Duration::from_nanos(self.elapsed_nanos.load(Relaxed)) + self.sum_partitions(ExecutorMetrics::elapsed)
+    }
+
+    pub fn peak_buffered(&self) -> usize {
+        self.peak_buffered.load(Relaxed) + self.sum_partitions(ExecutorMetrics::peak_buffered)
+    }
+
+    fn sum_partitions<T: Sum<T>>(&self, f: fn(&ExecutorMetrics) -> T) -> T {
+        self.partitions.lock().iter().map(|metrics| f(metrics)).sum()
+    }
+}
+
+/// What an executor yields, by the number of rows in it.
+pub trait Rows {
+    fn rows(&self) -> usize;
+}
+
+impl Rows for Tuple {
+    fn rows(&self) -> usize {
+        1
+    }
+}
+
+impl Rows for RecordBatch {
+    fn rows(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Wrap the executor to count its rows and the time of every poll into `metrics`.
+pub fn instrument<T: Rows + Send + 'static>(
+    input: BoxStream<'static, Result<T, ExecutorError>>,
+    metrics: Arc<ExecutorMetrics>
+) -> BoxStream<'static, Result<T, ExecutorError>> {
+    Instrumented { input, metrics }.boxed()
+}
+
+struct Instrumented<T> {
+    input: BoxStream<'static, Result<T, ExecutorError>>,
+    metrics: Arc<ExecutorMetrics>,
+}
+
+impl<T: Rows> Stream for Instrumented<T> {
+    type Item = Result<T, ExecutorError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let start = Instant::now();
+        let poll = self.input.poll_next_unpin(cx);
+
+        self.metrics.elapsed_nanos.fetch_add(start.elapsed().as_nanos() as u64, Relaxed);
+        if let Poll::Ready(Some(Ok(item))) = &poll {
+            self.metrics.rows.fetch_add(item.rows(), Relaxed);
+        }
+        poll
+    }
+}
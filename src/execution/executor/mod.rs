@@ -1,10 +1,20 @@
 pub(crate) mod dql;
 pub(crate)mod ddl;
 pub(crate)mod dml;
+pub mod cancel;
+pub mod exchange;
+pub(crate) mod metrics;
+pub mod memory;
+pub(crate) mod spill;
+pub mod vectorized;
 
+use std::iter;
+use std::sync::Arc;
+use std::time::Duration;
 use futures::stream::BoxStream;
 use futures::TryStreamExt;
 use crate::execution::executor::ddl::create_table::CreateTable;
+use crate::execution::executor::ddl::analyze::Analyze;
 use crate::execution::executor::ddl::drop_table::DropTable;
 use crate::execution::executor::ddl::truncate::Truncate;
 use crate::execution::executor::dml::delete::Delete;
@@ -13,81 +23,237 @@ use crate::execution::executor::dml::update::Update;
 use crate::execution::executor::dql::aggregate::hash_agg::HashAggExecutor;
 use crate::execution::executor::dql::aggregate::simple_agg::SimpleAggExecutor;
 use crate::execution::executor::dql::dummy::Dummy;
+use crate::execution::executor::dql::explain::Explain;
 use crate::execution::executor::dql::filter::Filter;
+use crate::execution::executor::dql::join::apply::Apply;
 use crate::execution::executor::dql::join::hash_join::HashJoin;
+use crate::execution::executor::dql::join::hash_semi_join::HashSemiJoin;
+use crate::execution::executor::dql::join::merge_join::MergeJoin;
+use crate::execution::executor::dql::join::nested_loop_join::NestedLoopJoin;
 use crate::execution::executor::dql::limit::Limit;
 use crate::execution::executor::dql::projection::Projection;
 use crate::execution::executor::dql::seq_scan::SeqScan;
 use crate::execution::executor::dql::sort::Sort;
 use crate::execution::executor::dql::values::Values;
+use crate::execution::executor::cancel::{cancellable, CancellationToken};
+use crate::execution::executor::exchange::{build_parallel, is_parallel, parallelism, ScanPartition};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::{ExecutorMetrics, instrument};
+use crate::execution::executor::vectorized::{batches_to_rows, build_batches, DEFAULT_BATCH_SIZE, is_vectorized};
 use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
 use crate::planner::LogicalPlan;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
 use crate::planner::operator::Operator;
 use crate::storage::Storage;
 use crate::types::tuple::Tuple;
 
 pub type BoxedExecutor = BoxStream<'static, Result<Tuple, ExecutorError>>;
 
+/// Bytes of tuples an operator may buffer by default before spilling them to temporary files.
+pub const DEFAULT_MEMORY_BUDGET: usize = 64 * 1024 * 1024;
+
+/// Settings of the executors of a statement.
+#[derive(Debug, Clone)]
+pub struct ExecutorConfig {
+    /// Bytes of tuples an operator may buffer before spilling them to temporary files.
+    pub memory_budget: usize,
+    /// Bytes the executors of a statement may hold in total, past it the statement fails with
+    /// `ExecutorError::MemoryLimitExceeded`. `None` for no limit.
+    pub memory_limit: Option<usize>,
+    /// Time a statement may run before it fails with `ExecutorError::StatementTimeout`.
+    /// `None` for no timeout.
+    pub statement_timeout: Option<Duration>,
+    /// Run the filters, projections, hash aggregations and inner hash joins on batches of
+    /// rows stored column by column.
+    pub vectorized: bool,
+    /// Rows of the batches of the vectorized executors.
+    pub batch_size: usize,
+    /// Partitions of the scans, hash aggregations and inner hash joins run in parallel, in the
+    /// tasks of the tokio runtime. The scans are split by the histogram of the primary key, so
+    /// only the tables with statistics are scanned in parallel.
+    pub parallelism: usize,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        ExecutorConfig {
+            memory_budget: DEFAULT_MEMORY_BUDGET,
+            memory_limit: None,
+            statement_timeout: None,
+            vectorized: false,
+            batch_size: DEFAULT_BATCH_SIZE,
+            parallelism: 1,
+        }
+    }
+}
+
+/// What the executors of a single statement share.
+#[derive(Debug, Clone, Default)]
+pub struct ExecutionContext {
+    pub config: ExecutorConfig,
+    pub memory: Arc<MemoryTracker>,
+    pub cancel: CancellationToken,
+    /// The key range read by the scan of a plan built once per partition.
+    pub partition: Option<ScanPartition>,
+}
+
+impl ExecutionContext {
+    pub fn new(config: ExecutorConfig) -> Self {
+        let memory = Arc::new(MemoryTracker::new(config.memory_limit));
+        let cancel = CancellationToken::default().with_timeout(config.statement_timeout);
+
+        ExecutionContext { config, memory, cancel, partition: None }
+    }
+}
+
 pub trait Executor<S: Storage> {
     fn execute(self, storage: &S) -> BoxedExecutor;
 }
 
-pub fn build<S: Storage>(plan: LogicalPlan, storage: &S) -> BoxedExecutor {
+pub fn build<S: Storage>(plan: LogicalPlan, storage: &S, ctx: &ExecutionContext) -> BoxedExecutor {
+    _build(plan, storage, ctx, &mut None)
+}
+
+/// Metrics of the operators in the pre-order of the plan, the same order as the lines of
+/// `LogicalPlan::explain`. The operators which are not built by `build`, e.g. the right side
+/// of `Apply`, have no metrics.
+pub type PlanMetrics = Vec<Option<Arc<ExecutorMetrics>>>;
+
+/// Like `build`, but every executor is wrapped to collect its metrics, for `EXPLAIN ANALYZE`.
+pub fn build_with_metrics<S: Storage>(plan: LogicalPlan, storage: &S, ctx: &ExecutionContext) -> (BoxedExecutor, PlanMetrics) {
+    let mut metrics_list = Some(Vec::new());
+    let executor = _build(plan, storage, ctx, &mut metrics_list);
+
+    (executor, metrics_list.unwrap_or_default())
+}
+
+fn _build<S: Storage>(
+    plan: LogicalPlan,
+    storage: &S,
+    ctx: &ExecutionContext,
+    metrics_list: &mut Option<PlanMetrics>
+) -> BoxedExecutor {
+    let parallelism = parallelism(ctx, storage);
+
+    if parallelism > 1 && is_parallel(&plan) {
+        return build_parallel(plan, storage, ctx, metrics_list, parallelism);
+    }
+    if ctx.config.vectorized && is_vectorized(&plan) {
+        return batches_to_rows(build_batches(plan, storage, ctx, metrics_list));
+    }
     let LogicalPlan { operator, mut childrens } = plan;
+    let metrics = Arc::new(ExecutorMetrics::default());
 
-    match operator {
+    if let Some(metrics_list) = metrics_list {
+        metrics_list.push(Some(metrics.clone()));
+    }
+    let executor = match operator {
         Operator::Dummy => Dummy{ }.execute(storage),
         Operator::Aggregate(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             if op.groupby_exprs.is_empty() {
-                SimpleAggExecutor::from((op, input)).execute(storage)
+                SimpleAggExecutor::from((op, input))
+                    .with_memory_tracker(ctx.memory.clone())
+                    .execute(storage)
             } else {
-                HashAggExecutor::from((op, input)).execute(storage)
+                HashAggExecutor::from((op, input))
+                    .with_metrics(metrics.clone())
+                    .with_memory_budget(ctx.config.memory_budget)
+                    .with_memory_tracker(ctx.memory.clone())
+                    .execute(storage)
             }
         }
         Operator::Filter(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             Filter::from((op, input)).execute(storage)
         }
         Operator::Join(op) => {
-            let left_input = build(childrens.remove(0), storage);
-            let right_input = build(childrens.remove(0), storage);
+            let is_sorted = is_sorted_on_join_keys(&op, &childrens[0], &childrens[1], storage, parallelism);
+            let (left_columns, right_columns) = (childrens[0].output_columns(), childrens[1].output_columns());
+            let left_input = _build(childrens.remove(0), storage, ctx, metrics_list);
+            let right_input = _build(childrens.remove(0), storage, ctx, metrics_list);
+            let is_semi_or_anti = matches!(
+                op.join_type,
+                JoinType::LeftSemi | JoinType::LeftAnti | JoinType::NullAwareLeftAnti
+            );
+
+            if is_semi_or_anti && op.has_equi_keys() {
+                HashSemiJoin::from((op, left_input, right_input))
+                    .with_metrics(metrics.clone())
+                    .with_memory_tracker(ctx.memory.clone())
+                    .execute(storage)
+            } else if !op.has_equi_keys() {
+                NestedLoopJoin::from((op, left_input, right_input))
+                    .with_metrics(metrics.clone())
+                    .with_memory_tracker(ctx.memory.clone())
+                    .with_schemas(left_columns, right_columns)
+                    .execute(storage)
+            } else if is_sorted {
+                MergeJoin::from((op, left_input, right_input))
+                    .with_metrics(metrics.clone())
+                    .with_memory_tracker(ctx.memory.clone())
+                    .with_schemas(left_columns, right_columns)
+                    .execute(storage)
+            } else {
+                HashJoin::from((op, left_input, right_input))
+                    .with_metrics(metrics.clone())
+                    .with_memory_budget(ctx.config.memory_budget)
+                    .with_memory_tracker(ctx.memory.clone())
+                    .with_schemas(left_columns, right_columns)
+                    .execute(storage)
+            }
+        }
+        Operator::Apply(op) => {
+            let left_input = _build(childrens.remove(0), storage, ctx, metrics_list);
+            let right_plan = childrens.remove(0);
 
-            HashJoin::from((op, left_input, right_input)).execute(storage)
+            if let Some(metrics_list) = metrics_list {
+                metrics_list.extend(iter::repeat(None).take(plan_size(&right_plan)));
+            }
+            Apply::from((op, left_input, right_plan))
+                .with_context(ctx.clone())
+                .execute(storage)
         }
         Operator::Project(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             Projection::from((op, input)).execute(storage)
         }
         Operator::Scan(op) => {
-            SeqScan::from(op).execute(storage)
+            SeqScan::from(op)
+                .with_partition(ctx.partition.clone())
+                .execute(storage)
         }
         Operator::Sort(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
-            Sort::from((op, input)).execute(storage)
+            Sort::from((op, input))
+                .with_metrics(metrics.clone())
+                .with_memory_budget(ctx.config.memory_budget)
+                .with_memory_tracker(ctx.memory.clone())
+                .execute(storage)
         }
         Operator::Limit(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             Limit::from((op, input)).execute(storage)
         }
         Operator::Insert(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             Insert::from((op, input)).execute(storage)
         }
         Operator::Update(op) => {
-            let input = build(childrens.remove(0), storage);
-            let values = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
+            let values = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             Update::from((op, input, values)).execute(storage)
         }
         Operator::Delete(op) => {
-            let input = build(childrens.remove(0), storage);
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
 
             Delete::from((op, input)).execute(storage)
         }
@@ -103,13 +269,89 @@ pub fn build<S: Storage>(plan: LogicalPlan, storage: &S) -> BoxedExecutor {
         Operator::Truncate(op) => {
             Truncate::from(op).execute(storage)
         }
+        Operator::Analyze(op) => {
+            let input = _build(childrens.remove(0), storage, ctx, metrics_list);
+
+            Analyze::from((op, input))
+                .with_metrics(metrics.clone())
+                .with_memory_tracker(ctx.memory.clone())
+                .execute(storage)
+        }
+        Operator::Explain(op) => {
+            Explain::from((op, childrens.remove(0)))
+                .with_context(ctx.clone())
+                .execute(storage)
+        }
+    };
+    let executor = cancellable(executor, ctx.cancel.clone());
+
+    if metrics_list.is_some() {
+        instrument(executor, metrics)
+    } else {
+        executor
     }
 }
 
+fn plan_size(plan: &LogicalPlan) -> usize {
+    1 + plan.childrens.iter().map(plan_size).sum::<usize>()
+}
+
+/// Whether both inputs of the join are sorted in ascending order on their equi-join keys,
+/// so that the join can be done by `MergeJoin` without building a hash table.
+///
+/// An input built by parallel executors is not, its partitions are gathered in no particular
+/// order.
+fn is_sorted_on_join_keys<S: Storage>(
+    op: &JoinOperator,
+    left: &LogicalPlan,
+    right: &LogicalPlan,
+    storage: &S,
+    parallelism: usize
+) -> bool {
+    let JoinCondition::On { on, .. } = &op.on else {
+        return false;
+    };
+    if parallelism > 1 && (is_gathered(left) || is_gathered(right)) {
+        return false;
+    }
+    let primary_key_ordered = storage.is_primary_key_ordered();
+    // the keys are resolved to the positions of the columns of the inputs, so is the ordering
+    // to compare them, e.g. of a scan, whose columns are not resolved
+    let is_sorted_on = |plan: &LogicalPlan, keys: Vec<&ScalarExpression>| {
+        let columns = plan.output_columns();
+        let ordering = plan.output_ordering(primary_key_ordered);
+
+        ordering.len() >= keys.len() && ordering
+            .into_iter()
+            .zip(keys)
+            .all(|(mut field, key)| {
+                field.expr.resolve_column_refs(&columns).is_ok() && !field.desc && &field.expr == key
+            })
+    };
+
+    matches!(op.join_type, JoinType::Inner | JoinType::Left | JoinType::Right | JoinType::Full)
+        && is_sorted_on(left, on.iter().map(|(left_key, _)| left_key).collect())
+        && is_sorted_on(right, on.iter().map(|(_, right_key)| right_key).collect())
+}
+
+/// Whether the ordering of the plan comes from parallel executors, i.e. the plan or the input
+/// of the operators which keep the ordering of their input is run in parallel.
+fn is_gathered(plan: &LogicalPlan) -> bool {
+    is_parallel(plan) || (matches!(plan.operator, Operator::Filter(_) | Operator::Limit(_) | Operator::Project(_))
+        && is_gathered(&plan.childrens[0]))
+}
+
 pub async fn try_collect(executor: &mut BoxedExecutor) -> Result<Vec<Tuple>, ExecutorError> {
+    try_collect_with_tracker(executor, Arc::new(MemoryTracker::default())).await
+}
+
+/// Like `try_collect`, but the collected tuples are reserved from the memory tracker.
+pub async fn try_collect_with_tracker(executor: &mut BoxedExecutor, memory: Arc<MemoryTracker>) -> Result<Vec<Tuple>, ExecutorError> {
+    let mut reservation = MemoryReservation::new(memory);
     let mut output = Vec::new();
 
     while let Some(tuple) = executor.try_next().await? {
+        reservation.try_grow(tuple.memory_size())?;
         output.push(tuple);
     }
     Ok(output)
@@ -0,0 +1,160 @@
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Seek, SeekFrom, Write};
+use std::sync::Arc;
+use ahash::RandomState;
+use futures::StreamExt;
+use integer_encoding::FixedInt;
+use crate::catalog::{ColumnCatalog, ColumnRef};
+use crate::execution::executor::BoxedExecutor;
+use crate::execution::ExecutorError;
+use crate::types::LogicalType;
+use crate::types::tuple::Tuple;
+use crate::types::value::ValueRef;
+
+/// Spilled tuples are split into this many partitions by the hash of their keys.
+pub(crate) const PARTITION_COUNT: usize = 8;
+
+/// Partitions are split again at most this many times, past it they are processed in memory
+/// because all the tuples left in a partition may have the same keys.
+pub(crate) const MAX_PARTITION_LEVEL: usize = 4;
+
+/// Tuples written to an anonymous temporary file, which is removed once it is dropped.
+///
+/// Every tuple is written as its length followed by `Tuple::serialize_to`, which doesn't hold
+/// the types of the values, so the type of a column is taken from its first non-null value.
+pub(crate) struct SpillFile {
+    writer: BufWriter<File>,
+    columns: Vec<ColumnRef>,
+    types: Vec<Option<LogicalType>>,
+    len: usize,
+}
+
+impl SpillFile {
+    pub(crate) fn new() -> Result<Self, ExecutorError> {
+        Ok(SpillFile {
+            writer: BufWriter::new(tempfile::tempfile()?),
+            columns: vec![],
+            types: vec![],
+            len: 0,
+        })
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn append(&mut self, tuple: &Tuple) -> Result<(), ExecutorError> {
+        if self.len == 0 {
+            self.columns = tuple.columns.clone();
+            self.types = vec![None; tuple.values.len()];
+        }
+        for (ty, value) in self.types.iter_mut().zip(tuple.values.iter()) {
+            if ty.is_none() && !value.is_null() {
+                *ty = Some(value.logical_type());
+            }
+        }
+        let bytes = tuple.serialize_to();
+
+        self.writer.write_all(&(bytes.len() as u32).encode_fixed_vec())?;
+        self.writer.write_all(&bytes)?;
+        self.len += 1;
+
+        Ok(())
+    }
+
+    /// Read the tuples back in the order they were appended.
+    pub(crate) fn reader(self) -> Result<SpillReader, ExecutorError> {
+        let SpillFile { writer, columns, types, len } = self;
+        let mut file = writer.into_inner().map_err(|err| err.into_error())?;
+        file.seek(SeekFrom::Start(0))?;
+
+        let raw_columns = columns
+            .iter()
+            .zip(types)
+            .map(|(col, ty)| {
+                let mut raw_col = ColumnCatalog::clone(col);
+                if let Some(ty) = ty {
+                    raw_col.desc.column_datatype = ty;
+                }
+                Arc::new(raw_col)
+            })
+            .collect();
+
+        Ok(SpillReader {
+            reader: BufReader::new(file),
+            columns,
+            raw_columns,
+            remaining: len,
+        })
+    }
+}
+
+/// Tuples written to `PARTITION_COUNT` spill files by the hash of their keys. The hash is
+/// seeded by the level of the partitioning, so that a partition is split differently again.
+pub(crate) struct SpillPartitions {
+    files: Vec<Option<SpillFile>>,
+    hash_random_state: RandomState,
+}
+
+impl SpillPartitions {
+    pub(crate) fn new(level: usize) -> Self {
+        SpillPartitions {
+            files: (0..PARTITION_COUNT).map(|_| None).collect(),
+            hash_random_state: RandomState::with_seeds(level as u64 + 1, 0, 0, 0),
+        }
+    }
+
+    pub(crate) fn append(&mut self, keys: &[ValueRef], tuple: &Tuple) -> Result<(), ExecutorError> {
+        let i = self.hash_random_state.hash_one(keys) as usize % PARTITION_COUNT;
+
+        let file = match &mut self.files[i] {
+            Some(file) => file,
+            file => file.insert(SpillFile::new()?),
+        };
+        file.append(tuple)
+    }
+
+    /// The spill files by the index of their partition, `None` for an empty one.
+    pub(crate) fn into_files(self) -> Vec<Option<SpillFile>> {
+        self.files
+    }
+}
+
+pub(crate) struct SpillReader {
+    reader: BufReader<File>,
+    columns: Vec<ColumnRef>,
+    /// The columns with the types the values were written with
+    raw_columns: Vec<ColumnRef>,
+    remaining: usize,
+}
+
+impl SpillReader {
+    pub(crate) fn into_executor(self) -> BoxedExecutor {
+        futures::stream::iter(self).boxed()
+    }
+
+    fn read_tuple(&mut self) -> Result<Tuple, ExecutorError> {
+        let mut len_bytes = [0; 4];
+        self.reader.read_exact(&mut len_bytes)?;
+        let mut bytes = vec![0; u32::decode_fixed(&len_bytes) as usize];
+        self.reader.read_exact(&mut bytes)?;
+
+        let mut tuple = Tuple::deserialize_from(self.raw_columns.clone(), &bytes);
+        tuple.columns = self.columns.clone();
+
+        Ok(tuple)
+    }
+}
+
+impl Iterator for SpillReader {
+    type Item = Result<Tuple, ExecutorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        Some(self.read_tuple())
+    }
+}
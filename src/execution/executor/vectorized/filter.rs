@@ -0,0 +1,45 @@
+use futures_async_stream::try_stream;
+use crate::execution::executor::vectorized::{BatchExecutor, BoxedBatchExecutor, selection};
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::filter::FilterOperator;
+use crate::storage::Storage;
+use crate::types::batch::RecordBatch;
+
+pub struct BatchFilter {
+    predicate: ScalarExpression,
+    input: BoxedBatchExecutor
+}
+
+impl From<(FilterOperator, BoxedBatchExecutor)> for BatchFilter {
+    fn from((FilterOperator { predicate, .. }, input): (FilterOperator, BoxedBatchExecutor)) -> Self {
+        BatchFilter {
+            predicate,
+            input
+        }
+    }
+}
+
+impl<S: Storage> BatchExecutor<S> for BatchFilter {
+    fn execute(self, _: &S) -> BoxedBatchExecutor {
+        self._execute()
+    }
+}
+
+impl BatchFilter {
+    #[try_stream(boxed, ok = RecordBatch, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let BatchFilter { predicate, input } = self;
+
+        #[for_await]
+        for batch in input {
+            let batch = batch?;
+            let vector = predicate.eval_batch(&batch)?;
+            let batch = batch.filter(&selection(&vector));
+
+            if !batch.is_empty() {
+                yield batch;
+            }
+        }
+    }
+}
@@ -0,0 +1,191 @@
+use std::sync::Arc;
+use ahash::{HashMap, HashMapExt};
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::execution::executor::dql::aggregate::{Accumulator, create_accumulators};
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::executor::vectorized::{BatchExecutor, BoxedBatchExecutor, DEFAULT_BATCH_SIZE};
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::aggregate::AggregateOperator;
+use crate::storage::Storage;
+use crate::types::batch::{ColumnVector, RecordBatch, VectorRef};
+use crate::types::errors::TypeError;
+use crate::types::value::{DataValue, ValueRef};
+
+/// Hash aggregation over batches, the group keys and the inputs of the agg calls are evaluated
+/// for a whole batch before the rows are fed into the accumulators of their groups.
+///
+/// Unlike `HashAggExecutor`, the groups are never spilled.
+pub struct BatchHashAgg {
+    agg_calls: Vec<ScalarExpression>,
+    groupby_exprs: Vec<ScalarExpression>,
+    input: BoxedBatchExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory: Arc<MemoryTracker>,
+    batch_size: usize,
+}
+
+impl From<(AggregateOperator, BoxedBatchExecutor)> for BatchHashAgg {
+    fn from((AggregateOperator { agg_calls, groupby_exprs }, input): (AggregateOperator, BoxedBatchExecutor)) -> Self {
+        BatchHashAgg {
+            agg_calls,
+            groupby_exprs,
+            input,
+            metrics: Default::default(),
+            memory: Default::default(),
+            batch_size: DEFAULT_BATCH_SIZE,
+        }
+    }
+}
+
+impl<S: Storage> BatchExecutor<S> for BatchHashAgg {
+    fn execute(self, _: &S) -> BoxedBatchExecutor {
+        self._execute()
+    }
+}
+
+/// The inputs of an agg call evaluated over a batch.
+struct AggInputs {
+    filter: Option<VectorRef>,
+    arg: VectorRef,
+    order_by: Vec<VectorRef>,
+}
+
+impl AggInputs {
+    fn eval(agg_call: &ScalarExpression, batch: &RecordBatch) -> Result<Self, TypeError> {
+        let ScalarExpression::AggCall { args, filter, order_by, .. } = agg_call else {
+            unreachable!()
+        };
+
+        Ok(AggInputs {
+            filter: filter.as_ref().map(|expr| expr.eval_batch(batch)).transpose()?,
+            arg: args[0].eval_batch(batch)?,
+            order_by: order_by.iter().map(|field| field.expr.eval_batch(batch)).try_collect()?,
+        })
+    }
+
+    /// Feeds the row `i` into the accumulator, as `update_accumulators` does for a tuple.
+    fn update(&self, acc: &mut Box<dyn Accumulator>, i: usize) -> Result<(), ExecutorError> {
+        if let Some(filter) = &self.filter {
+            if !matches!(filter.value(i), DataValue::Boolean(Some(true))) {
+                return Ok(());
+            }
+        }
+        let value = Arc::new(self.arg.value(i));
+
+        if self.order_by.is_empty() {
+            acc.update_value(&value)
+        } else {
+            let keys = self.order_by
+                .iter()
+                .map(|vector| Arc::new(vector.value(i)))
+                .collect_vec();
+
+            acc.update_value_with_keys(&value, keys)
+        }
+    }
+}
+
+impl BatchHashAgg {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    /// Groups in each of the output batches.
+    pub fn with_batch_size(mut self, batch_size: usize) -> Self {
+        self.batch_size = batch_size;
+        self
+    }
+
+    #[try_stream(boxed, ok = RecordBatch, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let BatchHashAgg { agg_calls, groupby_exprs, input, metrics, memory, batch_size } = self;
+        let mut group_and_agg_columns_option = None;
+        let mut group_hash_accs: HashMap<Vec<ValueRef>, Vec<Box<dyn Accumulator>>> = HashMap::new();
+        let mut reservation = MemoryReservation::new(memory);
+
+        #[for_await]
+        for batch in input {
+            let batch = batch?;
+
+            // Tips: AggCall First
+            group_and_agg_columns_option.get_or_insert_with(|| {
+                let header = batch.header();
+
+                agg_calls
+                    .iter()
+                    .chain(groupby_exprs.iter())
+                    .map(|expr| expr.output_columns(&header))
+                    .collect_vec()
+            });
+            let key_vectors: Vec<VectorRef> = groupby_exprs
+                .iter()
+                .map(|expr| expr.eval_batch(&batch))
+                .try_collect()?;
+            let agg_inputs: Vec<AggInputs> = agg_calls
+                .iter()
+                .map(|expr| AggInputs::eval(expr, &batch))
+                .try_collect()?;
+            let row_size = batch.memory_size() / batch.len().max(1);
+
+            for i in 0..batch.len() {
+                let group_keys = key_vectors
+                    .iter()
+                    .map(|vector| Arc::new(vector.value(i)))
+                    .collect_vec();
+
+                if !group_hash_accs.contains_key(&group_keys) {
+                    reservation.try_grow(row_size)?;
+                    metrics.buffer(1);
+                }
+                let accs = group_hash_accs
+                    .entry(group_keys)
+                    .or_insert_with(|| create_accumulators(&agg_calls));
+
+                for (acc, inputs) in accs.iter_mut().zip(agg_inputs.iter()) {
+                    inputs.update(acc, i)?;
+                }
+            }
+        }
+
+        let groups_len = group_hash_accs.len();
+
+        if let Some(columns) = group_and_agg_columns_option {
+            let mut groups = group_hash_accs.into_iter().peekable();
+
+            while groups.peek().is_some() {
+                let mut values = vec![Vec::with_capacity(batch_size); columns.len()];
+                let mut len = 0;
+
+                for (group_keys, accs) in groups.by_ref().take(batch_size) {
+                    // Tips: Accumulator First
+                    let group_values: Vec<ValueRef> = accs.iter()
+                        .map(|acc| acc.evaluate())
+                        .chain(group_keys.into_iter().map(Ok))
+                        .try_collect()?;
+
+                    for (column_values, value) in values.iter_mut().zip(group_values) {
+                        column_values.push(DataValue::clone(&value));
+                    }
+                    len += 1;
+                }
+                let vectors = columns
+                    .iter()
+                    .zip(values)
+                    .map(|(col, values)| ColumnVector::infer_from_values(values, *col.datatype()).map(Arc::new))
+                    .try_collect()?;
+
+                yield RecordBatch::new(columns.clone(), vectors, len);
+            }
+        }
+        metrics.release(groups_len);
+    }
+}
@@ -0,0 +1,144 @@
+use std::sync::Arc;
+use ahash::{HashMap, HashMapExt};
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::execution::executor::dql::join::join_side_columns;
+use crate::execution::executor::memory::{MemoryReservation, MemoryTracker};
+use crate::execution::executor::metrics::ExecutorMetrics;
+use crate::execution::executor::vectorized::{BatchExecutor, BoxedBatchExecutor, selection};
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::storage::Storage;
+use crate::types::batch::{RecordBatch, VectorRef};
+use crate::types::errors::TypeError;
+use crate::types::value::DataValue;
+
+/// Inner hash join over batches: the left side is concatenated into a single batch and every
+/// right batch is probed into the indices of the matched rows of both sides, which are taken
+/// from them at once.
+///
+/// Unlike `HashJoin`, the build side is never spilled.
+pub struct BatchHashJoin {
+    on: JoinCondition,
+    left_input: BoxedBatchExecutor,
+    right_input: BoxedBatchExecutor,
+    metrics: Arc<ExecutorMetrics>,
+    memory: Arc<MemoryTracker>,
+}
+
+impl From<(JoinOperator, BoxedBatchExecutor, BoxedBatchExecutor)> for BatchHashJoin {
+    fn from((JoinOperator { on, join_type }, left_input, right_input): (JoinOperator, BoxedBatchExecutor, BoxedBatchExecutor)) -> Self {
+        assert_eq!(join_type, JoinType::Inner, "BatchHashJoin only supports inner joins");
+
+        BatchHashJoin {
+            on,
+            left_input,
+            right_input,
+            metrics: Default::default(),
+            memory: Default::default(),
+        }
+    }
+}
+
+impl<S: Storage> BatchExecutor<S> for BatchHashJoin {
+    fn execute(self, _: &S) -> BoxedBatchExecutor {
+        self._execute()
+    }
+}
+
+impl BatchHashJoin {
+    pub fn with_metrics(mut self, metrics: Arc<ExecutorMetrics>) -> Self {
+        self.metrics = metrics;
+        self
+    }
+
+    pub fn with_memory_tracker(mut self, memory: Arc<MemoryTracker>) -> Self {
+        self.memory = memory;
+        self
+    }
+
+    #[try_stream(boxed, ok = RecordBatch, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let BatchHashJoin { on, left_input, right_input, metrics, memory } = self;
+        let JoinCondition::On { on, filter } = on else {
+            unreachable!("HashJoin must has on condition")
+        };
+        let (on_left_keys, on_right_keys): (Vec<ScalarExpression>, Vec<ScalarExpression>) = on.into_iter().unzip();
+        let mut reservation = MemoryReservation::new(memory);
+
+        // build phase
+        let mut left_batches = Vec::new();
+
+        #[for_await]
+        for batch in left_input {
+            let batch = batch?;
+
+            reservation.try_grow(batch.memory_size())?;
+            metrics.buffer(batch.len());
+            left_batches.push(batch);
+        }
+        if left_batches.is_empty() {
+            return Ok(());
+        }
+        let left = RecordBatch::concat(&left_batches)?;
+        let left_columns = join_side_columns(&left.columns, false);
+        drop(left_batches);
+
+        let left_keys = Self::eval_keys(&on_left_keys, &left)?;
+        let mut left_map: HashMap<Vec<DataValue>, Vec<usize>> = HashMap::new();
+
+        // a NULL key never equals another key
+        for i in (0..left.len()).filter(|i| left_keys.iter().all(|vector| vector.is_valid(*i))) {
+            left_map
+                .entry(left_keys.iter().map(|vector| vector.value(i)).collect_vec())
+                .or_default()
+                .push(i);
+        }
+
+        // probe phase
+        #[for_await]
+        for batch in right_input {
+            let batch = batch?;
+            let right_keys = Self::eval_keys(&on_right_keys, &batch)?;
+            let mut left_indices = Vec::new();
+            let mut right_indices = Vec::new();
+
+            for i in (0..batch.len()).filter(|i| right_keys.iter().all(|vector| vector.is_valid(*i))) {
+                let keys = right_keys.iter().map(|vector| vector.value(i)).collect_vec();
+
+                if let Some(indices) = left_map.get(&keys) {
+                    left_indices.extend_from_slice(indices);
+                    right_indices.extend(std::iter::repeat(i).take(indices.len()));
+                }
+            }
+            if left_indices.is_empty() {
+                continue;
+            }
+            let mut left_rows = left.take(&left_indices);
+            let mut right_rows = batch.take(&right_indices);
+
+            left_rows.columns = left_columns.clone();
+            right_rows.columns = join_side_columns(&batch.columns, false);
+
+            let mut joined = RecordBatch::merge(left_rows, right_rows);
+
+            // on filter
+            if let Some(expr) = &filter {
+                let vector = expr.eval_batch(&joined)?;
+
+                joined = joined.filter(&selection(&vector));
+            }
+            if !joined.is_empty() {
+                yield joined;
+            }
+        }
+        metrics.release(left.len());
+    }
+
+    fn eval_keys(on_keys: &[ScalarExpression], batch: &RecordBatch) -> Result<Vec<VectorRef>, TypeError> {
+        on_keys.iter()
+            .map(|expr| expr.eval_batch(batch))
+            .try_collect()
+    }
+}
@@ -0,0 +1,143 @@
+pub(crate) mod filter;
+pub(crate) mod hash_agg;
+pub(crate) mod hash_join;
+pub(crate) mod projection;
+
+use std::mem;
+use std::sync::Arc;
+use futures::stream::BoxStream;
+use futures_async_stream::try_stream;
+use itertools::Itertools;
+use crate::execution::executor::{_build, BoxedExecutor, ExecutionContext, PlanMetrics};
+use crate::execution::executor::cancel::cancellable;
+use crate::execution::executor::metrics::{ExecutorMetrics, instrument};
+use crate::execution::executor::vectorized::filter::BatchFilter;
+use crate::execution::executor::vectorized::hash_agg::BatchHashAgg;
+use crate::execution::executor::vectorized::hash_join::BatchHashJoin;
+use crate::execution::executor::vectorized::projection::BatchProjection;
+use crate::execution::ExecutorError;
+use crate::planner::LogicalPlan;
+use crate::planner::operator::join::JoinType;
+use crate::planner::operator::Operator;
+use crate::storage::Storage;
+use crate::types::batch::{ColumnVector, RecordBatch, VectorData};
+use crate::types::tuple::Tuple;
+
+pub type BoxedBatchExecutor = BoxStream<'static, Result<RecordBatch, ExecutorError>>;
+
+/// Rows of the batches read from the row-based executors by default.
+pub const DEFAULT_BATCH_SIZE: usize = 1024;
+
+pub trait BatchExecutor<S: Storage> {
+    fn execute(self, storage: &S) -> BoxedBatchExecutor;
+}
+
+/// Whether the operator of the plan has a vectorized executor.
+pub fn is_vectorized(plan: &LogicalPlan) -> bool {
+    match &plan.operator {
+        Operator::Filter(_) | Operator::Project(_) => true,
+        Operator::Aggregate(op) => !op.groupby_exprs.is_empty(),
+        Operator::Join(op) => op.join_type == JoinType::Inner && op.has_equi_keys(),
+        _ => false,
+    }
+}
+
+/// Like `_build`, the operators without a vectorized executor are built by `_build` and their
+/// tuples are gathered into batches.
+pub(crate) fn build_batches<S: Storage>(
+    plan: LogicalPlan,
+    storage: &S,
+    ctx: &ExecutionContext,
+    metrics_list: &mut Option<PlanMetrics>
+) -> BoxedBatchExecutor {
+    if !is_vectorized(&plan) {
+        return rows_to_batches(_build(plan, storage, ctx, metrics_list), ctx.config.batch_size);
+    }
+
+    let LogicalPlan { operator, mut childrens } = plan;
+    let metrics = Arc::new(ExecutorMetrics::default());
+
+    if let Some(metrics_list) = metrics_list {
+        metrics_list.push(Some(metrics.clone()));
+    }
+    let executor = match operator {
+        Operator::Filter(op) => {
+            let input = build_batches(childrens.remove(0), storage, ctx, metrics_list);
+
+            BatchFilter::from((op, input)).execute(storage)
+        }
+        Operator::Project(op) => {
+            let input = build_batches(childrens.remove(0), storage, ctx, metrics_list);
+
+            BatchProjection::from((op, input)).execute(storage)
+        }
+        Operator::Aggregate(op) => {
+            let input = build_batches(childrens.remove(0), storage, ctx, metrics_list);
+
+            BatchHashAgg::from((op, input))
+                .with_metrics(metrics.clone())
+                .with_memory_tracker(ctx.memory.clone())
+                .with_batch_size(ctx.config.batch_size)
+                .execute(storage)
+        }
+        Operator::Join(op) => {
+            let left_input = build_batches(childrens.remove(0), storage, ctx, metrics_list);
+            let right_input = build_batches(childrens.remove(0), storage, ctx, metrics_list);
+
+            BatchHashJoin::from((op, left_input, right_input))
+                .with_metrics(metrics.clone())
+                .with_memory_tracker(ctx.memory.clone())
+                .execute(storage)
+        }
+        operator => unreachable!("no vectorized executor for {:?}", operator),
+    };
+    let executor = cancellable(executor, ctx.cancel.clone());
+
+    if metrics_list.is_some() {
+        instrument(executor, metrics)
+    } else {
+        executor
+    }
+}
+
+/// Gather the tuples into batches of `batch_size` rows, the last one may be smaller.
+#[try_stream(boxed, ok = RecordBatch, error = ExecutorError)]
+pub async fn rows_to_batches(input: BoxedExecutor, batch_size: usize) {
+    let mut tuples = Vec::with_capacity(batch_size);
+
+    #[for_await]
+    for tuple in input {
+        tuples.push(tuple?);
+
+        if tuples.len() >= batch_size {
+            yield RecordBatch::from_tuples(&mem::take(&mut tuples))?;
+        }
+    }
+    if !tuples.is_empty() {
+        yield RecordBatch::from_tuples(&tuples)?;
+    }
+}
+
+#[try_stream(boxed, ok = Tuple, error = ExecutorError)]
+pub async fn batches_to_rows(input: BoxedBatchExecutor) {
+    #[for_await]
+    for batch in input {
+        for tuple in batch?.to_tuples() {
+            yield tuple;
+        }
+    }
+}
+
+/// The rows where the predicate is true, NULL is not.
+pub(crate) fn selection(predicate: &ColumnVector) -> Vec<bool> {
+    // a constant NULL, e.g. `WHERE NULL`
+    let VectorData::Boolean(values) = predicate.data() else {
+        return vec![false; predicate.len()];
+    };
+
+    values
+        .iter()
+        .zip(predicate.validity().iter())
+        .map(|(value, is_valid)| *value && is_valid)
+        .collect_vec()
+}
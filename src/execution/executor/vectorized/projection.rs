@@ -0,0 +1,50 @@
+use futures_async_stream::try_stream;
+use crate::execution::executor::vectorized::{BatchExecutor, BoxedBatchExecutor};
+use crate::execution::ExecutorError;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::project::ProjectOperator;
+use crate::storage::Storage;
+use crate::types::batch::RecordBatch;
+
+pub struct BatchProjection {
+    exprs: Vec<ScalarExpression>,
+    input: BoxedBatchExecutor
+}
+
+impl From<(ProjectOperator, BoxedBatchExecutor)> for BatchProjection {
+    fn from((ProjectOperator { columns }, input): (ProjectOperator, BoxedBatchExecutor)) -> Self {
+        BatchProjection {
+            exprs: columns,
+            input,
+        }
+    }
+}
+
+impl<S: Storage> BatchExecutor<S> for BatchProjection {
+    fn execute(self, _: &S) -> BoxedBatchExecutor {
+        self._execute()
+    }
+}
+
+impl BatchProjection {
+    #[try_stream(boxed, ok = RecordBatch, error = ExecutorError)]
+    pub async fn _execute(self) {
+        let BatchProjection { exprs, input } = self;
+
+        #[for_await]
+        for batch in input {
+            let batch = batch?;
+            let header = batch.header();
+
+            let mut columns = Vec::with_capacity(exprs.len());
+            let mut vectors = Vec::with_capacity(exprs.len());
+
+            for expr in exprs.iter() {
+                vectors.push(expr.eval_batch(&batch)?);
+                columns.push(expr.output_columns(&header));
+            }
+
+            yield RecordBatch::new(columns, vectors, batch.len());
+        }
+    }
+}
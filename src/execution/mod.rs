@@ -38,6 +38,22 @@ pub enum ExecutorError {
         #[from]
         ParserError
     ),
+    #[error("io error: {0}")]
+    IoError(
+        #[source]
+        #[from]
+        std::io::Error
+    ),
+    #[error("memory limit of {limit} bytes exceeded: {requested} bytes requested with {used} bytes in use")]
+    MemoryLimitExceeded {
+        limit: usize,
+        used: usize,
+        requested: usize,
+    },
+    #[error("statement cancelled")]
+    Cancelled,
+    #[error("statement timeout of {0:?} exceeded")]
+    StatementTimeout(std::time::Duration),
     #[error("Internal error: {0}")]
     InternalError(String),
 }
@@ -1,3 +1,8 @@
+use crate::expression::BinaryOperator;
+use crate::expression::value_compute::binary_result_type;
+use crate::types::decimal;
+use crate::types::LogicalType;
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum AggKind {
     Avg,
@@ -5,6 +10,7 @@ pub enum AggKind {
     Min,
     Sum,
     Count,
+    StringAgg,
 }
 
 impl AggKind {
@@ -15,6 +21,30 @@ impl AggKind {
             AggKind::Min => false,
             AggKind::Sum => true,
             AggKind::Count => true,
+            AggKind::StringAgg => true,
+        }
+    }
+
+    /// The type of the result of the call on the values of `ty`:
+    /// - the sum of decimals has the most digits, with the same scale
+    /// - the average is the one of the sum divided by the count as in `binary_op`, a decimal for
+    /// decimals and otherwise a double
+    pub fn return_type(&self, ty: LogicalType) -> LogicalType {
+        match self {
+            AggKind::Count => LogicalType::UInteger,
+            AggKind::Sum => match ty {
+                LogicalType::Decimal(_, scale) => LogicalType::Decimal(decimal::MAX_PRECISION, scale),
+                ty => ty,
+            },
+            AggKind::Avg => match ty {
+                LogicalType::Decimal(..) => {
+                    binary_result_type(&AggKind::Sum.return_type(ty), &LogicalType::UInteger, &BinaryOperator::Divide)
+                        .unwrap_or(LogicalType::Double)
+                }
+                _ => LogicalType::Double,
+            },
+            AggKind::Max | AggKind::Min => ty,
+            AggKind::StringAgg => LogicalType::Varchar,
         }
     }
 }
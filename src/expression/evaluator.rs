@@ -1,7 +1,6 @@
 use std::sync::Arc;
-use itertools::Itertools;
 use crate::expression::value_compute::{binary_op, unary_op};
-use crate::expression::ScalarExpression;
+use crate::expression::{column_position, ScalarExpression};
 use crate::types::errors::TypeError;
 use crate::types::tuple::Tuple;
 use crate::types::value::{DataValue, ValueRef};
@@ -11,11 +10,13 @@ impl ScalarExpression {
         match &self {
             ScalarExpression::Constant(val) => Ok(val.clone()),
             ScalarExpression::ColumnRef(col) => {
-                let (index, _) = tuple
-                    .columns
+                // Tips: the columns are resolved to `InputRef`s once the plan is optimized, but the
+                // ones of the scans, whose stored tuples have the columns of the catalog, which are
+                // not bound to the alias of the table
+                let index = column_position(&tuple.columns, col).or_else(|err| tuple.columns
                     .iter()
-                    .find_position(|tul_col| tul_col.name == col.name)
-                    .unwrap();
+                    .position(|column| column.id == col.id && column.table_name == col.table_name)
+                    .ok_or(err))?;
 
                 Ok(tuple.values[index].clone())
             },
@@ -32,14 +33,27 @@ impl ScalarExpression {
 
                 Ok(Arc::new(binary_op(&left, &right, op)?))
             }
-            ScalarExpression::IsNull{ expr } => {
-                Ok(Arc::new(DataValue::Boolean(Some(expr.nullable()))))
+            ScalarExpression::IsNull{ negated, expr } => {
+                let value = expr.eval_column(tuple)?;
+
+                Ok(Arc::new(DataValue::Boolean(Some(value.is_null() != *negated))))
             }
             ScalarExpression::Unary{ expr, op, .. } => {
                 let value = expr.eval_column(tuple)?;
 
                 Ok(Arc::new(unary_op(&value, op)?))
             },
+            ScalarExpression::Coalesce{ exprs, ty } => {
+                for expr in exprs {
+                    let value = expr.eval_column(tuple)?;
+
+                    if !value.is_null() {
+                        return Ok(value);
+                    }
+                }
+
+                Ok(Arc::new(DataValue::none(ty)))
+            }
             ScalarExpression::AggCall{ .. } => todo!()
         }
     }
@@ -8,14 +8,17 @@ use crate::binder::BinderContext;
 
 use self::agg::AggKind;
 use crate::catalog::{ColumnCatalog, ColumnDesc, ColumnRef};
+use crate::planner::operator::sort::SortField;
 use crate::storage::Storage;
 use crate::types::value::ValueRef;
+use crate::types::errors::TypeError;
 use crate::types::LogicalType;
 use crate::types::tuple::Tuple;
 
 pub mod agg;
 mod evaluator;
 pub mod value_compute;
+mod vectorized;
 
 /// ScalarExpression represnet all scalar expression in SQL.
 /// SELECT a+1, b FROM t1.
@@ -38,7 +41,9 @@ pub enum ScalarExpression {
         ty: LogicalType,
         is_try: bool,
     },
+    /// `expr IS NULL`, or `expr IS NOT NULL` when `negated`.
     IsNull {
+        negated: bool,
         expr: Box<ScalarExpression>,
     },
     Unary {
@@ -57,7 +62,35 @@ pub enum ScalarExpression {
         kind: AggKind,
         args: Vec<ScalarExpression>,
         ty: LogicalType,
+        /// `FILTER (WHERE ...)`: only rows that satisfy it are fed into the aggregate.
+        filter: Option<Box<ScalarExpression>>,
+        /// `ORDER BY` inside the call, e.g. `string_agg(x, ',' ORDER BY y)`.
+        order_by: Vec<SortField>,
     },
+    /// The first non-NULL value of `exprs`, used for the merged columns of `JOIN ... USING`.
+    Coalesce {
+        exprs: Vec<ScalarExpression>,
+        ty: LogicalType,
+    },
+}
+
+/// The position of the column in `columns`, of the same table bound by the same name. The
+/// columns of a derived table and the computed ones, e.g. `count(*)`, get new ids, they are
+/// matched by name with the only computed column of that name.
+pub fn column_position(columns: &[ColumnRef], col: &ColumnRef) -> Result<usize, TypeError> {
+    // a column may be output more than once, e.g. `SELECT a, a FROM t`
+    if let Some(index) = columns.iter().position(|column| column.is_same_column(col)) {
+        return Ok(index);
+    }
+    let mut positions = columns
+        .iter()
+        .positions(|column| column.table_name.is_none() && column.name == col.name);
+
+    match (positions.next(), positions.next()) {
+        (Some(index), None) => Ok(index),
+        (Some(_), Some(_)) => Err(TypeError::AmbiguousColumn(col.name.clone())),
+        (None, _) => Err(TypeError::ColumnNotFound(col.name.clone())),
+    }
 }
 
 impl ScalarExpression {
@@ -76,11 +109,12 @@ impl ScalarExpression {
             ScalarExpression::InputRef { .. } => unreachable!(),
             ScalarExpression::Alias { expr, .. } => expr.nullable(),
             ScalarExpression::TypeCast { expr, .. } => expr.nullable(),
-            ScalarExpression::IsNull { expr } => expr.nullable(),
+            ScalarExpression::IsNull { .. } => false,
             ScalarExpression::Unary { expr, .. } => expr.nullable(),
             ScalarExpression::Binary { left_expr, right_expr, .. } =>
                 left_expr.nullable() && right_expr.nullable(),
-            ScalarExpression::AggCall { args, .. } => args[0].nullable()
+            ScalarExpression::AggCall { args, .. } => args[0].nullable(),
+            ScalarExpression::Coalesce { exprs, .. } => exprs.iter().all(|expr| expr.nullable()),
         }
     }
 
@@ -103,6 +137,9 @@ impl ScalarExpression {
             Self::InputRef {
                 ty: return_type, ..
             } => return_type.clone(),
+            Self::Coalesce {
+                ty: return_type, ..
+            } => return_type.clone(),
             Self::IsNull { .. } => LogicalType::Boolean,
             Self::Alias { expr, .. } => expr.return_type(),
         }
@@ -130,10 +167,21 @@ impl ScalarExpression {
                     columns_collect(left_expr, vec);
                     columns_collect(right_expr, vec);
                 }
-                ScalarExpression::AggCall { args, .. } => {
+                ScalarExpression::AggCall { args, filter, order_by, .. } => {
                     for expr in args {
                         columns_collect(expr, vec)
                     }
+                    if let Some(expr) = filter {
+                        columns_collect(expr, vec)
+                    }
+                    for SortField { expr, .. } in order_by {
+                        columns_collect(expr, vec)
+                    }
+                }
+                ScalarExpression::Coalesce { exprs, .. } => {
+                    for expr in exprs {
+                        columns_collect(expr, vec)
+                    }
                 }
                 _ => (),
             }
@@ -146,6 +194,54 @@ impl ScalarExpression {
         exprs
     }
 
+    /// Replace every column reference that `f` returns an expression for.
+    pub fn replace_column_refs(&mut self, f: &impl Fn(&ColumnRef) -> Option<ScalarExpression>) {
+        match self {
+            ScalarExpression::ColumnRef(col) => {
+                if let Some(expr) = f(col) {
+                    *self = expr;
+                }
+            }
+            ScalarExpression::Alias { expr, .. }
+            | ScalarExpression::TypeCast { expr, .. }
+            | ScalarExpression::IsNull { expr, .. }
+            | ScalarExpression::Unary { expr, .. } => expr.replace_column_refs(f),
+            ScalarExpression::Binary { left_expr, right_expr, .. } => {
+                left_expr.replace_column_refs(f);
+                right_expr.replace_column_refs(f);
+            }
+            ScalarExpression::AggCall { args, filter, order_by, .. } => {
+                for expr in args {
+                    expr.replace_column_refs(f);
+                }
+                if let Some(expr) = filter {
+                    expr.replace_column_refs(f);
+                }
+                for SortField { expr, .. } in order_by {
+                    expr.replace_column_refs(f);
+                }
+            }
+            ScalarExpression::Coalesce { exprs, .. } => {
+                for expr in exprs {
+                    expr.replace_column_refs(f);
+                }
+            }
+            ScalarExpression::Constant(_) | ScalarExpression::InputRef { .. } => (),
+        }
+    }
+
+    /// Resolve the column references to `InputRef`s, the positions of the columns in `columns`.
+    pub fn resolve_column_refs(&mut self, columns: &[ColumnRef]) -> Result<(), TypeError> {
+        for col in self.referenced_columns() {
+            let _ = column_position(columns, &col)?;
+        }
+        self.replace_column_refs(&|col| column_position(columns, col)
+            .ok()
+            .map(|index| ScalarExpression::InputRef { index, ty: *col.datatype() }));
+
+        Ok(())
+    }
+
     pub fn has_agg_call<S: Storage>(&self, context: &BinderContext<S>) -> bool {
         match self {
             ScalarExpression::InputRef { index, .. } => {
@@ -161,6 +257,9 @@ impl ScalarExpression {
             ScalarExpression::Binary { left_expr, right_expr, .. } => {
                 left_expr.has_agg_call(context) || right_expr.has_agg_call(context)
             }
+            ScalarExpression::Coalesce { exprs, .. } => {
+                exprs.iter().any(|expr| expr.has_agg_call(context))
+            }
         }
     }
 
@@ -183,10 +282,26 @@ impl ScalarExpression {
                     ColumnDesc::new(expr.return_type(), false)
                 ))
             }
-            ScalarExpression::AggCall { kind, args, ty, distinct } => {
+            ScalarExpression::AggCall { kind, args, ty, distinct, filter, order_by } => {
                 let args_str = args.iter()
                     .map(|expr| expr.output_columns(tuple).name.clone())
                     .join(", ");
+                let order_by_str = if order_by.is_empty() {
+                    String::new()
+                } else {
+                    let fields = order_by.iter()
+                        .map(|SortField { expr, desc, .. }| {
+                            let name = expr.output_columns(tuple).name.clone();
+
+                            if *desc { format!("{} DESC", name) } else { name }
+                        })
+                        .join(", ");
+
+                    format!(" ORDER BY {}", fields)
+                };
+                let filter_str = filter.as_ref()
+                    .map(|expr| format!(" FILTER (WHERE {})", expr.output_columns(tuple).name))
+                    .unwrap_or_default();
                 let op = |allow_distinct, distinct| {
                     if allow_distinct && distinct {
                         "DISTINCT "
@@ -195,10 +310,12 @@ impl ScalarExpression {
                     }
                 };
                 let column_name = format!(
-                    "{:?}({}{})",
+                    "{:?}({}{}{}){}",
                     kind,
                     op(kind.allow_distinct(), *distinct),
-                    args_str
+                    args_str,
+                    order_by_str,
+                    filter_str
                 );
 
                 Arc::new(ColumnCatalog::new(
@@ -229,7 +346,104 @@ impl ScalarExpression {
                     ColumnDesc::new(ty.clone(), false)
                 ))
             }
-            _ => unreachable!()
+            ScalarExpression::Unary { expr, op, ty } => {
+                let column_name = format!(
+                    "({} {})",
+                    op,
+                    expr.output_columns(tuple).name,
+                );
+
+                Arc::new(ColumnCatalog::new(
+                    column_name,
+                    true,
+                    ColumnDesc::new(ty.clone(), false)
+                ))
+            }
+            ScalarExpression::TypeCast { expr, ty, .. } => {
+                let column_name = format!(
+                    "CAST({} AS {})",
+                    expr.output_columns(tuple).name,
+                    ty,
+                );
+
+                Arc::new(ColumnCatalog::new(
+                    column_name,
+                    true,
+                    ColumnDesc::new(ty.clone(), false)
+                ))
+            }
+            ScalarExpression::Coalesce { exprs, ty } => {
+                let column_name = format!(
+                    "COALESCE({})",
+                    exprs.iter()
+                        .map(|expr| expr.output_columns(tuple).name.clone())
+                        .join(", "),
+                );
+
+                Arc::new(ColumnCatalog::new(
+                    column_name,
+                    true,
+                    ColumnDesc::new(ty.clone(), false)
+                ))
+            }
+            ScalarExpression::IsNull { negated, expr } => {
+                let column_name = format!(
+                    "({} IS {}NULL)",
+                    expr.output_columns(tuple).name,
+                    if *negated { "NOT " } else { "" },
+                );
+
+                Arc::new(ColumnCatalog::new(
+                    column_name,
+                    true,
+                    ColumnDesc::new(LogicalType::Boolean, false)
+                ))
+            }
+        }
+    }
+}
+
+/// Used by `EXPLAIN`, the column references are qualified by their tables.
+impl fmt::Display for ScalarExpression {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            ScalarExpression::Constant(value) => write!(f, "{}", value),
+            ScalarExpression::ColumnRef(col) => match &col.table_name {
+                Some(table_name) => write!(f, "{}.{}", table_name, col.name),
+                None => write!(f, "{}", col.name),
+            },
+            ScalarExpression::InputRef { index, .. } => write!(f, "#{}", index),
+            ScalarExpression::Alias { expr, alias } => write!(f, "{} AS {}", expr, alias),
+            ScalarExpression::TypeCast { expr, ty, is_try } => {
+                let cast = if *is_try { "TRY_CAST" } else { "CAST" };
+
+                write!(f, "{}({} AS {})", cast, expr, ty)
+            }
+            ScalarExpression::IsNull { negated, expr } => {
+                write!(f, "({} IS {}NULL)", expr, if *negated { "NOT " } else { "" })
+            }
+            ScalarExpression::Unary { op, expr, .. } => write!(f, "({} {})", op, expr),
+            ScalarExpression::Binary { op, left_expr, right_expr, .. } => {
+                write!(f, "({} {} {})", left_expr, op, right_expr)
+            }
+            ScalarExpression::AggCall { distinct, kind, args, filter, order_by, .. } => {
+                write!(f, "{:?}(", kind)?;
+                if *distinct && kind.allow_distinct() {
+                    write!(f, "DISTINCT ")?;
+                }
+                write!(f, "{}", args.iter().join(", "))?;
+                if !order_by.is_empty() {
+                    write!(f, " ORDER BY {}", order_by.iter().join(", "))?;
+                }
+                write!(f, ")")?;
+                if let Some(expr) = filter {
+                    write!(f, " FILTER (WHERE {})", expr)?;
+                }
+                Ok(())
+            }
+            ScalarExpression::Coalesce { exprs, .. } => {
+                write!(f, "COALESCE({})", exprs.iter().join(", "))
+            }
         }
     }
 }
@@ -241,6 +455,16 @@ pub enum UnaryOperator {
     Not,
 }
 
+impl fmt::Display for UnaryOperator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            UnaryOperator::Plus => write!(f, "+"),
+            UnaryOperator::Minus => write!(f, "-"),
+            UnaryOperator::Not => write!(f, "!"),
+        }
+    }
+}
+
 impl From<SqlUnaryOperator> for UnaryOperator {
     fn from(value: SqlUnaryOperator) -> Self {
         match value {
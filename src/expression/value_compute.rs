@@ -1,4 +1,6 @@
+use std::cmp::Ordering;
 use crate::expression::{BinaryOperator, UnaryOperator};
+use crate::types::decimal;
 use crate::types::errors::TypeError;
 use crate::types::LogicalType;
 use crate::types::value::DataValue;
@@ -59,6 +61,149 @@ fn unpack_date(value: DataValue) -> Option<i64> {
     }
 }
 
+fn unpack_utf8(value: DataValue) -> Option<String> {
+    match value {
+        DataValue::Utf8(inner) => inner,
+        _ => None
+    }
+}
+
+/// The unscaled value and the scale, of the integers too.
+fn unpack_decimal(value: &DataValue) -> Result<Option<(i128, u8)>, TypeError> {
+    if value.is_null() {
+        return Ok(None);
+    }
+    let (precision, scale) = value.logical_type()
+        .decimal_type()
+        .ok_or(TypeError::InvalidType)?;
+
+    match value.clone().cast(&LogicalType::Decimal(precision, scale))? {
+        DataValue::Decimal(inner, _, scale) => Ok(inner.map(|v| (v, scale))),
+        _ => unreachable!(),
+    }
+}
+
+/// The arithmetic of the numeric types, an integer which does not fit its type is an error
+/// instead of wrapping around.
+pub(crate) trait CheckedArithmetic: Copy {
+    fn checked_plus(self, other: Self) -> Result<Self, TypeError>;
+
+    fn checked_minus(self, other: Self) -> Result<Self, TypeError>;
+
+    fn checked_multiply(self, other: Self) -> Result<Self, TypeError>;
+
+    /// The quotient of integers is truncated toward zero, e.g. `7 / 2` is 3 and `-7 / 2` is -3.
+    fn checked_divide(self, other: Self) -> Result<Self, TypeError>;
+
+    /// The remainder has the sign of the dividend, e.g. `-7 % 3` is -1.
+    fn checked_modulo(self, other: Self) -> Result<Self, TypeError>;
+}
+
+macro_rules! impl_checked_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl CheckedArithmetic for $ty {
+                fn checked_plus(self, other: Self) -> Result<Self, TypeError> {
+                    self.checked_add(other).ok_or(TypeError::Overflow)
+                }
+
+                fn checked_minus(self, other: Self) -> Result<Self, TypeError> {
+                    self.checked_sub(other).ok_or(TypeError::Overflow)
+                }
+
+                fn checked_multiply(self, other: Self) -> Result<Self, TypeError> {
+                    self.checked_mul(other).ok_or(TypeError::Overflow)
+                }
+
+                fn checked_divide(self, other: Self) -> Result<Self, TypeError> {
+                    if other == 0 {
+                        return Err(TypeError::DivisionByZero);
+                    }
+                    // `MIN / -1`
+                    self.checked_div(other).ok_or(TypeError::Overflow)
+                }
+
+                fn checked_modulo(self, other: Self) -> Result<Self, TypeError> {
+                    if other == 0 {
+                        return Err(TypeError::DivisionByZero);
+                    }
+                    // `MIN % -1`
+                    self.checked_rem(other).ok_or(TypeError::Overflow)
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_checked_float {
+    ($($ty:ty),*) => {
+        $(
+            impl CheckedArithmetic for $ty {
+                fn checked_plus(self, other: Self) -> Result<Self, TypeError> {
+                    Ok(self + other)
+                }
+
+                fn checked_minus(self, other: Self) -> Result<Self, TypeError> {
+                    Ok(self - other)
+                }
+
+                fn checked_multiply(self, other: Self) -> Result<Self, TypeError> {
+                    Ok(self * other)
+                }
+
+                fn checked_divide(self, other: Self) -> Result<Self, TypeError> {
+                    if other == 0.0 {
+                        return Err(TypeError::DivisionByZero);
+                    }
+                    Ok(self / other)
+                }
+
+                fn checked_modulo(self, other: Self) -> Result<Self, TypeError> {
+                    if other == 0.0 {
+                        return Err(TypeError::DivisionByZero);
+                    }
+                    Ok(self % other)
+                }
+            }
+        )*
+    };
+}
+
+impl_checked_integer!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+impl_checked_float!(f32, f64);
+
+/// The type of the result of an arithmetic operator:
+/// - the one both sides are cast to, e.g. `INTEGER + BIGINT` is a BIGINT
+/// - `/` of integers is truncated in their common type, e.g. `7 / 2` is 3, `/` of floats is a DOUBLE
+/// - decimals, or decimals and integers, have the precision and scale of
+/// `decimal::arithmetic_type`, e.g. `DECIMAL(10, 2) * DECIMAL(10, 2)` is a `DECIMAL(20, 4)`
+pub fn binary_result_type(
+    left: &LogicalType,
+    right: &LogicalType,
+    op: &BinaryOperator,
+) -> Result<LogicalType, TypeError> {
+    let unified_type = LogicalType::max_logical_type(left, right)?;
+
+    if let LogicalType::Decimal(..) = unified_type {
+        let decimal_type = |ty: &LogicalType| if ty == &LogicalType::SqlNull {
+            unified_type.decimal_type()
+        } else {
+            ty.decimal_type()
+        };
+
+        if let (Some(left), Some(right)) = (decimal_type(left), decimal_type(right)) {
+            let (precision, scale) = decimal::arithmetic_type(op, left, right);
+
+            return Ok(LogicalType::Decimal(precision, scale));
+        }
+    }
+    if let (BinaryOperator::Divide, LogicalType::Float | LogicalType::Double) = (op, unified_type) {
+        return Ok(LogicalType::Double);
+    }
+
+    Ok(unified_type)
+}
+
 pub fn unary_op(
     value: &DataValue,
     op: &UnaryOperator,
@@ -84,10 +229,11 @@ pub fn unary_op(
                 match value {
                     DataValue::Float32(option) => DataValue::Float32(option.map(|v| -v)),
                     DataValue::Float64(option) => DataValue::Float64(option.map(|v| -v)),
-                    DataValue::Int8(option) => DataValue::Int8(option.map(|v| -v)),
-                    DataValue::Int16(option) => DataValue::Int16(option.map(|v| -v)),
-                    DataValue::Int32(option) => DataValue::Int32(option.map(|v| -v)),
-                    DataValue::Int64(option) => DataValue::Int64(option.map(|v| -v)),
+                    DataValue::Int8(option) => DataValue::Int8(option.map(|v| v.checked_neg().ok_or(TypeError::Overflow)).transpose()?),
+                    DataValue::Int16(option) => DataValue::Int16(option.map(|v| v.checked_neg().ok_or(TypeError::Overflow)).transpose()?),
+                    DataValue::Int32(option) => DataValue::Int32(option.map(|v| v.checked_neg().ok_or(TypeError::Overflow)).transpose()?),
+                    DataValue::Int64(option) => DataValue::Int64(option.map(|v| v.checked_neg().ok_or(TypeError::Overflow)).transpose()?),
+                    DataValue::Decimal(option, precision, scale) => DataValue::Decimal(option.map(|v| -v), precision, scale),
                     _ => unreachable!()
                 }
             }
@@ -100,24 +246,45 @@ pub fn unary_op(
             DataValue::Boolean(option) => Ok(DataValue::Boolean(option.map(|v| !v))),
             _ => unreachable!()
         }
+    } else if value_type == LogicalType::SqlNull {
+        // `NOT NULL` is unknown
+        match op {
+            UnaryOperator::Not => Ok(DataValue::Boolean(None)),
+            _ => Ok(DataValue::Null),
+        }
     } else {
         Err(TypeError::InvalidType)
     }
 }
 
 /// Tips:
-/// - Null values operate as null values
+/// - NULL is unknown, the result of an operation with a NULL is NULL, except for `AND` and `OR`
+/// when the other side decides it, e.g. `FALSE AND NULL` is FALSE, and `<=>` which is never NULL
+/// - an integer result which does not fit the common type of both sides is `TypeError::Overflow`
+/// - the result types of the arithmetic are the ones of `binary_result_type`, `/` and `%` fail
+/// with `TypeError::DivisionByZero` when the divisor is zero
+/// - decimals, also with integers, are computed exactly, except for `/` which is rounded to its scale
 pub fn binary_op(
     left: &DataValue,
     right: &DataValue,
     op: &BinaryOperator,
 ) -> Result<DataValue, TypeError> {
+    // `IS NOT DISTINCT FROM`: NULL only equals NULL, never unknown
+    if let BinaryOperator::Spaceship = op {
+        let value = match (left.is_null(), right.is_null()) {
+            (false, false) => matches!(binary_op(left, right, &BinaryOperator::Eq)?, DataValue::Boolean(Some(true))),
+            (left_is_null, right_is_null) => left_is_null && right_is_null,
+        };
+
+        return Ok(DataValue::Boolean(Some(value)));
+    }
     let unified_type = LogicalType::max_logical_type(
         &left.logical_type(),
         &right.logical_type()
     )?;
 
     let value = match &unified_type {
+        LogicalType::Decimal(..) => decimal_binary_op(left, right, op)?,
         LogicalType::Integer => {
             let left_value = unpack_i32(left.clone().cast(&unified_type)?);
             let right_value = unpack_i32(right.clone().cast(&unified_type)?);
@@ -125,7 +292,7 @@ pub fn binary_op(
             match op {
                 BinaryOperator::Plus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 + v2)
+                        Some(v1.checked_plus(v2)?)
                     } else {
                         None
                     };
@@ -134,7 +301,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Minus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 - v2)
+                        Some(v1.checked_minus(v2)?)
                     } else {
                         None
                     };
@@ -143,7 +310,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Multiply => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 * v2)
+                        Some(v1.checked_multiply(v2)?)
                     } else {
                         None
                     };
@@ -152,12 +319,21 @@ pub fn binary_op(
                 }
                 BinaryOperator::Divide => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 as f64 / v2 as f64)
+                        Some(v1.checked_divide(v2)?)
                     } else {
                         None
                     };
 
-                    DataValue::Float64(value)
+                    DataValue::Int32(value)
+                }
+                BinaryOperator::Modulo => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1.checked_modulo(v2)?)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Int32(value)
                 }
 
                 BinaryOperator::Gt => {
@@ -197,16 +373,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -220,7 +390,7 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::Bigint => {
@@ -230,7 +400,7 @@ pub fn binary_op(
             match op {
                 BinaryOperator::Plus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 + v2)
+                        Some(v1.checked_plus(v2)?)
                     } else {
                         None
                     };
@@ -239,7 +409,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Minus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 - v2)
+                        Some(v1.checked_minus(v2)?)
                     } else {
                         None
                     };
@@ -248,7 +418,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Multiply => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 * v2)
+                        Some(v1.checked_multiply(v2)?)
                     } else {
                         None
                     };
@@ -257,12 +427,21 @@ pub fn binary_op(
                 }
                 BinaryOperator::Divide => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 as f64 / v2 as f64)
+                        Some(v1.checked_divide(v2)?)
                     } else {
                         None
                     };
 
-                    DataValue::Float64(value)
+                    DataValue::Int64(value)
+                }
+                BinaryOperator::Modulo => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1.checked_modulo(v2)?)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Int64(value)
                 }
 
                 BinaryOperator::Gt => {
@@ -302,16 +481,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -325,7 +498,7 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::UInteger => {
@@ -335,7 +508,7 @@ pub fn binary_op(
             match op {
                 BinaryOperator::Plus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 + v2)
+                        Some(v1.checked_plus(v2)?)
                     } else {
                         None
                     };
@@ -344,7 +517,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Minus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 - v2)
+                        Some(v1.checked_minus(v2)?)
                     } else {
                         None
                     };
@@ -353,7 +526,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Multiply => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 * v2)
+                        Some(v1.checked_multiply(v2)?)
                     } else {
                         None
                     };
@@ -362,12 +535,21 @@ pub fn binary_op(
                 }
                 BinaryOperator::Divide => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 as f64 / v2 as f64)
+                        Some(v1.checked_divide(v2)?)
                     } else {
                         None
                     };
 
-                    DataValue::Float64(value)
+                    DataValue::UInt32(value)
+                }
+                BinaryOperator::Modulo => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1.checked_modulo(v2)?)
+                    } else {
+                        None
+                    };
+
+                    DataValue::UInt32(value)
                 }
 
                 BinaryOperator::Gt => {
@@ -407,16 +589,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -430,7 +606,7 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::UBigint => {
@@ -440,7 +616,7 @@ pub fn binary_op(
             match op {
                 BinaryOperator::Plus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 + v2)
+                        Some(v1.checked_plus(v2)?)
                     } else {
                         None
                     };
@@ -449,7 +625,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Minus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 - v2)
+                        Some(v1.checked_minus(v2)?)
                     } else {
                         None
                     };
@@ -458,7 +634,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Multiply => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 * v2)
+                        Some(v1.checked_multiply(v2)?)
                     } else {
                         None
                     };
@@ -467,12 +643,21 @@ pub fn binary_op(
                 }
                 BinaryOperator::Divide => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 as f64 / v2 as f64)
+                        Some(v1.checked_divide(v2)?)
                     } else {
                         None
                     };
 
-                    DataValue::Float64(value)
+                    DataValue::UInt64(value)
+                }
+                BinaryOperator::Modulo => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1.checked_modulo(v2)?)
+                    } else {
+                        None
+                    };
+
+                    DataValue::UInt64(value)
                 }
 
                 BinaryOperator::Gt => {
@@ -512,16 +697,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -535,7 +714,7 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::Double => {
@@ -545,7 +724,7 @@ pub fn binary_op(
             match op {
                 BinaryOperator::Plus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 + v2)
+                        Some(v1.checked_plus(v2)?)
                     } else {
                         None
                     };
@@ -554,7 +733,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Minus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 - v2)
+                        Some(v1.checked_minus(v2)?)
                     } else {
                         None
                     };
@@ -563,7 +742,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Multiply => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 * v2)
+                        Some(v1.checked_multiply(v2)?)
                     } else {
                         None
                     };
@@ -572,7 +751,16 @@ pub fn binary_op(
                 }
                 BinaryOperator::Divide => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 / v2)
+                        Some(v1.checked_divide(v2)?)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Float64(value)
+                }
+                BinaryOperator::Modulo => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1.checked_modulo(v2)?)
                     } else {
                         None
                     };
@@ -617,16 +805,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -640,7 +822,7 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::Boolean => {
@@ -648,25 +830,53 @@ pub fn binary_op(
             let right_value = unpack_bool(right.clone().cast(&unified_type)?);
 
             match op {
+                // three-valued logic: NULL is unknown, unless the other side decides the result
                 BinaryOperator::And => {
+                    let value = match (left_value, right_value) {
+                        (Some(false), _) | (_, Some(false)) => Some(false),
+                        (Some(true), Some(true)) => Some(true),
+                        _ => None,
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::Or => {
+                    let value = match (left_value, right_value) {
+                        (Some(true), _) | (_, Some(true)) => Some(true),
+                        (Some(false), Some(false)) => Some(false),
+                        _ => None,
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::Xor => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 && v2)
+                        Some(v1 ^ v2)
                     } else {
                         None
                     };
 
                     DataValue::Boolean(value)
                 }
-                BinaryOperator::Or => {
+                BinaryOperator::Eq => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 || v2)
+                        Some(v1 == v2)
                     } else {
                         None
                     };
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                BinaryOperator::NotEq => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 != v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::Float => {
@@ -676,7 +886,7 @@ pub fn binary_op(
             match op {
                 BinaryOperator::Plus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 + v2)
+                        Some(v1.checked_plus(v2)?)
                     } else {
                         None
                     };
@@ -685,7 +895,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Minus => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 - v2)
+                        Some(v1.checked_minus(v2)?)
                     } else {
                         None
                     };
@@ -694,7 +904,7 @@ pub fn binary_op(
                 }
                 BinaryOperator::Multiply => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 * v2)
+                        Some(v1.checked_multiply(v2)?)
                     } else {
                         None
                     };
@@ -703,13 +913,22 @@ pub fn binary_op(
                 }
                 BinaryOperator::Divide => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
-                        Some(v1 as f64 / v2 as f64)
+                        Some((v1 as f64).checked_divide(v2 as f64)?)
                     } else {
                         None
                     };
 
                     DataValue::Float64(value)
                 }
+                BinaryOperator::Modulo => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1.checked_modulo(v2)?)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Float32(value)
+                }
                 BinaryOperator::Gt => {
                     let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
                         Some(v1 > v2)
@@ -747,16 +966,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -770,7 +983,7 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
         LogicalType::SqlNull => {
@@ -818,16 +1031,10 @@ pub fn binary_op(
                     DataValue::Boolean(value)
                 }
                 BinaryOperator::Eq => {
-                    let value = match (left_value, right_value) {
-                        (Some(v1), Some(v2)) => {
-                            Some(v1 == v2)
-                        }
-                        (None, None) => {
-                            Some(true)
-                        }
-                        (_, _) => {
-                            None
-                        }
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
                     };
 
                     DataValue::Boolean(value)
@@ -841,19 +1048,134 @@ pub fn binary_op(
 
                     DataValue::Boolean(value)
                 }
-                _ => todo!("unsupported operator")
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
             }
         }
-        _ => todo!("unsupported data type"),
+        LogicalType::Varchar => {
+            let left_value = unpack_utf8(left.clone().cast(&unified_type)?);
+            let right_value = unpack_utf8(right.clone().cast(&unified_type)?);
+
+            match op {
+                BinaryOperator::Gt => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 > v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::Lt => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 < v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::GtEq => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 >= v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::LtEq => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 <= v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::Eq => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 == v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                BinaryOperator::NotEq => {
+                    let value = if let (Some(v1), Some(v2)) = (left_value, right_value) {
+                        Some(v1 != v2)
+                    } else {
+                        None
+                    };
+
+                    DataValue::Boolean(value)
+                }
+                _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
+            }
+        }
+        _ => return Err(TypeError::InvalidType),
     };
 
     Ok(value)
 }
 
+/// Both sides are decimals or integers, computed on their unscaled values.
+fn decimal_binary_op(
+    left: &DataValue,
+    right: &DataValue,
+    op: &BinaryOperator,
+) -> Result<DataValue, TypeError> {
+    let (left_value, right_value) = (unpack_decimal(left)?, unpack_decimal(right)?);
+
+    if let Some(compare) = match op {
+        BinaryOperator::Gt => Some(Ordering::is_gt as fn(Ordering) -> bool),
+        BinaryOperator::Lt => Some(Ordering::is_lt as fn(Ordering) -> bool),
+        BinaryOperator::GtEq => Some(Ordering::is_ge as fn(Ordering) -> bool),
+        BinaryOperator::LtEq => Some(Ordering::is_le as fn(Ordering) -> bool),
+        BinaryOperator::Eq => Some(Ordering::is_eq as fn(Ordering) -> bool),
+        BinaryOperator::NotEq => Some(Ordering::is_ne as fn(Ordering) -> bool),
+        _ => None,
+    } {
+        let value = if let (Some((v1, s1)), Some((v2, s2))) = (left_value, right_value) {
+            Some(compare(decimal::compare(v1, s1, v2, s2)))
+        } else {
+            None
+        };
+
+        return Ok(DataValue::Boolean(value));
+    }
+    let LogicalType::Decimal(precision, scale) = binary_result_type(&left.logical_type(), &right.logical_type(), op)? else {
+        unreachable!()
+    };
+    let (Some((v1, s1)), Some((v2, s2))) = (left_value, right_value) else {
+        return Ok(DataValue::Decimal(None, precision, scale));
+    };
+
+    let value = match op {
+        BinaryOperator::Plus | BinaryOperator::Minus | BinaryOperator::Modulo => {
+            let common_scale = s1.max(s2);
+            let v1 = decimal::rescale(v1, s1, common_scale)?;
+            let v2 = decimal::rescale(v2, s2, common_scale)?;
+            let value = match op {
+                BinaryOperator::Plus => v1.checked_plus(v2)?,
+                BinaryOperator::Minus => v1.checked_minus(v2)?,
+                _ => v1.checked_modulo(v2)?,
+            };
+
+            decimal::rescale(value, common_scale, scale)?
+        }
+        BinaryOperator::Multiply => decimal::rescale(v1.checked_multiply(v2)?, s1 + s2, scale)?,
+        BinaryOperator::Divide => decimal::divide(v1, s1, v2, s2, scale)?,
+        _ => return Err(TypeError::UnsupportedOperator(op.to_string())),
+    };
+
+    Ok(DataValue::Decimal(Some(decimal::check_precision(value, precision)?), precision, scale))
+}
+
 #[cfg(test)]
 mod test {
-    use crate::expression::value_compute::binary_op;
-    use crate::expression::BinaryOperator;
+    use crate::expression::value_compute::{binary_op, unary_op};
+    use crate::expression::{BinaryOperator, UnaryOperator};
     use crate::types::errors::TypeError;
     use crate::types::value::DataValue;
 
@@ -889,6 +1211,83 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn test_checked_arithmetic() -> Result<(), TypeError> {
+        let is_overflow = |result: Result<DataValue, TypeError>| matches!(result, Err(TypeError::Overflow));
+        let is_division_by_zero = |result: Result<DataValue, TypeError>| matches!(result, Err(TypeError::DivisionByZero));
+
+        assert!(is_overflow(binary_op(&DataValue::Int32(Some(i32::MAX)), &DataValue::Int32(Some(1)), &BinaryOperator::Plus)));
+        assert!(is_overflow(binary_op(&DataValue::Int64(Some(i64::MIN)), &DataValue::Int64(Some(1)), &BinaryOperator::Minus)));
+        assert!(is_overflow(binary_op(&DataValue::UInt32(Some(0)), &DataValue::UInt32(Some(1)), &BinaryOperator::Minus)));
+        assert!(is_overflow(binary_op(&DataValue::UInt64(Some(u64::MAX)), &DataValue::UInt64(Some(2)), &BinaryOperator::Multiply)));
+        assert!(is_overflow(binary_op(&DataValue::Int32(Some(i32::MIN)), &DataValue::Int32(Some(-1)), &BinaryOperator::Modulo)));
+        assert!(is_overflow(binary_op(&DataValue::Int64(Some(i64::MIN)), &DataValue::Int64(Some(-1)), &BinaryOperator::Divide)));
+        assert!(is_overflow(unary_op(&DataValue::Int32(Some(i32::MIN)), &UnaryOperator::Minus)));
+        // the common type of both sides is wide enough
+        assert_eq!(
+            binary_op(&DataValue::Int32(Some(i32::MAX)), &DataValue::Int64(Some(1)), &BinaryOperator::Plus)?,
+            DataValue::Int64(Some(i32::MAX as i64 + 1))
+        );
+
+        assert!(is_division_by_zero(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int32(Some(0)), &BinaryOperator::Divide)));
+        assert!(is_division_by_zero(binary_op(&DataValue::Float64(Some(1.0)), &DataValue::Float64(Some(0.0)), &BinaryOperator::Divide)));
+        assert!(is_division_by_zero(binary_op(&DataValue::UInt64(Some(1)), &DataValue::UInt64(Some(0)), &BinaryOperator::Modulo)));
+        assert!(is_division_by_zero(binary_op(&DataValue::Float32(Some(1.0)), &DataValue::Float32(Some(0.0)), &BinaryOperator::Modulo)));
+        // NULL is not a zero
+        assert_eq!(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int32(None), &BinaryOperator::Divide)?, DataValue::Int32(None));
+
+        // the division of integers is truncated toward zero
+        assert_eq!(binary_op(&DataValue::Int32(Some(7)), &DataValue::Int32(Some(2)), &BinaryOperator::Divide)?, DataValue::Int32(Some(3)));
+        assert_eq!(binary_op(&DataValue::Int32(Some(-7)), &DataValue::Int32(Some(2)), &BinaryOperator::Divide)?, DataValue::Int32(Some(-3)));
+        // exact beyond the integers of doubles
+        assert_eq!(
+            binary_op(&DataValue::Int64(Some(9007199254740993)), &DataValue::Int64(Some(1)), &BinaryOperator::Divide)?,
+            DataValue::Int64(Some(9007199254740993))
+        );
+        assert_eq!(binary_op(&DataValue::Float64(Some(7.0)), &DataValue::Int32(Some(2)), &BinaryOperator::Divide)?, DataValue::Float64(Some(3.5)));
+        assert_eq!(binary_op(&DataValue::Int32(Some(-7)), &DataValue::Int32(Some(3)), &BinaryOperator::Modulo)?, DataValue::Int32(Some(-1)));
+        assert_eq!(binary_op(&DataValue::Float64(Some(7.5)), &DataValue::Float64(Some(2.0)), &BinaryOperator::Modulo)?, DataValue::Float64(Some(1.5)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_decimal_arithmetic() -> Result<(), TypeError> {
+        let decimal = |v: i128, precision: u8, scale: u8| DataValue::Decimal(Some(v), precision, scale);
+
+        // 0.1 + 0.2
+        assert_eq!(binary_op(&decimal(1, 2, 1), &decimal(20, 3, 2), &BinaryOperator::Plus)?, decimal(30, 4, 2));
+        // 19.99 * 3
+        assert_eq!(binary_op(&decimal(1999, 10, 2), &DataValue::Int32(Some(3)), &BinaryOperator::Multiply)?, decimal(5997, 20, 2));
+        // 1.00 - 1.5
+        assert_eq!(binary_op(&decimal(100, 3, 2), &decimal(15, 2, 1), &BinaryOperator::Minus)?, decimal(-50, 4, 2));
+        // 10.00 / 3, rounded
+        assert_eq!(binary_op(&decimal(1000, 4, 2), &DataValue::Int32(Some(3)), &BinaryOperator::Divide)?, decimal(3333333, 14, 6));
+        // -2 / 3.00
+        assert_eq!(binary_op(&DataValue::Int32(Some(-2)), &decimal(300, 3, 2), &BinaryOperator::Divide)?, decimal(-6667, 16, 4));
+        // 7.5 % 2
+        assert_eq!(binary_op(&decimal(75, 2, 1), &DataValue::Int32(Some(2)), &BinaryOperator::Modulo)?, decimal(15, 12, 1));
+        assert_eq!(unary_op(&decimal(75, 2, 1), &UnaryOperator::Minus)?, decimal(-75, 2, 1));
+
+        assert_eq!(binary_op(&decimal(150, 3, 2), &decimal(15, 2, 1), &BinaryOperator::Eq)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&decimal(-150, 3, 2), &DataValue::Int64(Some(-1)), &BinaryOperator::Lt)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&decimal(150, 3, 2), &DataValue::Null, &BinaryOperator::Plus)?, DataValue::Decimal(None, 3, 2));
+        // with the doubles, the result is not exact
+        assert_eq!(binary_op(&decimal(15, 2, 1), &DataValue::Float64(Some(0.5)), &BinaryOperator::Plus)?, DataValue::Float64(Some(2.0)));
+
+        // 1.0 / 1.0, the dividend would not fit an i128 with the digits of the quotient
+        let one = decimal(10_i128.pow(20), 38, 20);
+        assert_eq!(binary_op(&one, &one, &BinaryOperator::Divide)?, decimal(10_i128.pow(24), 38, 24));
+
+        assert!(matches!(binary_op(&decimal(1, 38, 0), &DataValue::Int32(Some(0)), &BinaryOperator::Divide), Err(TypeError::DivisionByZero)));
+        assert!(matches!(
+            binary_op(&decimal(10_i128.pow(37), 38, 0), &decimal(10_i128.pow(37), 38, 0), &BinaryOperator::Multiply),
+            Err(TypeError::Overflow)
+        ));
+
+        Ok(())
+    }
+
     #[test]
     fn test_binary_op_arithmetic_minus() -> Result<(), TypeError> {
         let minus_i32_1 = binary_op(&DataValue::Int32(None), &DataValue::Int32(None), &BinaryOperator::Minus)?;
@@ -962,7 +1361,7 @@ mod test {
 
         assert_eq!(divide_i32_1, divide_i32_2);
         assert_eq!(divide_i32_2, divide_i32_3);
-        assert_eq!(divide_i32_4, DataValue::Float64(Some(1.0)));
+        assert_eq!(divide_i32_4, DataValue::Int32(Some(1)));
 
         let divide_i64_1 = binary_op(&DataValue::Int64(None), &DataValue::Int64(None), &BinaryOperator::Divide)?;
         let divide_i64_2 = binary_op(&DataValue::Int64(Some(1)), &DataValue::Int64(None), &BinaryOperator::Divide)?;
@@ -971,7 +1370,7 @@ mod test {
 
         assert_eq!(divide_i64_1, divide_i64_2);
         assert_eq!(divide_i64_2, divide_i64_3);
-        assert_eq!(divide_i64_4, DataValue::Float64(Some(1.0)));
+        assert_eq!(divide_i64_4, DataValue::Int64(Some(1)));
 
         let divide_f64_1 = binary_op(&DataValue::Float64(None), &DataValue::Float64(None), &BinaryOperator::Divide)?;
         let divide_f64_2 = binary_op(&DataValue::Float64(Some(1.0)), &DataValue::Float64(None), &BinaryOperator::Divide)?;
@@ -1021,7 +1420,7 @@ mod test {
         assert_eq!(binary_op(&DataValue::Int32(None), &DataValue::Int32(Some(1)), &BinaryOperator::NotEq)?, DataValue::Boolean(None));
 
         assert_eq!(binary_op(&DataValue::Int32(None), &DataValue::Int32(Some(1)), &BinaryOperator::Eq)?, DataValue::Boolean(None));
-        assert_eq!(binary_op(&DataValue::Int32(None), &DataValue::Int32(None), &BinaryOperator::Eq)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&DataValue::Int32(None), &DataValue::Int32(None), &BinaryOperator::Eq)?, DataValue::Boolean(None));
 
         Ok(())
     }
@@ -1042,7 +1441,7 @@ mod test {
         assert_eq!(binary_op(&DataValue::Int64(None), &DataValue::Int64(Some(1)), &BinaryOperator::NotEq)?, DataValue::Boolean(None));
 
         assert_eq!(binary_op(&DataValue::Int64(None), &DataValue::Int64(Some(1)), &BinaryOperator::Eq)?, DataValue::Boolean(None));
-        assert_eq!(binary_op(&DataValue::Int64(None), &DataValue::Int64(None), &BinaryOperator::Eq)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&DataValue::Int64(None), &DataValue::Int64(None), &BinaryOperator::Eq)?, DataValue::Boolean(None));
 
         Ok(())
     }
@@ -1063,7 +1462,7 @@ mod test {
         assert_eq!(binary_op(&DataValue::Float64(None), &DataValue::Float64(Some(1.0)), &BinaryOperator::NotEq)?, DataValue::Boolean(None));
 
         assert_eq!(binary_op(&DataValue::Float64(None), &DataValue::Float64(Some(1.0)), &BinaryOperator::Eq)?, DataValue::Boolean(None));
-        assert_eq!(binary_op(&DataValue::Float64(None), &DataValue::Float64(None), &BinaryOperator::Eq)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&DataValue::Float64(None), &DataValue::Float64(None), &BinaryOperator::Eq)?, DataValue::Boolean(None));
 
         Ok(())
     }
@@ -1084,7 +1483,7 @@ mod test {
         assert_eq!(binary_op(&DataValue::Float32(None), &DataValue::Float32(Some(1.0)), &BinaryOperator::NotEq)?, DataValue::Boolean(None));
 
         assert_eq!(binary_op(&DataValue::Float32(None), &DataValue::Float32(Some(1.0)), &BinaryOperator::Eq)?, DataValue::Boolean(None));
-        assert_eq!(binary_op(&DataValue::Float32(None), &DataValue::Float32(None), &BinaryOperator::Eq)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&DataValue::Float32(None), &DataValue::Float32(None), &BinaryOperator::Eq)?, DataValue::Boolean(None));
 
         Ok(())
     }
@@ -1101,7 +1500,98 @@ mod test {
         assert_eq!(binary_op(&DataValue::Boolean(Some(false)), &DataValue::Boolean(Some(true)), &BinaryOperator::Or)?, DataValue::Boolean(Some(true)));
         assert_eq!(binary_op(&DataValue::Boolean(Some(false)), &DataValue::Boolean(Some(false)), &BinaryOperator::Or)?, DataValue::Boolean(Some(false)));
 
-        assert_eq!(binary_op(&DataValue::Boolean(None), &DataValue::Boolean(Some(true)), &BinaryOperator::Or)?, DataValue::Boolean(None));
+        assert_eq!(binary_op(&DataValue::Boolean(None), &DataValue::Boolean(Some(true)), &BinaryOperator::Or)?, DataValue::Boolean(Some(true)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_binary_op_is_not_distinct_from() -> Result<(), TypeError> {
+        assert_eq!(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int64(Some(1)), &BinaryOperator::Spaceship)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int32(Some(2)), &BinaryOperator::Spaceship)?, DataValue::Boolean(Some(false)));
+        assert_eq!(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int32(None), &BinaryOperator::Spaceship)?, DataValue::Boolean(Some(false)));
+        assert_eq!(binary_op(&DataValue::Int32(None), &DataValue::Null, &BinaryOperator::Spaceship)?, DataValue::Boolean(Some(true)));
+        assert_eq!(binary_op(&DataValue::Boolean(None), &DataValue::Boolean(Some(true)), &BinaryOperator::Spaceship)?, DataValue::Boolean(Some(false)));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_three_valued_logic() -> Result<(), TypeError> {
+        let (t, f, n) = (DataValue::Boolean(Some(true)), DataValue::Boolean(Some(false)), DataValue::Boolean(None));
+        // (left, right, AND, OR, XOR)
+        let truth_table = [
+            (&t, &t, &t, &t, &f),
+            (&t, &f, &f, &t, &t),
+            (&t, &n, &n, &t, &n),
+            (&f, &t, &f, &t, &t),
+            (&f, &f, &f, &f, &f),
+            (&f, &n, &f, &n, &n),
+            (&n, &t, &n, &t, &n),
+            (&n, &f, &f, &n, &n),
+            (&n, &n, &n, &n, &n),
+        ];
+
+        for (left, right, and, or, xor) in truth_table {
+            assert_eq!(&binary_op(left, right, &BinaryOperator::And)?, and, "{} AND {}", left, right);
+            assert_eq!(&binary_op(left, right, &BinaryOperator::Or)?, or, "{} OR {}", left, right);
+            assert_eq!(&binary_op(left, right, &BinaryOperator::Xor)?, xor, "{} XOR {}", left, right);
+        }
+        // the NULL literal
+        assert_eq!(binary_op(&f, &DataValue::Null, &BinaryOperator::And)?, f);
+        assert_eq!(binary_op(&DataValue::Null, &t, &BinaryOperator::Or)?, t);
+        assert_eq!(binary_op(&DataValue::Null, &DataValue::Null, &BinaryOperator::And)?, n);
+
+        assert_eq!(unary_op(&t, &UnaryOperator::Not)?, f);
+        assert_eq!(unary_op(&f, &UnaryOperator::Not)?, t);
+        assert_eq!(unary_op(&n, &UnaryOperator::Not)?, n);
+        assert_eq!(unary_op(&DataValue::Null, &UnaryOperator::Not)?, n);
+        assert_eq!(unary_op(&DataValue::Null, &UnaryOperator::Minus)?, DataValue::Null);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_unsupported_operator() {
+        let is_unsupported = |result: Result<DataValue, TypeError>| matches!(result, Err(TypeError::UnsupportedOperator(_)));
+        let utf8 = DataValue::Utf8(Some("a".to_string()));
+
+        assert!(is_unsupported(binary_op(&utf8, &utf8, &BinaryOperator::Plus)));
+        assert!(is_unsupported(binary_op(&DataValue::Boolean(Some(true)), &DataValue::Boolean(Some(false)), &BinaryOperator::Multiply)));
+        assert!(is_unsupported(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int32(Some(2)), &BinaryOperator::StringConcat)));
+        assert!(is_unsupported(binary_op(&DataValue::Decimal(Some(15), 2, 1), &DataValue::Int32(Some(2)), &BinaryOperator::StringConcat)));
+    }
+
+    #[test]
+    fn test_null_operands() -> Result<(), TypeError> {
+        let comparisons = [
+            BinaryOperator::Eq,
+            BinaryOperator::NotEq,
+            BinaryOperator::Gt,
+            BinaryOperator::Lt,
+            BinaryOperator::GtEq,
+            BinaryOperator::LtEq,
+        ];
+        let operands = [
+            (DataValue::Int32(None), DataValue::Int32(Some(1))),
+            (DataValue::Int32(None), DataValue::Int32(None)),
+            (DataValue::Int64(Some(1)), DataValue::Null),
+            (DataValue::Float64(None), DataValue::Float64(None)),
+            (DataValue::Utf8(None), DataValue::Utf8(Some("a".to_string()))),
+            (DataValue::Utf8(None), DataValue::Utf8(None)),
+        ];
+
+        for (left, right) in operands.iter() {
+            for op in comparisons.iter() {
+                assert_eq!(binary_op(left, right, op)?, DataValue::Boolean(None), "{} {} {}", left, op, right);
+                assert_eq!(binary_op(right, left, op)?, DataValue::Boolean(None), "{} {} {}", right, op, left);
+            }
+        }
+        for op in [BinaryOperator::Plus, BinaryOperator::Minus, BinaryOperator::Multiply, BinaryOperator::Divide] {
+            assert!(binary_op(&DataValue::Int32(Some(1)), &DataValue::Int32(None), &op)?.is_null());
+            assert!(binary_op(&DataValue::Null, &DataValue::Int64(Some(1)), &op)?.is_null());
+            assert!(binary_op(&DataValue::Float64(None), &DataValue::Int32(Some(1)), &op)?.is_null());
+        }
 
         Ok(())
     }
@@ -0,0 +1,280 @@
+use std::sync::Arc;
+use itertools::Itertools;
+use crate::expression::value_compute::{binary_op, unary_op, CheckedArithmetic};
+use crate::expression::{BinaryOperator, ScalarExpression};
+use crate::types::batch::{Bitmap, ColumnVector, RecordBatch, VectorData, VectorRef};
+use crate::types::errors::TypeError;
+use crate::types::LogicalType;
+use crate::types::value::DataValue;
+
+impl ScalarExpression {
+    /// Evaluate the expression for all the rows of the batch at once, the same as `eval_column`
+    /// for each of them.
+    pub fn eval_batch(&self, batch: &RecordBatch) -> Result<VectorRef, TypeError> {
+        match self {
+            ScalarExpression::Constant(value) => {
+                Ok(Arc::new(ColumnVector::from_constant(value, batch.len())?))
+            }
+            ScalarExpression::ColumnRef(col) => {
+                let index = batch.column_index(col)?;
+
+                Ok(batch.vectors[index].clone())
+            }
+            ScalarExpression::InputRef { index, .. } => Ok(batch.vectors[*index].clone()),
+            ScalarExpression::Alias { expr, .. } => expr.eval_batch(batch),
+            ScalarExpression::TypeCast { expr, ty, .. } => {
+                let vector = expr.eval_batch(batch)?;
+
+                if vector.logical_type() == ty {
+                    return Ok(vector);
+                }
+                Ok(Arc::new(ColumnVector::from_values(*ty, vector.values())?))
+            }
+            ScalarExpression::Binary { left_expr, right_expr, op, ty } => {
+                let left = left_expr.eval_batch(batch)?;
+                let right = right_expr.eval_batch(batch)?;
+
+                Ok(Arc::new(binary_kernel(&left, &right, op, *ty)?))
+            }
+            ScalarExpression::Unary { expr, op, ty } => {
+                let vector = expr.eval_batch(batch)?;
+                let values = vector.values()
+                    .map(|value| unary_op(&value, op))
+                    .try_collect()?;
+
+                Ok(Arc::new(ColumnVector::infer_from_values(values, *ty)?))
+            }
+            ScalarExpression::IsNull { negated, expr } => {
+                let vector = expr.eval_batch(batch)?;
+                let values = vector.validity()
+                    .iter()
+                    .map(|is_valid| is_valid == *negated)
+                    .collect();
+
+                Ok(Arc::new(ColumnVector::new(
+                    LogicalType::Boolean,
+                    VectorData::Boolean(values),
+                    Bitmap::new(batch.len(), true),
+                )))
+            }
+            // rare in the hot paths, evaluated row by row
+            ScalarExpression::Coalesce { .. }
+            | ScalarExpression::AggCall { .. } => {
+                let values = (0..batch.len())
+                    .map(|i| self.eval_column(&batch.row(i)).map(|value| DataValue::clone(&value)))
+                    .try_collect()?;
+
+                Ok(Arc::new(ColumnVector::infer_from_values(values, self.return_type())?))
+            }
+        }
+    }
+}
+
+/// Both sides are cast to their common type as in `binary_op`, which is used for each row when
+/// there is no kernel for the type and the operator.
+fn binary_kernel(
+    left: &ColumnVector,
+    right: &ColumnVector,
+    op: &BinaryOperator,
+    ty: LogicalType
+) -> Result<ColumnVector, TypeError> {
+    let unified_type = LogicalType::max_logical_type(left.logical_type(), right.logical_type())?;
+
+    if has_typed_kernel(&unified_type, op) {
+        let cast = |vector: &ColumnVector| {
+            if vector.logical_type() == &unified_type {
+                Ok(vector.clone())
+            } else {
+                ColumnVector::from_values(unified_type, vector.values())
+            }
+        };
+
+        return typed_binary_kernel(&cast(left)?, &cast(right)?, op);
+    }
+    let values = left.values()
+        .zip(right.values())
+        .map(|(left, right)| binary_op(&left, &right, op))
+        .try_collect()?;
+
+    ColumnVector::infer_from_values(values, ty)
+}
+
+fn has_typed_kernel(ty: &LogicalType, op: &BinaryOperator) -> bool {
+    match ty {
+        LogicalType::Integer
+        | LogicalType::Bigint
+        | LogicalType::UInteger
+        | LogicalType::UBigint
+        | LogicalType::Float
+        | LogicalType::Double => matches!(
+            op,
+            BinaryOperator::Plus
+                | BinaryOperator::Minus
+                | BinaryOperator::Multiply
+                | BinaryOperator::Modulo
+                | BinaryOperator::Gt
+                | BinaryOperator::Lt
+                | BinaryOperator::GtEq
+                | BinaryOperator::LtEq
+                | BinaryOperator::Eq
+                | BinaryOperator::NotEq
+        ),
+        LogicalType::Boolean => matches!(op, BinaryOperator::And | BinaryOperator::Or),
+        _ => false,
+    }
+}
+
+/// Both sides have the same type, which `has_typed_kernel` accepts with the operator.
+fn typed_binary_kernel(
+    left: &ColumnVector,
+    right: &ColumnVector,
+    op: &BinaryOperator
+) -> Result<ColumnVector, TypeError> {
+    let mut validity = left.validity().and(right.validity());
+    let ty = *left.logical_type();
+
+    // the slots of NULLs are skipped, e.g. `NULL - 1` of unsigned integers would overflow
+    macro_rules! apply {
+        ($left:expr, $right:expr, $variant:path, $f:expr) => {
+            $variant($left
+                .iter()
+                .zip($right.iter())
+                .enumerate()
+                .map(|(i, (a, b))| if validity.get(i) { $f(a, b) } else { Default::default() })
+                .collect())
+        };
+    }
+    // the arithmetic which may overflow or divide by zero, as in `binary_op`
+    macro_rules! try_apply {
+        ($left:expr, $right:expr, $variant:path, $f:expr) => {
+            $variant($left
+                .iter()
+                .zip($right.iter())
+                .enumerate()
+                .map(|(i, (a, b))| if validity.get(i) { $f(*a, *b) } else { Ok(Default::default()) })
+                .try_collect()?)
+        };
+    }
+    macro_rules! numeric_kernel {
+        ($left:expr, $right:expr, $variant:path) => {
+            match op {
+                BinaryOperator::Plus => (ty, try_apply!($left, $right, $variant, CheckedArithmetic::checked_plus)),
+                BinaryOperator::Minus => (ty, try_apply!($left, $right, $variant, CheckedArithmetic::checked_minus)),
+                BinaryOperator::Multiply => (ty, try_apply!($left, $right, $variant, CheckedArithmetic::checked_multiply)),
+                BinaryOperator::Modulo => (ty, try_apply!($left, $right, $variant, CheckedArithmetic::checked_modulo)),
+                BinaryOperator::Gt => (LogicalType::Boolean, apply!($left, $right, VectorData::Boolean, |a, b| a > b)),
+                BinaryOperator::Lt => (LogicalType::Boolean, apply!($left, $right, VectorData::Boolean, |a, b| a < b)),
+                BinaryOperator::GtEq => (LogicalType::Boolean, apply!($left, $right, VectorData::Boolean, |a, b| a >= b)),
+                BinaryOperator::LtEq => (LogicalType::Boolean, apply!($left, $right, VectorData::Boolean, |a, b| a <= b)),
+                BinaryOperator::Eq => (LogicalType::Boolean, apply!($left, $right, VectorData::Boolean, |a, b| a == b)),
+                BinaryOperator::NotEq => (LogicalType::Boolean, apply!($left, $right, VectorData::Boolean, |a, b| a != b)),
+                _ => unreachable!(),
+            }
+        };
+    }
+
+    let (ty, data) = match (left.data(), right.data()) {
+        (VectorData::Int32(l), VectorData::Int32(r)) => numeric_kernel!(l, r, VectorData::Int32),
+        (VectorData::Int64(l), VectorData::Int64(r)) => numeric_kernel!(l, r, VectorData::Int64),
+        (VectorData::UInt32(l), VectorData::UInt32(r)) => numeric_kernel!(l, r, VectorData::UInt32),
+        (VectorData::UInt64(l), VectorData::UInt64(r)) => numeric_kernel!(l, r, VectorData::UInt64),
+        (VectorData::Float32(l), VectorData::Float32(r)) => numeric_kernel!(l, r, VectorData::Float32),
+        (VectorData::Float64(l), VectorData::Float64(r)) => numeric_kernel!(l, r, VectorData::Float64),
+        (VectorData::Boolean(l), VectorData::Boolean(r)) => {
+            // three-valued logic as in `binary_op`: the side which is FALSE for `AND`, or TRUE
+            // for `OR`, decides the result even if the other one is NULL
+            let decisive = matches!(op, BinaryOperator::Or);
+            let values = (0..l.len())
+                .map(|i| {
+                    let (left_valid, right_valid) = (left.is_valid(i), right.is_valid(i));
+
+                    if (left_valid && l[i] == decisive) || (right_valid && r[i] == decisive) {
+                        validity.set(i, true);
+                        decisive
+                    } else {
+                        left_valid && right_valid && !decisive
+                    }
+                })
+                .collect();
+
+            (ty, VectorData::Boolean(values))
+        }
+        (left, right) => unreachable!("no kernel for {:?} and {:?}", left, right),
+    };
+
+    Ok(ColumnVector::new(ty, data, validity))
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use crate::catalog::{ColumnCatalog, ColumnDesc};
+    use crate::expression::{BinaryOperator, ScalarExpression, UnaryOperator};
+    use crate::types::batch::RecordBatch;
+    use crate::types::errors::TypeError;
+    use crate::types::LogicalType;
+    use crate::types::tuple::Tuple;
+    use crate::types::value::DataValue;
+
+    #[test]
+    fn test_eval_batch() -> Result<(), TypeError> {
+        let c1 = Arc::new(ColumnCatalog::new("c1".to_string(), true, ColumnDesc::new(LogicalType::Integer, false)));
+        let c2 = Arc::new(ColumnCatalog::new("c2".to_string(), true, ColumnDesc::new(LogicalType::Bigint, false)));
+        let tuples = [(Some(1), Some(10)), (None, Some(20)), (Some(3), None), (None, None), (Some(5), Some(5))]
+            .into_iter()
+            .map(|(v1, v2)| Tuple {
+                id: None,
+                columns: vec![c1.clone(), c2.clone()],
+                values: vec![Arc::new(DataValue::Int32(v1)), Arc::new(DataValue::Int64(v2))],
+            })
+            .collect::<Vec<_>>();
+        let batch = RecordBatch::from_tuples(&tuples)?;
+
+        let binary = |op, left_expr, right_expr| ScalarExpression::Binary {
+            op,
+            left_expr: Box::new(left_expr),
+            right_expr: Box::new(right_expr),
+            ty: LogicalType::Boolean,
+        };
+        let col_1 = ScalarExpression::ColumnRef(c1);
+        let col_2 = ScalarExpression::ColumnRef(c2);
+        let exprs = vec![
+            binary(BinaryOperator::Plus, col_1.clone(), col_2.clone()),
+            binary(BinaryOperator::Eq, col_1.clone(), col_2.clone()),
+            binary(
+                BinaryOperator::And,
+                binary(BinaryOperator::Gt, col_1.clone(), ScalarExpression::Constant(Arc::new(DataValue::Int32(Some(1))))),
+                binary(BinaryOperator::NotEq, col_2.clone(), ScalarExpression::Constant(Arc::new(DataValue::Int64(Some(5))))),
+            ),
+            binary(
+                BinaryOperator::Or,
+                binary(BinaryOperator::Lt, col_1.clone(), ScalarExpression::Constant(Arc::new(DataValue::Int32(Some(2))))),
+                binary(BinaryOperator::Eq, col_2.clone(), ScalarExpression::Constant(Arc::new(DataValue::Int64(Some(20))))),
+            ),
+            binary(BinaryOperator::Modulo, col_2.clone(), col_1.clone()),
+            // no typed kernel
+            binary(BinaryOperator::Divide, col_2.clone(), col_1.clone()),
+            ScalarExpression::Unary { op: UnaryOperator::Minus, expr: Box::new(col_1.clone()), ty: LogicalType::Integer },
+            ScalarExpression::TypeCast { expr: Box::new(col_1.clone()), ty: LogicalType::Bigint, is_try: false },
+            ScalarExpression::IsNull { negated: false, expr: Box::new(col_1.clone()) },
+            ScalarExpression::IsNull { negated: true, expr: Box::new(col_1) },
+        ];
+
+        for expr in exprs {
+            let vector = expr.eval_batch(&batch)?;
+
+            for (i, tuple) in tuples.iter().enumerate() {
+                assert_eq!(vector.value(i), *expr.eval_column(tuple)?, "{:?} of the row {}", expr, i);
+            }
+        }
+        // the rows with NULLs are skipped, the others overflow as with `eval_column`
+        let overflow = binary(
+            BinaryOperator::Multiply,
+            col_2,
+            ScalarExpression::Constant(Arc::new(DataValue::Int64(Some(i64::MAX)))),
+        );
+        assert!(matches!(overflow.eval_batch(&batch), Err(TypeError::Overflow)));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,223 @@
+use ahash::HashMap;
+use crate::catalog::{ColumnRef, ColumnStatistics, TableName, TableStatistics};
+use crate::expression::{BinaryOperator, ScalarExpression, UnaryOperator};
+use crate::planner::LogicalPlan;
+use crate::planner::operator::join::{JoinCondition, JoinType};
+use crate::planner::operator::Operator;
+use crate::types::value::DataValue;
+
+/// Row count of the tables without statistics.
+pub const DEFAULT_ROW_COUNT: f64 = 1000.0;
+/// Selectivity of `a = b` when the number of distinct values is unknown.
+const EQ_SELECTIVITY: f64 = 0.1;
+const RANGE_SELECTIVITY: f64 = 1.0 / 3.0;
+const DEFAULT_SELECTIVITY: f64 = 0.25;
+/// Groups of `GROUP BY` relative to the input rows.
+const GROUP_BY_RATIO: f64 = 0.1;
+/// Inserting a row into the hash table costs more than probing it.
+const HASH_BUILD_FACTOR: f64 = 2.0;
+
+/// Estimates the output rows and the costs of plans from the row counts of the tables and the
+/// statistics collected by `ANALYZE`, tables without a row count are assumed to have
+/// `DEFAULT_ROW_COUNT` rows.
+pub struct CostModel {
+    table_rows: HashMap<TableName, usize>,
+    statistics: HashMap<TableName, TableStatistics>,
+}
+
+impl CostModel {
+    pub fn new(table_rows: HashMap<TableName, usize>, statistics: HashMap<TableName, TableStatistics>) -> Self {
+        CostModel { table_rows, statistics }
+    }
+
+    pub fn table_rows(&self, table_name: &TableName) -> f64 {
+        self.table_rows
+            .get(table_name)
+            .map(|rows| *rows as f64)
+            .unwrap_or(DEFAULT_ROW_COUNT)
+    }
+
+    /// Whether the row counts of all the tables scanned by the plan are known, otherwise its
+    /// estimates are only made of the defaults.
+    pub fn has_row_counts(&self, plan: &LogicalPlan) -> bool {
+        match &plan.operator {
+            Operator::Scan(op) => self.table_rows.contains_key(&op.table_name),
+            _ => plan.childrens.iter().all(|child| self.has_row_counts(child)),
+        }
+    }
+
+    fn column_statistics(&self, col: &ColumnRef) -> Option<&ColumnStatistics> {
+        self.statistics.get(col.table_name.as_ref()?)?.column(&col.id)
+    }
+
+    /// Number of distinct values of the column, known for analyzed columns and primary keys.
+    fn column_ndv(&self, col: &ColumnRef) -> Option<f64> {
+        if let Some(statistics) = self.column_statistics(col) {
+            return Some(statistics.ndv as f64);
+        }
+        let table_name = col.table_name.as_ref()?;
+
+        col.desc.is_primary.then(|| self.table_rows(table_name))
+    }
+
+    fn not_null_fraction(&self, col: &ColumnRef) -> f64 {
+        self.column_statistics(col)
+            .map(|statistics| 1.0 - statistics.null_fraction)
+            .unwrap_or(1.0)
+    }
+
+    /// Selectivity of `col < value` or `col > value` from the histogram of the column.
+    fn range_selectivity(&self, col: &ColumnRef, value: &DataValue, is_less: bool) -> Option<f64> {
+        let statistics = self.column_statistics(col)?;
+        let less = statistics.histogram.less_than_fraction(value);
+        let fraction = if is_less { less } else { 1.0 - less };
+
+        Some(fraction * (1.0 - statistics.null_fraction))
+    }
+
+    /// Estimated output rows of the plan, at least 1.
+    pub fn estimate_rows(&self, plan: &LogicalPlan) -> f64 {
+        let child_rows = |index: usize| plan
+            .child(index)
+            .map(|child| self.estimate_rows(child))
+            .unwrap_or(1.0);
+
+        let rows = match &plan.operator {
+            Operator::Scan(op) => {
+                let rows = op.pre_where
+                    .iter()
+                    .fold(self.table_rows(&op.table_name), |rows, expr| rows * self.selectivity(expr));
+
+                match op.limit {
+                    (_, Some(limit)) => rows.min(limit as f64),
+                    _ => rows,
+                }
+            }
+            Operator::Filter(op) => child_rows(0) * self.selectivity(&op.predicate),
+            Operator::Join(op) => self.join_rows(op.join_type, &op.on, child_rows(0), child_rows(1)),
+            Operator::Aggregate(op) if op.groupby_exprs.is_empty() => 1.0,
+            Operator::Aggregate(_) => child_rows(0) * GROUP_BY_RATIO,
+            Operator::Limit(op) => child_rows(0).min(op.limit as f64),
+            Operator::Sort(op) => match op.limit {
+                Some(limit) => child_rows(0).min(limit as f64),
+                None => child_rows(0),
+            },
+            Operator::Values(op) => op.rows.len() as f64,
+            Operator::Project(_) | Operator::Apply(_) | Operator::Explain(_) => child_rows(0),
+            _ => 1.0,
+        };
+
+        rows.max(1.0)
+    }
+
+    /// Estimated output rows of a join with the given input rows.
+    pub fn join_rows(&self, join_type: JoinType, on: &JoinCondition, left_rows: f64, right_rows: f64) -> f64 {
+        let selectivity = match on {
+            JoinCondition::On { on, filter } => on
+                .iter()
+                .map(|(left_expr, right_expr)| self.equi_selectivity(left_expr, right_expr, left_rows.max(right_rows)))
+                .chain(filter.iter().map(|expr| self.selectivity(expr)))
+                .product(),
+            JoinCondition::None => 1.0,
+        };
+        let inner_rows = left_rows * right_rows * selectivity;
+
+        match join_type {
+            JoinType::Inner | JoinType::Cross => inner_rows,
+            JoinType::Left => inner_rows.max(left_rows),
+            JoinType::Right => inner_rows.max(right_rows),
+            JoinType::Full => inner_rows.max(left_rows).max(right_rows),
+            JoinType::LeftSemi => inner_rows.min(left_rows),
+            JoinType::LeftAnti | JoinType::NullAwareLeftAnti => left_rows - inner_rows.min(left_rows),
+        }
+    }
+
+    /// Selectivity of the equi-join key `left = right`, assuming that each row matches one row
+    /// of the side with more distinct values, whose count falls back to `default_ndv`.
+    pub fn equi_selectivity(&self, left: &ScalarExpression, right: &ScalarExpression, default_ndv: f64) -> f64 {
+        let ndv = |expr: &ScalarExpression| match expr.unpack_alias() {
+            ScalarExpression::ColumnRef(col) => self.column_ndv(col),
+            _ => None,
+        };
+        let ndv = match (ndv(left), ndv(right)) {
+            (Some(left_ndv), Some(right_ndv)) => left_ndv.max(right_ndv),
+            (Some(ndv), None) | (None, Some(ndv)) => ndv,
+            (None, None) => default_ndv,
+        };
+
+        1.0 / ndv.max(1.0)
+    }
+
+    /// The fraction of the rows that satisfy the predicate.
+    pub fn selectivity(&self, predicate: &ScalarExpression) -> f64 {
+        match predicate {
+            ScalarExpression::Binary { op: BinaryOperator::And, left_expr, right_expr, .. } => {
+                self.selectivity(left_expr) * self.selectivity(right_expr)
+            }
+            ScalarExpression::Binary { op: BinaryOperator::Or, left_expr, right_expr, .. } => {
+                let (left, right) = (self.selectivity(left_expr), self.selectivity(right_expr));
+
+                left + right - left * right
+            }
+            ScalarExpression::Unary { op: UnaryOperator::Not, expr, .. } => 1.0 - self.selectivity(expr),
+            ScalarExpression::Binary { op: BinaryOperator::Eq, left_expr, right_expr, .. } => {
+                match (left_expr.unpack_alias(), right_expr.unpack_alias()) {
+                    (ScalarExpression::ColumnRef(col), ScalarExpression::Constant(_))
+                    | (ScalarExpression::Constant(_), ScalarExpression::ColumnRef(col)) => {
+                        self.column_ndv(col)
+                            .map(|ndv| self.not_null_fraction(col) / ndv.max(1.0))
+                            .unwrap_or(EQ_SELECTIVITY)
+                    }
+                    _ => EQ_SELECTIVITY,
+                }
+            }
+            ScalarExpression::Binary { op: BinaryOperator::NotEq, .. } => 1.0 - EQ_SELECTIVITY,
+            ScalarExpression::Binary {
+                op: op @ (BinaryOperator::Gt | BinaryOperator::GtEq | BinaryOperator::Lt | BinaryOperator::LtEq),
+                left_expr,
+                right_expr,
+                ..
+            } => {
+                let is_less = matches!(op, BinaryOperator::Lt | BinaryOperator::LtEq);
+
+                match (left_expr.unpack_alias(), right_expr.unpack_alias()) {
+                    (ScalarExpression::ColumnRef(col), ScalarExpression::Constant(value)) => {
+                        self.range_selectivity(col, value, is_less)
+                    }
+                    // `1 < a` is `a > 1`
+                    (ScalarExpression::Constant(value), ScalarExpression::ColumnRef(col)) => {
+                        self.range_selectivity(col, value, !is_less)
+                    }
+                    _ => None,
+                }
+                .unwrap_or(RANGE_SELECTIVITY)
+            }
+            ScalarExpression::IsNull { negated, expr } => {
+                let selectivity = match expr.unpack_alias() {
+                    ScalarExpression::ColumnRef(col) => self.column_statistics(col)
+                        .map(|statistics| statistics.null_fraction)
+                        .unwrap_or(EQ_SELECTIVITY),
+                    _ => EQ_SELECTIVITY,
+                };
+
+                if *negated { 1.0 - selectivity } else { selectivity }
+            }
+            ScalarExpression::Constant(value) => match value.as_ref() {
+                DataValue::Boolean(Some(true)) => 1.0,
+                DataValue::Boolean(_) => 0.0,
+                _ => DEFAULT_SELECTIVITY,
+            },
+            _ => DEFAULT_SELECTIVITY,
+        }
+    }
+
+    /// Cost of joining the inputs, the left input is the build side of `HashJoin`, and the
+    /// joins without equi-join keys compare every pair of rows.
+    pub fn join_cost(&self, has_equi_keys: bool, left_rows: f64, right_rows: f64, output_rows: f64) -> f64 {
+        if has_equi_keys {
+            left_rows * HASH_BUILD_FACTOR + right_rows + output_rows
+        } else {
+            left_rows * right_rows + output_rows
+        }
+    }
+}
@@ -0,0 +1,80 @@
+use ahash::{HashMap, HashMapExt};
+
+/// Bitmap of the join inputs covered by a group, the bit `i` stands for the `i`-th input.
+pub type RelationSet = u32;
+pub type GroupId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum GroupExpr {
+    /// The `i`-th join input, which is not reordered
+    Input(usize),
+    /// Join of two groups, the left one is the build side
+    Join { left: GroupId, right: GroupId },
+}
+
+/// The equivalent expressions that join the same inputs, e.g. `(a ⋈ b) ⋈ c` and `a ⋈ (c ⋈ b)`.
+#[derive(Debug)]
+pub struct Group {
+    pub relations: RelationSet,
+    /// Estimated output rows, the same for all the expressions of the group
+    pub rows: f64,
+    pub exprs: Vec<GroupExpr>,
+    /// The index of the cheapest expression and its cost
+    pub winner: Option<(usize, f64)>,
+}
+
+/// Groups of the explored join orders, each set of inputs is explored only once.
+#[derive(Debug)]
+pub struct Memo {
+    groups: Vec<Group>,
+    group_ids: HashMap<RelationSet, GroupId>,
+}
+
+impl Memo {
+    pub fn new() -> Self {
+        Memo {
+            groups: vec![],
+            group_ids: HashMap::new(),
+        }
+    }
+
+    pub fn group_id(&self, relations: RelationSet) -> Option<GroupId> {
+        self.group_ids.get(&relations).copied()
+    }
+
+    pub fn group(&self, group_id: GroupId) -> &Group {
+        &self.groups[group_id]
+    }
+
+    pub fn add_group(&mut self, relations: RelationSet, rows: f64) -> GroupId {
+        let group_id = self.groups.len();
+
+        self.groups.push(Group {
+            relations,
+            rows,
+            exprs: vec![],
+            winner: None,
+        });
+        self.group_ids.insert(relations, group_id);
+
+        group_id
+    }
+
+    /// Add an expression to the group, which becomes the winner if it is the cheapest so far.
+    pub fn add_expr(&mut self, group_id: GroupId, expr: GroupExpr, cost: f64) {
+        let group = &mut self.groups[group_id];
+
+        group.exprs.push(expr);
+        if group.winner.map_or(true, |(_, best_cost)| cost < best_cost) {
+            group.winner = Some((group.exprs.len() - 1, cost));
+        }
+    }
+
+    /// The cheapest expression of the group and its cost.
+    pub fn winner(&self, group_id: GroupId) -> (GroupExpr, f64) {
+        let group = &self.groups[group_id];
+        let (index, cost) = group.winner.expect("the group has not been explored");
+
+        (group.exprs[index], cost)
+    }
+}
@@ -0,0 +1,3 @@
+pub mod cost;
+pub mod memo;
+pub mod optimizer;
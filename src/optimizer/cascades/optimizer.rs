@@ -0,0 +1,573 @@
+use std::mem;
+use ahash::HashMap;
+use itertools::Itertools;
+use crate::catalog::{ColumnRef, TableName, TableStatistics};
+use crate::expression::{BinaryOperator, ScalarExpression};
+use crate::optimizer::cascades::cost::CostModel;
+use crate::optimizer::cascades::memo::{GroupExpr, GroupId, Memo, RelationSet};
+use crate::planner::LogicalPlan;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::planner::operator::Operator;
+use crate::planner::operator::project::ProjectOperator;
+use crate::types::LogicalType;
+
+/// Inner joins of at most this many inputs are reordered, the search is exponential in it.
+const MAX_JOIN_INPUTS: usize = 10;
+
+/// Cost-based optimizer which runs after the heuristic rewrites:
+/// - the inputs of adjacent inner joins are reordered by a top-down search over a `Memo`,
+///   in the style of Volcano/Cascades, and cross products are avoided when possible;
+/// - the smaller input of a join becomes its left side, the build side of `HashJoin`.
+///
+/// The plan is only changed when the estimated cost is lower than the one of the plan as written.
+pub struct CascadesOptimizer {
+    cost_model: CostModel,
+    fired_rules: Vec<String>,
+}
+
+impl CascadesOptimizer {
+    /// `table_rows` and `statistics`: what is known of the tables, e.g. after `ANALYZE`.
+    pub fn new(table_rows: HashMap<TableName, usize>, statistics: HashMap<TableName, TableStatistics>) -> Self {
+        CascadesOptimizer {
+            cost_model: CostModel::new(table_rows, statistics),
+            fired_rules: vec![],
+        }
+    }
+
+    pub fn find_best(&mut self, plan: LogicalPlan) -> LogicalPlan {
+        self.optimize_plan(plan)
+    }
+
+    /// The transformations that changed the plan, in the order they first fired.
+    pub fn fired_rules(&self) -> Vec<String> {
+        self.fired_rules.clone()
+    }
+
+    fn fire(&mut self, rule: &str) {
+        if !self.fired_rules.iter().any(|fired| fired == rule) {
+            self.fired_rules.push(rule.to_string());
+        }
+    }
+
+    fn optimize_plan(&mut self, plan: LogicalPlan) -> LogicalPlan {
+        if is_inner_join(&plan) {
+            return self.reorder_joins(plan);
+        }
+        let LogicalPlan { operator, childrens } = plan;
+        let plan = LogicalPlan {
+            operator,
+            childrens: childrens
+                .into_iter()
+                .map(|child| self.optimize_plan(child))
+                .collect_vec(),
+        };
+
+        self.commute_outer_join(plan)
+    }
+
+    fn reorder_joins(&mut self, plan: LogicalPlan) -> LogicalPlan {
+        let mut inputs = Vec::new();
+        let mut conditions = Vec::new();
+
+        collect_join_inputs(&plan, &mut inputs, &mut conditions);
+        let inputs = inputs
+            .into_iter()
+            .map(|input| self.optimize_plan(input.clone()))
+            .collect_vec();
+
+        let reordered = (inputs.len() <= MAX_JOIN_INPUTS)
+            .then(|| JoinSearch::new(&self.cost_model, &inputs, conditions))
+            .flatten()
+            .and_then(|mut search| search.find_best(&plan, &inputs));
+
+        match reordered {
+            // the inputs of the reordered joins output their columns in another order
+            Some(reordered) => {
+                self.fire("JoinReorder");
+                project_columns(plan.output_columns(), reordered)
+            }
+            None => replace_join_inputs(plan, &mut inputs.into_iter()),
+        }
+    }
+
+    /// Put the smaller input of an outer join on the build side, e.g. `a LEFT JOIN b` into
+    /// `b RIGHT JOIN a`, under a projection of the columns in the order of the join as written.
+    /// Only the joins of the tables with known row counts are commuted.
+    fn commute_outer_join(&mut self, mut plan: LogicalPlan) -> LogicalPlan {
+        let Operator::Join(op) = &mut plan.operator else {
+            return plan;
+        };
+        let join_type = match op.join_type {
+            JoinType::Left => JoinType::Right,
+            JoinType::Right => JoinType::Left,
+            JoinType::Full => JoinType::Full,
+            _ => return plan,
+        };
+        if !op.has_equi_keys()
+            || !plan.childrens.iter().all(|child| self.cost_model.has_row_counts(child))
+            || self.cost_model.estimate_rows(&plan.childrens[1]) >= self.cost_model.estimate_rows(&plan.childrens[0]) {
+            return plan;
+        }
+        let columns = plan.output_columns();
+        let Operator::Join(op) = &mut plan.operator else {
+            unreachable!()
+        };
+        op.join_type = join_type;
+        if let JoinCondition::On { on, .. } = &mut op.on {
+            for (left_expr, right_expr) in on.iter_mut() {
+                mem::swap(left_expr, right_expr);
+            }
+        }
+        plan.childrens.swap(0, 1);
+        self.fire("JoinCommute");
+
+        project_columns(columns, plan)
+    }
+}
+
+/// Project the output of `plan` to `columns`, e.g. to the column order of a join before it is
+/// rewritten.
+fn project_columns(columns: Vec<ColumnRef>, plan: LogicalPlan) -> LogicalPlan {
+    LogicalPlan {
+        operator: Operator::Project(ProjectOperator {
+            columns: columns
+                .into_iter()
+                .map(ScalarExpression::ColumnRef)
+                .collect_vec(),
+        }),
+        childrens: vec![plan],
+    }
+}
+
+fn is_inner_join(plan: &LogicalPlan) -> bool {
+    matches!(
+        plan.operator,
+        Operator::Join(JoinOperator { join_type: JoinType::Inner | JoinType::Cross, .. })
+    )
+}
+
+/// Collect the inputs and the conditions of the adjacent inner joins, from left to right.
+fn collect_join_inputs<'a>(plan: &'a LogicalPlan, inputs: &mut Vec<&'a LogicalPlan>, conditions: &mut Vec<JoinCondition>) {
+    match &plan.operator {
+        Operator::Join(op) if is_inner_join(plan) => {
+            conditions.push(op.on.clone());
+
+            for child in plan.childrens.iter() {
+                collect_join_inputs(child, inputs, conditions);
+            }
+        }
+        _ => inputs.push(plan),
+    }
+}
+
+/// Replace the inputs of the adjacent inner joins in the order of `collect_join_inputs`.
+fn replace_join_inputs(plan: LogicalPlan, inputs: &mut impl Iterator<Item = LogicalPlan>) -> LogicalPlan {
+    if !is_inner_join(&plan) {
+        return inputs.next().expect("the inputs of the joins are collected from the plan");
+    }
+    let LogicalPlan { operator, childrens } = plan;
+
+    LogicalPlan {
+        operator,
+        childrens: childrens
+            .into_iter()
+            .map(|child| replace_join_inputs(child, inputs))
+            .collect_vec(),
+    }
+}
+
+/// All the columns referenced in the plan, a superset of its output columns.
+fn plan_columns(plan: &LogicalPlan) -> Vec<ColumnRef> {
+    plan.operator
+        .referenced_columns()
+        .into_iter()
+        .chain(plan.childrens.iter().flat_map(plan_columns))
+        .collect_vec()
+}
+
+fn split_conjunctions(expr: ScalarExpression, conjunctions: &mut Vec<ScalarExpression>) {
+    match expr {
+        ScalarExpression::Binary { op: BinaryOperator::And, left_expr, right_expr, .. } => {
+            split_conjunctions(*left_expr, conjunctions);
+            split_conjunctions(*right_expr, conjunctions);
+        }
+        expr => conjunctions.push(expr),
+    }
+}
+
+fn is_subset(relations: RelationSet, other: RelationSet) -> bool {
+    relations & !other == 0
+}
+
+/// A condition of the reordered joins, with the inputs it references.
+enum JoinPredicate {
+    /// `left = right`, an equi-join key when the sides reference different join inputs
+    Equi {
+        left: ScalarExpression,
+        right: ScalarExpression,
+        left_relations: RelationSet,
+        right_relations: RelationSet,
+    },
+    Filter {
+        expr: ScalarExpression,
+        relations: RelationSet,
+    },
+}
+
+impl JoinPredicate {
+    /// The predicates without columns, e.g. `ON true`, are evaluated with the first input.
+    fn relations(&self) -> RelationSet {
+        let relations = match self {
+            JoinPredicate::Equi { left_relations, right_relations, .. } => left_relations | right_relations,
+            JoinPredicate::Filter { relations, .. } => *relations,
+        };
+
+        relations.max(1)
+    }
+
+    /// Whether the predicate is evaluated by the join of `left` and `right`: the join covers
+    /// its inputs, and neither side could evaluate it, a single input is never filtered here.
+    fn is_applied_at(&self, left: RelationSet, right: RelationSet) -> bool {
+        let relations = self.relations();
+
+        is_subset(relations, left | right)
+            && !(is_subset(relations, left) && left.count_ones() > 1)
+            && !(is_subset(relations, right) && right.count_ones() > 1)
+    }
+}
+
+/// Searches the cheapest order of the inputs of adjacent inner joins.
+struct JoinSearch<'a> {
+    cost_model: &'a CostModel,
+    input_rows: Vec<f64>,
+    predicates: Vec<JoinPredicate>,
+    memo: Memo,
+}
+
+impl<'a> JoinSearch<'a> {
+    /// Returns `None` if a column of the conditions can not be traced to exactly one input,
+    /// e.g. the columns of a derived table.
+    fn new(cost_model: &'a CostModel, inputs: &[LogicalPlan], conditions: Vec<JoinCondition>) -> Option<Self> {
+        let input_columns = inputs.iter().map(plan_columns).collect_vec();
+        let relations_of = |expr: &ScalarExpression| -> Option<RelationSet> {
+            expr.referenced_columns()
+                .iter()
+                .try_fold(0, |relations, col| {
+                    let mut positions = input_columns
+                        .iter()
+                        .positions(|columns| columns
+                            .iter()
+                            .any(|input_col| input_col.is_same_column(col)));
+
+                    match (positions.next(), positions.next()) {
+                        (Some(index), None) => Some(relations | 1 << index),
+                        _ => None,
+                    }
+                })
+        };
+
+        let mut predicates = Vec::new();
+        for condition in conditions {
+            let JoinCondition::On { on, filter } = condition else {
+                continue;
+            };
+            for (left, right) in on {
+                predicates.push(JoinPredicate::Equi {
+                    left_relations: relations_of(&left)?,
+                    right_relations: relations_of(&right)?,
+                    left,
+                    right,
+                });
+            }
+            let mut conjunctions = Vec::new();
+            if let Some(expr) = filter {
+                split_conjunctions(expr, &mut conjunctions);
+            }
+            // `a = b` of a filter may also be a key of the reordered joins
+            for expr in conjunctions {
+                let predicate = match expr {
+                    ScalarExpression::Binary { op: BinaryOperator::Eq, left_expr, right_expr, .. } => JoinPredicate::Equi {
+                        left_relations: relations_of(&left_expr)?,
+                        right_relations: relations_of(&right_expr)?,
+                        left: *left_expr,
+                        right: *right_expr,
+                    },
+                    expr => JoinPredicate::Filter {
+                        relations: relations_of(&expr)?,
+                        expr,
+                    },
+                };
+                predicates.push(predicate);
+            }
+        }
+
+        Some(JoinSearch {
+            cost_model,
+            input_rows: inputs.iter().map(|input| cost_model.estimate_rows(input)).collect_vec(),
+            predicates,
+            memo: Memo::new(),
+        })
+    }
+
+    /// The cheapest join tree, if it is cheaper than `original`.
+    fn find_best(&mut self, original: &LogicalPlan, inputs: &[LogicalPlan]) -> Option<LogicalPlan> {
+        let all_relations: RelationSet = (1 << inputs.len()) - 1;
+        let root_id = self.explore_group(all_relations);
+        let (_, best_cost) = self.memo.winner(root_id);
+        let (_, original_cost) = self.tree_cost(original, &mut 0);
+
+        // the same tree may cost slightly different because of the order of the additions
+        (best_cost < original_cost * (1.0 - f64::EPSILON.sqrt()))
+            .then(|| self.build_plan(root_id, inputs))
+    }
+
+    fn inputs_of(&self, relations: RelationSet) -> impl Iterator<Item = usize> {
+        (0..self.input_rows.len()).filter(move |index| relations & (1 << index) != 0)
+    }
+
+    /// Estimated rows of joining the inputs, the selectivities of the predicates are independent.
+    fn rows(&self, relations: RelationSet) -> f64 {
+        let input_rows: f64 = self.inputs_of(relations)
+            .map(|index| self.input_rows[index])
+            .product();
+        let selectivity: f64 = self.predicates
+            .iter()
+            .filter(|predicate| is_subset(predicate.relations(), relations))
+            .map(|predicate| match predicate {
+                JoinPredicate::Equi { left, right, .. } => {
+                    let default_ndv = self.inputs_of(predicate.relations())
+                        .map(|index| self.input_rows[index])
+                        .fold(1.0, f64::max);
+
+                    self.cost_model.equi_selectivity(left, right, default_ndv)
+                }
+                JoinPredicate::Filter { expr, .. } => self.cost_model.selectivity(expr),
+            })
+            .product();
+
+        (input_rows * selectivity).max(1.0)
+    }
+
+    fn is_connected(&self, left: RelationSet, right: RelationSet) -> bool {
+        self.predicates
+            .iter()
+            .map(JoinPredicate::relations)
+            .any(|relations| is_subset(relations, left | right) && relations & left != 0 && relations & right != 0)
+    }
+
+    /// The condition of the join of `left` and `right`, equi-join keys are oriented to the sides.
+    fn join_condition(&self, left: RelationSet, right: RelationSet) -> JoinCondition {
+        let mut on = Vec::new();
+        let mut filters = Vec::new();
+
+        for predicate in self.predicates.iter().filter(|predicate| predicate.is_applied_at(left, right)) {
+            match predicate {
+                JoinPredicate::Equi { left: left_expr, right: right_expr, left_relations, right_relations } => {
+                    let is_key = *left_relations != 0 && *right_relations != 0;
+
+                    if is_key && is_subset(*left_relations, left) && is_subset(*right_relations, right) {
+                        on.push((left_expr.clone(), right_expr.clone()));
+                    } else if is_key && is_subset(*left_relations, right) && is_subset(*right_relations, left) {
+                        on.push((right_expr.clone(), left_expr.clone()));
+                    } else {
+                        filters.push(ScalarExpression::Binary {
+                            op: BinaryOperator::Eq,
+                            left_expr: Box::new(left_expr.clone()),
+                            right_expr: Box::new(right_expr.clone()),
+                            ty: LogicalType::Boolean,
+                        });
+                    }
+                }
+                JoinPredicate::Filter { expr, .. } => filters.push(expr.clone()),
+            }
+        }
+        let filter = filters
+            .into_iter()
+            .reduce(|left_expr, right_expr| ScalarExpression::Binary {
+                op: BinaryOperator::And,
+                left_expr: Box::new(left_expr),
+                right_expr: Box::new(right_expr),
+                ty: LogicalType::Boolean,
+            });
+
+        if on.is_empty() && filter.is_none() {
+            JoinCondition::None
+        } else {
+            JoinCondition::On { on, filter }
+        }
+    }
+
+    fn join_cost(&self, left: RelationSet, right: RelationSet) -> f64 {
+        let has_equi_keys = matches!(
+            self.join_condition(left, right),
+            JoinCondition::On { on, .. } if !on.is_empty()
+        );
+
+        self.cost_model.join_cost(has_equi_keys, self.rows(left), self.rows(right), self.rows(left | right))
+    }
+
+    /// Explore all the ways to join the inputs, only the partitions connected by a predicate
+    /// are joined unless there is none.
+    fn explore_group(&mut self, relations: RelationSet) -> GroupId {
+        if let Some(group_id) = self.memo.group_id(relations) {
+            return group_id;
+        }
+        let group_id = self.memo.add_group(relations, self.rows(relations));
+
+        if relations.count_ones() == 1 {
+            self.memo.add_expr(group_id, GroupExpr::Input(relations.trailing_zeros() as usize), 0.0);
+            return group_id;
+        }
+        let mut partitions = Vec::new();
+        let mut left = (relations - 1) & relations;
+        while left > 0 {
+            partitions.push((left, relations & !left));
+            left = (left - 1) & relations;
+        }
+        let connected = partitions
+            .iter()
+            .filter(|(left, right)| self.is_connected(*left, *right))
+            .copied()
+            .collect_vec();
+        if !connected.is_empty() {
+            partitions = connected;
+        }
+
+        for (left, right) in partitions {
+            let left_id = self.explore_group(left);
+            let right_id = self.explore_group(right);
+            let cost = self.memo.winner(left_id).1
+                + self.memo.winner(right_id).1
+                + self.join_cost(left, right);
+
+            self.memo.add_expr(group_id, GroupExpr::Join { left: left_id, right: right_id }, cost);
+        }
+
+        group_id
+    }
+
+    /// The relations and the cost of the join tree as written, the inputs are numbered in the
+    /// order of `collect_join_inputs`.
+    fn tree_cost(&self, plan: &LogicalPlan, next_input: &mut usize) -> (RelationSet, f64) {
+        if !is_inner_join(plan) {
+            *next_input += 1;
+
+            return (1 << (*next_input - 1), 0.0);
+        }
+        let (left, left_cost) = self.tree_cost(&plan.childrens[0], next_input);
+        let (right, right_cost) = self.tree_cost(&plan.childrens[1], next_input);
+
+        (left | right, left_cost + right_cost + self.join_cost(left, right))
+    }
+
+    fn build_plan(&self, group_id: GroupId, inputs: &[LogicalPlan]) -> LogicalPlan {
+        match self.memo.winner(group_id).0 {
+            GroupExpr::Input(index) => inputs[index].clone(),
+            GroupExpr::Join { left, right } => {
+                let on = self.join_condition(self.memo.group(left).relations, self.memo.group(right).relations);
+                let join_type = if matches!(on, JoinCondition::None) {
+                    JoinType::Cross
+                } else {
+                    JoinType::Inner
+                };
+
+                JoinOperator::new(
+                    self.build_plan(left, inputs),
+                    self.build_plan(right, inputs),
+                    on,
+                    join_type,
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+    use ahash::{HashMap, HashMapExt};
+    use crate::binder::test::select_sql_run;
+    use crate::db::DatabaseError;
+    use crate::optimizer::cascades::optimizer::CascadesOptimizer;
+    use crate::planner::LogicalPlan;
+    use crate::planner::operator::join::JoinType;
+    use crate::planner::operator::Operator;
+
+    fn join_tables(plan: &LogicalPlan, tables: &mut Vec<String>) {
+        match &plan.operator {
+            Operator::Scan(op) => tables.push(op.table_name.to_string()),
+            _ => plan.childrens.iter().for_each(|child| join_tables(child, tables)),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_reorder_inner_joins() -> Result<(), DatabaseError> {
+        let plan = select_sql_run("select * from t1 join t2 on t1.c1 = t2.c4").await?;
+        let join_columns = plan.childrens[0].output_columns();
+        let mut table_rows = HashMap::new();
+        let _ = table_rows.insert(Arc::new("t1".to_string()), 100000);
+        let _ = table_rows.insert(Arc::new("t2".to_string()), 10);
+
+        let mut optimizer = CascadesOptimizer::new(table_rows, HashMap::new());
+        let best_plan = optimizer.find_best(plan);
+        let mut tables = Vec::new();
+        join_tables(&best_plan, &mut tables);
+
+        // the smaller input is built into the hash table
+        assert_eq!(tables, vec!["t2", "t1"]);
+        assert_eq!(optimizer.fired_rules(), vec!["JoinReorder".to_string()]);
+        // the columns keep the order of the join as written
+        let reordered = &best_plan.childrens[0];
+        assert!(matches!(&reordered.operator, Operator::Project(_)));
+        assert_eq!(reordered.output_columns(), join_columns);
+
+        // without statistics the plan as written is kept
+        let plan = select_sql_run("select * from t1 join t2 on t1.c1 = t2.c4").await?;
+        let mut optimizer = CascadesOptimizer::new(HashMap::new(), HashMap::new());
+        let _ = optimizer.find_best(plan);
+
+        assert!(optimizer.fired_rules().is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_commute_outer_join() -> Result<(), DatabaseError> {
+        let plan = select_sql_run("select * from t1 left join t2 on t1.c1 = t2.c3").await?;
+        let mut table_rows = HashMap::new();
+        let _ = table_rows.insert(Arc::new("t1".to_string()), 1000);
+        let _ = table_rows.insert(Arc::new("t2".to_string()), 10);
+
+        let mut optimizer = CascadesOptimizer::new(table_rows, HashMap::new());
+        let best_plan = optimizer.find_best(plan);
+        let mut tables = Vec::new();
+        join_tables(&best_plan, &mut tables);
+
+        assert_eq!(tables, vec!["t2", "t1"]);
+        let reordered = &best_plan.childrens[0];
+        assert!(matches!(&reordered.operator, Operator::Project(op) if op.columns.len() == best_plan.output_columns().len()));
+        let join_type = reordered.childrens[0].operator.clone();
+        assert!(matches!(join_type, Operator::Join(op) if op.join_type == JoinType::Right));
+
+        // the larger input stays on the probe side
+        let plan = select_sql_run("select * from t2 left join t1 on t1.c1 = t2.c3").await?;
+        let mut table_rows = HashMap::new();
+        let _ = table_rows.insert(Arc::new("t1".to_string()), 1000);
+        let _ = table_rows.insert(Arc::new("t2".to_string()), 10);
+        let mut optimizer = CascadesOptimizer::new(table_rows, HashMap::new());
+        let _ = optimizer.find_best(plan);
+
+        assert!(optimizer.fired_rules().is_empty());
+
+        // without the row count of t1 the estimates are only the defaults
+        let plan = select_sql_run("select * from t1 left join t2 on t1.c1 = t2.c3").await?;
+        let mut table_rows = HashMap::new();
+        let _ = table_rows.insert(Arc::new("t2".to_string()), 10);
+        let mut optimizer = CascadesOptimizer::new(table_rows, HashMap::new());
+        let _ = optimizer.find_best(plan);
+
+        assert!(optimizer.fired_rules().is_empty());
+
+        Ok(())
+    }
+}
@@ -3,6 +3,7 @@ use crate::optimizer::core::rule::Rule;
 use crate::optimizer::heuristic::batch::{HepBatch, HepBatchStrategy};
 use crate::optimizer::heuristic::graph::{HepGraph, HepNodeId};
 use crate::optimizer::heuristic::matcher::HepMatcher;
+use crate::optimizer::OptimizerTrace;
 use crate::optimizer::rule::RuleImpl;
 use crate::planner::LogicalPlan;
 
@@ -25,30 +26,52 @@ impl HepOptimizer {
     }
 
     pub fn find_best(&mut self) -> LogicalPlan {
+        self.optimize(false).0
+    }
+
+    /// Like `find_best`, but also returns the plan after each batch with the rules that fired.
+    pub fn find_best_with_trace(&mut self) -> (LogicalPlan, Vec<OptimizerTrace>) {
+        self.optimize(true)
+    }
+
+    fn optimize(&mut self, trace: bool) -> (LogicalPlan, Vec<OptimizerTrace>) {
         let batches = self.batches.clone();
+        let mut traces = Vec::new();
 
         for batch in batches {
             let mut batch_over = false;
             let mut iteration = 1usize;
+            let mut fired_rules = Vec::new();
 
             while iteration <= batch.strategy.max_iteration && !batch_over {
-                if self.apply_batch(&batch) {
+                if self.apply_batch(&batch, &mut fired_rules) {
                     iteration += 1;
                 } else {
                     batch_over = true
                 }
             }
+
+            if trace {
+                traces.push(OptimizerTrace {
+                    name: batch.name,
+                    fired_rules: fired_rules.iter().map(|rule| format!("{:?}", rule)).collect(),
+                    plan: self.graph.to_plan(),
+                });
+            }
         }
 
-        self.graph.to_plan()
+        (self.graph.to_plan(), traces)
     }
 
-    fn apply_batch(&mut self, HepBatch{ rules, strategy, .. }: &HepBatch) -> bool {
+    fn apply_batch(&mut self, HepBatch{ rules, strategy, .. }: &HepBatch, fired_rules: &mut Vec<RuleImpl>) -> bool {
         let start_ver = self.graph.version;
 
         for rule in rules {
             for node_id in self.graph.nodes_iter(strategy.match_order, None) {
                 if self.apply_rule(rule, node_id) {
+                    if !fired_rules.contains(rule) {
+                        fired_rules.push(*rule);
+                    }
                     break;
                 }
             }
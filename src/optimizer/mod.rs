@@ -1,6 +1,18 @@
 /// The architecture and some components,
 /// such as (/core) are referenced from sqlrs
 
+use crate::planner::LogicalPlan;
+
 mod core;
+pub mod cascades;
 pub mod heuristic;
-pub mod rule;
\ No newline at end of file
+pub mod rule;
+
+/// The plan after an optimizer step, e.g. a `HepBatch`, recorded for `EXPLAIN VERBOSE`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct OptimizerTrace {
+    pub name: String,
+    /// Rules that changed the plan, in the order they first fired
+    pub fired_rules: Vec<String>,
+    pub plan: LogicalPlan,
+}
\ No newline at end of file
@@ -6,6 +6,7 @@ use crate::optimizer::core::opt_expr::OptExprNode;
 use crate::optimizer::core::pattern::{Pattern, PatternChildrenPredicate};
 use crate::optimizer::core::rule::Rule;
 use crate::optimizer::heuristic::graph::{HepGraph, HepNodeId};
+use crate::optimizer::rule::subtree_columns;
 use crate::planner::operator::Operator;
 use crate::planner::operator::project::ProjectOperator;
 use crate::types::ColumnId;
@@ -47,13 +48,16 @@ impl Rule for PushProjectIntoScan {
         if let Operator::Project(project_op) = graph.operator(node_id) {
             let child_index = graph.children_at(node_id)[0];
             if let Operator::Scan(scan_op) = graph.operator(child_index) {
+                // computed columns would be lost, e.g. `SELECT v + 1 AS total FROM t`
+                if !project_op.columns
+                    .iter()
+                    .all(|expr| matches!(expr.unpack_alias(), ScalarExpression::ColumnRef(_)))
+                {
+                    return;
+                }
                 let mut new_scan_op = scan_op.clone();
 
-                new_scan_op.columns = project_op.columns
-                    .iter()
-                    .filter(|expr| matches!(expr.unpack_alias(),ScalarExpression::ColumnRef(_)))
-                    .cloned()
-                    .collect_vec();
+                new_scan_op.columns = project_op.columns.clone();
 
                 graph.remove_node(node_id, false);
                 graph.replace_node(
@@ -109,10 +113,10 @@ impl Rule for PushProjectThroughChild {
             }
 
             for grandson_id in graph.children_at(child_index) {
-                let mut columns = graph.operator(grandson_id)
-                    .referenced_columns()
+                // Tips: a grandson like `Filter` outputs more columns than it references
+                let mut columns = subtree_columns(graph, grandson_id)
                     .into_iter()
-                    .unique_by(|col| col.id)
+                    .unique_by(|col| (col.id, col.binding.clone()))
                     .filter(|u| intersection_columns_ids.contains(&u.id))
                     .map(|col| ScalarExpression::ColumnRef(col))
                     .collect_vec();
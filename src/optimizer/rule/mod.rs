@@ -1,18 +1,25 @@
+use itertools::Itertools;
+use crate::catalog::ColumnRef;
 use crate::expression::ScalarExpression;
 use crate::optimizer::core::pattern::Pattern;
 use crate::optimizer::core::rule::Rule;
 use crate::optimizer::heuristic::graph::{HepGraph, HepNodeId};
 use crate::optimizer::rule::column_pruning::{PushProjectIntoScan, PushProjectThroughChild};
 use crate::optimizer::rule::combine_operators::{CollapseProject, CombineFilter};
-use crate::optimizer::rule::pushdown_limit::{LimitProjectTranspose, EliminateLimits, PushLimitThroughJoin, PushLimitIntoScan};
-use crate::optimizer::rule::pushdown_predicates::PushPredicateThroughJoin;
+use crate::optimizer::rule::pushdown_limit::{LimitProjectTranspose, EliminateLimits, PushLimitThroughJoin, PushLimitIntoScan, PushLimitIntoSort};
+use crate::optimizer::rule::simplification::{ConstantFolding, PruneFilters};
+use crate::optimizer::rule::simplify_join::{EliminateLeftJoin, SimplifyOuterJoin};
+use crate::optimizer::rule::pushdown_predicates::{PushPredicateIntoScan, PushPredicateThroughJoin, PushPredicateThroughNonJoin};
+use crate::planner::operator::Operator;
 
 mod column_pruning;
 mod combine_operators;
 mod pushdown_limit;
 mod pushdown_predicates;
+mod simplification;
+mod simplify_join;
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum RuleImpl {
     // Column pruning
     PushProjectIntoScan,
@@ -25,8 +32,17 @@ pub enum RuleImpl {
     EliminateLimits,
     PushLimitThroughJoin,
     PushLimitIntoTableScan,
+    PushLimitIntoSort,
     // PushDown predicates
-    PushPredicateThroughJoin
+    PushPredicateThroughJoin,
+    PushPredicateThroughNonJoin,
+    PushPredicateIntoScan,
+    // Simplification
+    ConstantFolding,
+    PruneFilters,
+    // Join simplification
+    SimplifyOuterJoin,
+    EliminateLeftJoin,
 }
 
 impl Rule for RuleImpl {
@@ -40,7 +56,14 @@ impl Rule for RuleImpl {
             RuleImpl::EliminateLimits => EliminateLimits {}.pattern(),
             RuleImpl::PushLimitThroughJoin => PushLimitThroughJoin {}.pattern(),
             RuleImpl::PushLimitIntoTableScan => PushLimitIntoScan {}.pattern(),
+            RuleImpl::PushLimitIntoSort => PushLimitIntoSort {}.pattern(),
             RuleImpl::PushPredicateThroughJoin => PushPredicateThroughJoin {}.pattern(),
+            RuleImpl::PushPredicateThroughNonJoin => PushPredicateThroughNonJoin {}.pattern(),
+            RuleImpl::PushPredicateIntoScan => PushPredicateIntoScan {}.pattern(),
+            RuleImpl::ConstantFolding => ConstantFolding {}.pattern(),
+            RuleImpl::PruneFilters => PruneFilters {}.pattern(),
+            RuleImpl::SimplifyOuterJoin => SimplifyOuterJoin {}.pattern(),
+            RuleImpl::EliminateLeftJoin => EliminateLeftJoin {}.pattern(),
         }
     }
 
@@ -54,7 +77,14 @@ impl Rule for RuleImpl {
             RuleImpl::EliminateLimits => EliminateLimits {}.apply(node_id, graph),
             RuleImpl::PushLimitThroughJoin => PushLimitThroughJoin {}.apply(node_id, graph),
             RuleImpl::PushLimitIntoTableScan => PushLimitIntoScan {}.apply(node_id, graph),
+            RuleImpl::PushLimitIntoSort => PushLimitIntoSort {}.apply(node_id, graph),
             RuleImpl::PushPredicateThroughJoin => PushPredicateThroughJoin {}.apply(node_id, graph),
+            RuleImpl::PushPredicateThroughNonJoin => PushPredicateThroughNonJoin {}.apply(node_id, graph),
+            RuleImpl::PushPredicateIntoScan => PushPredicateIntoScan {}.apply(node_id, graph),
+            RuleImpl::ConstantFolding => ConstantFolding {}.apply(node_id, graph),
+            RuleImpl::PruneFilters => PruneFilters {}.apply(node_id, graph),
+            RuleImpl::SimplifyOuterJoin => SimplifyOuterJoin {}.apply(node_id, graph),
+            RuleImpl::EliminateLeftJoin => EliminateLeftJoin {}.apply(node_id, graph),
         }
     }
 }
@@ -62,4 +92,18 @@ impl Rule for RuleImpl {
 /// Return true when left is subset of right
 pub fn is_subset_exprs(left: &[ScalarExpression], right: &[ScalarExpression]) -> bool {
     left.iter().all(|l| right.contains(l))
-}
\ No newline at end of file
+}
+
+/// Collect the columns that the subtree rooted at `node_id` can provide to its parent.
+pub(crate) fn subtree_columns(graph: &HepGraph, node_id: HepNodeId) -> Vec<ColumnRef> {
+    match graph.operator(node_id) {
+        Operator::Scan(_) | Operator::Project(_) | Operator::Aggregate(_) => {
+            graph.operator(node_id).referenced_columns()
+        }
+        _ => graph
+            .children_at(node_id)
+            .into_iter()
+            .flat_map(|child_id| subtree_columns(graph, child_id))
+            .collect_vec(),
+    }
+}
@@ -36,6 +36,15 @@ lazy_static! {
             }]),
         }
     };
+    static ref PUSH_LIMIT_INTO_SORT_RULE: Pattern = {
+        Pattern {
+            predicate: |op| matches!(op, Operator::Limit(_)),
+            children: PatternChildrenPredicate::Predicate(vec![Pattern {
+                predicate: |op| matches!(op, Operator::Sort(_)),
+                children: PatternChildrenPredicate::None,
+            }]),
+        }
+    };
     static ref PUSH_LIMIT_INTO_TABLE_SCAN_RULE: Pattern = {
         Pattern {
             predicate: |op| matches!(op, Operator::Limit(_)),
@@ -157,6 +166,39 @@ impl Rule for PushLimitIntoScan {
     }
 }
 
+/// Push down `Limit` into a `Sort`, which only keeps the first `offset + limit` tuples.
+/// The `Limit` is kept to skip the offset.
+pub struct PushLimitIntoSort;
+
+impl Rule for PushLimitIntoSort {
+    fn pattern(&self) -> &Pattern {
+        &PUSH_LIMIT_INTO_SORT_RULE
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        if let Operator::Limit(limit_op) = graph.operator(node_id) {
+            let child_index = graph.children_at(node_id)[0];
+            if let Operator::Sort(sort_op) = graph.operator(child_index) {
+                let limit = limit_op.offset.saturating_add(limit_op.limit);
+                let has_offset = limit_op.offset > 0;
+                let mut new_sort_op = sort_op.clone();
+
+                new_sort_op.limit = Some(sort_op.limit.map_or(limit, |sort_limit| cmp::min(sort_limit, limit)));
+
+                if new_sort_op.limit != sort_op.limit {
+                    graph.replace_node(
+                        child_index,
+                        OptExprNode::OperatorRef(Operator::Sort(new_sort_op))
+                    );
+                }
+                if !has_offset {
+                    graph.remove_node(node_id, false);
+                }
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::binder::test::select_sql_run;
@@ -283,4 +325,49 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_push_limit_into_sort() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select c1, c2 from t1 order by c2 limit 2 offset 1").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_push_limit_into_sort".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![
+                    RuleImpl::LimitProjectTranspose,
+                    RuleImpl::PushLimitIntoSort
+                ]
+            )
+            .find_best();
+
+        // the offset is skipped by the limit
+        assert!(matches!(best_plan.childrens[0].operator, Operator::Limit(_)));
+        if let Operator::Sort(op) = &best_plan.childrens[0].childrens[0].operator {
+            assert_eq!(op.limit, Some(3))
+        } else {
+            unreachable!("Should be a sort operator")
+        }
+
+        let plan = select_sql_run("select c1, c2 from t1 order by c2 limit 2").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_push_limit_into_sort".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![
+                    RuleImpl::LimitProjectTranspose,
+                    RuleImpl::PushLimitIntoSort
+                ]
+            )
+            .find_best();
+
+        if let Operator::Sort(op) = &best_plan.childrens[0].operator {
+            assert_eq!(op.limit, Some(2))
+        } else {
+            unreachable!("Should be a sort operator")
+        }
+
+        Ok(())
+    }
 }
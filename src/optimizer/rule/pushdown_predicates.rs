@@ -6,9 +6,10 @@ use crate::optimizer::core::opt_expr::OptExprNode;
 use crate::optimizer::core::pattern::Pattern;
 use crate::optimizer::core::rule::Rule;
 use crate::optimizer::heuristic::graph::{HepGraph, HepNodeId};
+use crate::optimizer::rule::subtree_columns;
 use crate::optimizer::core::pattern::PatternChildrenPredicate;
 use crate::planner::operator::filter::FilterOperator;
-use crate::planner::operator::join::JoinType;
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
 use crate::planner::operator::Operator;
 use crate::types::LogicalType;
 
@@ -23,19 +24,28 @@ lazy_static! {
         }
     };
 
-    // TODO
     static ref PUSH_PREDICATE_THROUGH_NON_JOIN: Pattern = {
         Pattern {
             predicate: |op| matches!(op, Operator::Filter(_)),
             children: PatternChildrenPredicate::Predicate(vec![Pattern {
-                predicate: |op| matches!(op, Operator::Project(_)),
+                predicate: |op| matches!(op, Operator::Project(_) | Operator::Aggregate(_) | Operator::Sort(_)),
+                children: PatternChildrenPredicate::None,
+            }]),
+        }
+    };
+
+    static ref PUSH_PREDICATE_INTO_SCAN: Pattern = {
+        Pattern {
+            predicate: |op| matches!(op, Operator::Filter(_)),
+            children: PatternChildrenPredicate::Predicate(vec![Pattern {
+                predicate: |op| matches!(op, Operator::Scan(_)),
                 children: PatternChildrenPredicate::None,
             }]),
         }
     };
 }
 
-fn split_conjunctive_predicates(expr: &ScalarExpression) -> Vec<ScalarExpression> {
+pub(crate) fn split_conjunctive_predicates(expr: &ScalarExpression) -> Vec<ScalarExpression> {
     match expr {
         ScalarExpression::Binary {
             op: BinaryOperator::And,
@@ -69,6 +79,34 @@ fn reduce_filters(filters: Vec<ScalarExpression>, having: bool) -> Option<Filter
         })
 }
 
+/// Split out the `left_col = right_col` predicates which can be used as equi-join keys,
+/// the keys are ordered as (left, right).
+fn extract_equi_join_keys(
+    filters: Vec<ScalarExpression>,
+    left_columns: &[ColumnRef],
+    right_columns: &[ColumnRef],
+) -> (Vec<(ScalarExpression, ScalarExpression)>, Vec<ScalarExpression>) {
+    let mut on_keys = vec![];
+    let mut rest = vec![];
+
+    for filter in filters {
+        if let ScalarExpression::Binary { op: BinaryOperator::Eq, left_expr, right_expr, .. } = &filter {
+            if let (ScalarExpression::ColumnRef(l), ScalarExpression::ColumnRef(r)) = (left_expr.as_ref(), right_expr.as_ref()) {
+                if left_columns.contains(l) && right_columns.contains(r) {
+                    on_keys.push((*left_expr.clone(), *right_expr.clone()));
+                    continue;
+                } else if left_columns.contains(r) && right_columns.contains(l) {
+                    on_keys.push((*right_expr.clone(), *left_expr.clone()));
+                    continue;
+                }
+            }
+        }
+        rest.push(filter);
+    }
+
+    (on_keys, rest)
+}
+
 /// Return true when left is subset of right, only compare table_id and column_id, so it's safe to
 /// used for join output cols with nullable columns.
 /// If left equals right, return true.
@@ -84,6 +122,9 @@ pub fn is_subset_cols(left: &[ColumnRef], right: &[ColumnRef]) -> bool {
 ///
 /// And also pushes down the join filter, where the `condition` can be evaluated using only the
 /// attributes of the left or right side of sub query when applicable.
+///
+/// For inner and cross joins, `left_col = right_col` predicates become equi-join keys,
+/// so `SELECT * FROM a, b WHERE a.id = b.id` is executed as an inner join.
 pub struct PushPredicateThroughJoin;
 
 impl Rule for PushPredicateThroughJoin {
@@ -95,32 +136,30 @@ impl Rule for PushPredicateThroughJoin {
     fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
         let child_id = graph.children_at(node_id)[0];
         if let Operator::Join(child_op) = graph.operator(child_id) {
-            if !matches!(child_op.join_type, JoinType::Inner | JoinType::Left | JoinType::Right) {
+            if matches!(child_op.join_type, JoinType::Full) {
                 return ;
             }
 
             let join_childs = graph.children_at(child_id);
-            let left_columns = graph
-                .operator(join_childs[0])
-                .referenced_columns();
-            let right_columns = graph
-                .operator(join_childs[1])
-                .referenced_columns();
+            let left_columns = subtree_columns(graph, join_childs[0]);
+            let right_columns = subtree_columns(graph, join_childs[1]);
+            let correlated_columns = correlated_columns(graph, node_id);
 
             let mut new_ops = (None, None, None);
+            let mut new_join_op = None;
 
             if let Operator::Filter(op) = graph.operator(node_id) {
                 let filter_exprs = split_conjunctive_predicates(&op.predicate);
 
                 let (left_filters, rest): (Vec<_>, Vec<_>) = filter_exprs
                     .into_iter()
-                    .partition(|f| is_subset_cols(&f.referenced_columns(), &left_columns));
+                    .partition(|f| is_subset_cols(&uncorrelated_columns(f, &correlated_columns), &left_columns));
                 let (right_filters, common_filters): (Vec<_>, Vec<_>) = rest
                     .into_iter()
-                    .partition(|f| is_subset_cols(&f.referenced_columns(), &right_columns));
+                    .partition(|f| is_subset_cols(&uncorrelated_columns(f, &correlated_columns), &right_columns));
 
                 let replace_filters = match child_op.join_type {
-                    JoinType::Inner => {
+                    JoinType::Inner | JoinType::Cross => {
                         if !left_filters.is_empty() {
                             if let Some(left_filter_op) = reduce_filters(left_filters, op.having) {
                                 new_ops.0 = Some(Operator::Filter(left_filter_op));
@@ -133,9 +172,29 @@ impl Rule for PushPredicateThroughJoin {
                             }
                         }
 
-                        common_filters
+                        let (on_keys, rest) = extract_equi_join_keys(
+                            common_filters,
+                            &left_columns,
+                            &right_columns
+                        );
+
+                        if !on_keys.is_empty() {
+                            let (mut on, filter) = match &child_op.on {
+                                JoinCondition::On { on, filter } => (on.clone(), filter.clone()),
+                                JoinCondition::None => (vec![], None),
+                            };
+                            on.extend(on_keys);
+
+                            new_join_op = Some(Operator::Join(JoinOperator {
+                                on: JoinCondition::On { on, filter },
+                                join_type: JoinType::Inner,
+                            }));
+                        }
+
+                        rest
                     }
-                    JoinType::Left => {
+                    // semi and anti joins only output the left columns
+                    JoinType::Left | JoinType::LeftSemi | JoinType::LeftAnti | JoinType::NullAwareLeftAnti => {
                         if !left_filters.is_empty() {
                             if let Some(left_filter_op) = reduce_filters(left_filters, op.having) {
                                 new_ops.0 = Some(Operator::Filter(left_filter_op));
@@ -169,6 +228,13 @@ impl Rule for PushPredicateThroughJoin {
                 }
             }
 
+            if let Some(join_op) = new_join_op {
+                graph.replace_node(
+                    child_id,
+                    OptExprNode::OperatorRef(join_op)
+                );
+            }
+
             if let Some(left_op) = new_ops.0 {
                 graph.add_node(
                     child_id,
@@ -197,6 +263,199 @@ impl Rule for PushPredicateThroughJoin {
     }
 }
 
+/// Whether the expression needs the output of an aggregate, which doesn't exist below it.
+fn has_agg_output(expr: &ScalarExpression) -> bool {
+    match expr {
+        ScalarExpression::AggCall { .. } | ScalarExpression::InputRef { .. } => true,
+        ScalarExpression::Constant(_) | ScalarExpression::ColumnRef(_) => false,
+        ScalarExpression::Alias { expr, .. }
+        | ScalarExpression::TypeCast { expr, .. }
+        | ScalarExpression::IsNull { expr, .. }
+        | ScalarExpression::Unary { expr, .. } => has_agg_output(expr),
+        ScalarExpression::Binary { left_expr, right_expr, .. } => {
+            has_agg_output(left_expr) || has_agg_output(right_expr)
+        }
+        ScalarExpression::Coalesce { exprs, .. } => exprs.iter().any(has_agg_output),
+    }
+}
+
+/// Columns of the outer rows referenced by the `LATERAL` subqueries containing the node,
+/// they are constants for the subquery, so the predicates are pushed down as if they did not
+/// reference them, and the right plan of `Apply` is optimized once for all the outer rows.
+fn correlated_columns(graph: &HepGraph, node_id: HepNodeId) -> Vec<ColumnRef> {
+    let mut columns = vec![];
+    let mut node_id = node_id;
+
+    while let Some(parent_id) = graph.parent_id(node_id) {
+        if let Operator::Apply(op) = graph.operator(parent_id) {
+            columns.extend(op.correlated_columns.iter().cloned());
+        }
+        node_id = parent_id;
+    }
+
+    columns
+}
+
+/// The columns referenced by the expression, other than the correlated columns.
+fn uncorrelated_columns(expr: &ScalarExpression, correlated_columns: &[ColumnRef]) -> Vec<ColumnRef> {
+    expr.referenced_columns()
+        .into_iter()
+        .filter(|col| !correlated_columns.contains(col))
+        .collect_vec()
+}
+
+/// The expression of the projection that outputs the column, the columns of a derived table
+/// are matched by name as they are at runtime.
+fn projected_expr(col: &ColumnRef, exprs: &[ScalarExpression]) -> Option<ScalarExpression> {
+    if exprs.contains(&ScalarExpression::ColumnRef(col.clone())) {
+        return Some(ScalarExpression::ColumnRef(col.clone()));
+    }
+    let candidates = exprs
+        .iter()
+        .filter(|expr| match expr {
+            ScalarExpression::ColumnRef(output_col) => output_col.name == col.name,
+            ScalarExpression::Alias { alias, .. } => alias == &col.name,
+            _ => false,
+        })
+        .collect_vec();
+
+    match candidates.as_slice() {
+        [expr] if !has_agg_output(expr) => Some(expr.unpack_alias().clone()),
+        _ => None,
+    }
+}
+
+/// Rewrite the predicate to be evaluated below the projection.
+fn replace_projected_columns(
+    predicate: &ScalarExpression,
+    exprs: &[ScalarExpression],
+    correlated_columns: &[ColumnRef],
+) -> Option<ScalarExpression> {
+    let replaced = uncorrelated_columns(predicate, correlated_columns)
+        .into_iter()
+        .map(|col| projected_expr(&col, exprs).map(|expr| (col, expr)))
+        .collect::<Option<Vec<_>>>()?;
+
+    let mut predicate = predicate.clone();
+    predicate.replace_column_refs(&|col| replaced
+        .iter()
+        .find(|(replaced_col, _)| replaced_col == col)
+        .map(|(_, expr)| expr.clone()));
+
+    Some(predicate)
+}
+
+/// Pushes the conjuncts of a `Filter` below a `Project`, `Aggregate` or `Sort`.
+///
+/// - Project: the columns are replaced by the expressions which output them.
+/// - Aggregate: only the conjuncts which reference the group-by columns alone.
+/// - Sort: the whole predicate, unless the sort has a limit.
+pub struct PushPredicateThroughNonJoin;
+
+impl Rule for PushPredicateThroughNonJoin {
+    fn pattern(&self) -> &Pattern {
+        &PUSH_PREDICATE_THROUGH_NON_JOIN
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        let child_id = graph.children_at(node_id)[0];
+        let Operator::Filter(op) = graph.operator(node_id) else {
+            return;
+        };
+        let filter_exprs = split_conjunctive_predicates(&op.predicate);
+        let correlated_columns = correlated_columns(graph, node_id);
+
+        let (pushed, rest, having) = match graph.operator(child_id) {
+            Operator::Project(project_op) => {
+                let mut pushed = vec![];
+                let mut rest = vec![];
+
+                for expr in filter_exprs {
+                    match replace_projected_columns(&expr, &project_op.columns, &correlated_columns) {
+                        Some(replaced) => pushed.push(replaced),
+                        None => rest.push(expr),
+                    }
+                }
+                (pushed, rest, op.having)
+            }
+            Operator::Aggregate(agg_op) => {
+                let (pushed, rest): (Vec<_>, Vec<_>) = filter_exprs
+                    .into_iter()
+                    .partition(|expr| {
+                        let columns = uncorrelated_columns(expr, &correlated_columns);
+
+                        !columns.is_empty()
+                            && !has_agg_output(expr)
+                            && columns.iter().all(|col| {
+                                agg_op.groupby_exprs.contains(&ScalarExpression::ColumnRef(col.clone()))
+                            })
+                    });
+                (pushed, rest, false)
+            }
+            Operator::Sort(sort_op) if sort_op.limit.is_none() => (filter_exprs, vec![], op.having),
+            _ => return,
+        };
+
+        let Some(pushed_op) = reduce_filters(pushed, having) else {
+            return;
+        };
+        let rest_op = reduce_filters(rest, op.having);
+
+        graph.add_node(
+            child_id,
+            graph.children_at(child_id).first().cloned(),
+            OptExprNode::OperatorRef(Operator::Filter(pushed_op))
+        );
+        if let Some(rest_op) = rest_op {
+            graph.replace_node(node_id, OptExprNode::OperatorRef(Operator::Filter(rest_op)));
+        } else {
+            graph.remove_node(node_id, false);
+        }
+    }
+}
+
+/// Moves the conjuncts of a `Filter` which only reference the columns of the table into
+/// `pre_where` of the `Scan` below it, they are evaluated while the tuples are read.
+pub struct PushPredicateIntoScan;
+
+impl Rule for PushPredicateIntoScan {
+    fn pattern(&self) -> &Pattern {
+        &PUSH_PREDICATE_INTO_SCAN
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        let child_id = graph.children_at(node_id)[0];
+        if let (Operator::Filter(op), Operator::Scan(scan_op)) = (graph.operator(node_id), graph.operator(child_id)) {
+            // the bounds of the scan are applied to the filtered tuples
+            if op.having || scan_op.limit != (None, None) {
+                return;
+            }
+            let scan_columns = scan_op.columns
+                .iter()
+                .flat_map(|expr| expr.referenced_columns())
+                .collect_vec();
+            let correlated_columns = correlated_columns(graph, node_id);
+            let (pushed, rest): (Vec<_>, Vec<_>) = split_conjunctive_predicates(&op.predicate)
+                .into_iter()
+                .partition(|expr| is_subset_cols(&uncorrelated_columns(expr, &correlated_columns), &scan_columns));
+
+            if pushed.is_empty() {
+                return;
+            }
+            let mut new_scan_op = scan_op.clone();
+            new_scan_op.pre_where.extend(pushed);
+            let rest_op = reduce_filters(rest, op.having);
+
+            graph.replace_node(child_id, OptExprNode::OperatorRef(Operator::Scan(new_scan_op)));
+            if let Some(rest_op) = rest_op {
+                graph.replace_node(node_id, OptExprNode::OperatorRef(Operator::Filter(rest_op)));
+            } else {
+                graph.remove_node(node_id, false);
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::binder::test::select_sql_run;
@@ -205,6 +464,7 @@ mod tests {
     use crate::optimizer::heuristic::batch::HepBatchStrategy;
     use crate::optimizer::heuristic::optimizer::HepOptimizer;
     use crate::optimizer::rule::RuleImpl;
+    use crate::planner::operator::join::{JoinCondition, JoinType};
     use crate::planner::operator::Operator;
     use crate::types::LogicalType;
 
@@ -336,4 +596,114 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_push_predicate_through_join_in_cross_join() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select * from t1, t2 where c1 = c3 and c1 > 1 and c2 < c4").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_push_predicate_through_join".to_string(),
+                HepBatchStrategy::once_topdown(),
+                vec![RuleImpl::PushPredicateThroughJoin]
+            )
+            .find_best();
+
+        if let Operator::Filter(op) = &best_plan.childrens[0].operator {
+            match op.predicate {
+                ScalarExpression::Binary {
+                    op: BinaryOperator::Lt,
+                    ty: LogicalType::Boolean,
+                    ..
+                } => (),
+                _ => unreachable!()
+            }
+        } else {
+            unreachable!("Should be a filter operator")
+        }
+
+        if let Operator::Join(op) = &best_plan.childrens[0].childrens[0].operator {
+            assert_eq!(op.join_type, JoinType::Inner);
+
+            match &op.on {
+                JoinCondition::On { on, filter: None } => assert_eq!(on.len(), 1),
+                _ => unreachable!("Should have an equi-join condition")
+            }
+        } else {
+            unreachable!("Should be a join operator")
+        }
+
+        if let Operator::Filter(op) = &best_plan.childrens[0].childrens[0].childrens[0].operator {
+            match op.predicate {
+                ScalarExpression::Binary {
+                    op: BinaryOperator::Gt,
+                    ty: LogicalType::Boolean,
+                    ..
+                } => (),
+                _ => unreachable!()
+            }
+        } else {
+            unreachable!("Should be a filter operator")
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_predicate_through_project_into_scan() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select * from (select c1, c2 + 1 as x from t1 order by c2) s where x > 1 and c1 < 3").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_push_predicate_through_non_join".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![RuleImpl::PushPredicateThroughNonJoin, RuleImpl::PushPredicateIntoScan]
+            )
+            .find_best();
+
+        let mut plan = &best_plan;
+        while !plan.childrens.is_empty() {
+            assert!(!matches!(plan.operator, Operator::Filter(_)), "Should be pushed into the scan");
+            plan = &plan.childrens[0];
+        }
+        if let Operator::Scan(op) = &plan.operator {
+            assert_eq!(op.pre_where.len(), 2);
+            // the alias is replaced by the projected expression
+            assert_eq!(op.pre_where[0].to_string(), "((t1.c2 + 1) > 1)");
+        } else {
+            unreachable!("Should be a scan operator")
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_push_predicate_through_aggregate() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select c1, sum(c2) from t1 group by c1 having c1 > 1 and sum(c2) > 1").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_push_predicate_through_non_join".to_string(),
+                HepBatchStrategy::once_topdown(),
+                vec![RuleImpl::PushPredicateThroughNonJoin]
+            )
+            .find_best();
+
+        if let Operator::Filter(op) = &best_plan.childrens[0].operator {
+            assert!(op.having);
+            assert_eq!(op.predicate.to_string(), "(#1 > 1)");
+        } else {
+            unreachable!("Should be a filter operator")
+        }
+        assert!(matches!(best_plan.childrens[0].childrens[0].operator, Operator::Aggregate(_)));
+
+        if let Operator::Filter(op) = &best_plan.childrens[0].childrens[0].childrens[0].operator {
+            assert!(!op.having);
+            assert_eq!(op.predicate.to_string(), "(t1.c1 > 1)");
+        } else {
+            unreachable!("Should be a filter operator")
+        }
+
+        Ok(())
+    }
+}
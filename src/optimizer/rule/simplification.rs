@@ -0,0 +1,350 @@
+use std::sync::Arc;
+use lazy_static::lazy_static;
+use crate::catalog::ColumnRef;
+use crate::expression::{BinaryOperator, ScalarExpression, UnaryOperator};
+use crate::expression::value_compute::{binary_op, unary_op};
+use crate::optimizer::core::opt_expr::OptExprNode;
+use crate::optimizer::core::pattern::{Pattern, PatternChildrenPredicate};
+use crate::optimizer::core::rule::Rule;
+use crate::optimizer::heuristic::graph::{HepGraph, HepNodeId};
+use crate::optimizer::heuristic::batch::HepMatchOrder;
+use crate::planner::operator::join::{JoinCondition, JoinOperator};
+use crate::planner::operator::Operator;
+use crate::planner::operator::values::ValuesOperator;
+use crate::types::LogicalType;
+use crate::types::value::DataValue;
+
+lazy_static! {
+    static ref CONSTANT_FOLDING_RULE: Pattern = {
+        Pattern {
+            predicate: |op| matches!(op, Operator::Filter(_) | Operator::Join(_) | Operator::Scan(_)),
+            children: PatternChildrenPredicate::None,
+        }
+    };
+    static ref PRUNE_FILTERS_RULE: Pattern = {
+        Pattern {
+            predicate: |op| matches!(op, Operator::Filter(_)),
+            children: PatternChildrenPredicate::None,
+        }
+    };
+}
+
+fn boolean(value: bool) -> ScalarExpression {
+    ScalarExpression::Constant(Arc::new(DataValue::Boolean(Some(value))))
+}
+
+fn is_boolean(expr: &ScalarExpression, value: bool) -> bool {
+    matches!(expr, ScalarExpression::Constant(v) if v.as_ref() == &DataValue::Boolean(Some(value)))
+}
+
+/// The comparison with the operands swapped, `1 < a` is `a > 1`.
+fn flip_comparison(op: &BinaryOperator) -> Option<BinaryOperator> {
+    match op {
+        BinaryOperator::Gt => Some(BinaryOperator::Lt),
+        BinaryOperator::Lt => Some(BinaryOperator::Gt),
+        BinaryOperator::GtEq => Some(BinaryOperator::LtEq),
+        BinaryOperator::LtEq => Some(BinaryOperator::GtEq),
+        BinaryOperator::Eq | BinaryOperator::NotEq | BinaryOperator::Spaceship => Some(*op),
+        _ => None,
+    }
+}
+
+/// The negated comparison, `NOT a > 1` is `a <= 1`, which also holds for NULL.
+fn negate_comparison(op: &BinaryOperator) -> Option<BinaryOperator> {
+    match op {
+        BinaryOperator::Gt => Some(BinaryOperator::LtEq),
+        BinaryOperator::Lt => Some(BinaryOperator::GtEq),
+        BinaryOperator::GtEq => Some(BinaryOperator::Lt),
+        BinaryOperator::LtEq => Some(BinaryOperator::Gt),
+        BinaryOperator::Eq => Some(BinaryOperator::NotEq),
+        BinaryOperator::NotEq => Some(BinaryOperator::Eq),
+        _ => None,
+    }
+}
+
+/// Folds the constant subtrees, simplifies the boolean algebra and moves the constants of the
+/// comparisons to the right.
+///
+/// `not_null_columns` are the columns known to never be NULL, `x = x` is only true for them.
+fn simplify(expr: ScalarExpression, not_null_columns: &[ColumnRef]) -> ScalarExpression {
+    match expr {
+        ScalarExpression::Binary { op, left_expr, right_expr, ty } => simplify_binary(
+            op,
+            simplify(*left_expr, not_null_columns),
+            simplify(*right_expr, not_null_columns),
+            ty,
+            not_null_columns,
+        ),
+        ScalarExpression::Unary { op, expr, ty } => {
+            let expr = simplify(*expr, not_null_columns);
+
+            match (&op, expr) {
+                (_, ScalarExpression::Constant(value)) => match unary_op(&value, &op) {
+                    Ok(value) => ScalarExpression::Constant(Arc::new(value)),
+                    Err(_) => ScalarExpression::Unary { op, expr: Box::new(ScalarExpression::Constant(value)), ty },
+                },
+                (UnaryOperator::Not, ScalarExpression::Unary { op: UnaryOperator::Not, expr, .. }) => *expr,
+                (UnaryOperator::Not, ScalarExpression::Binary { op: binary_op, left_expr, right_expr, ty })
+                    if negate_comparison(&binary_op).is_some() => ScalarExpression::Binary {
+                        op: negate_comparison(&binary_op).unwrap(),
+                        left_expr,
+                        right_expr,
+                        ty,
+                    },
+                (_, expr) => ScalarExpression::Unary { op, expr: Box::new(expr), ty },
+            }
+        }
+        ScalarExpression::TypeCast { expr, ty, is_try } => {
+            let expr = simplify(*expr, not_null_columns);
+
+            if let ScalarExpression::Constant(value) = &expr {
+                if let Ok(value) = DataValue::clone(value).cast(&ty) {
+                    return ScalarExpression::Constant(Arc::new(value));
+                }
+            }
+            ScalarExpression::TypeCast { expr: Box::new(expr), ty, is_try }
+        }
+        ScalarExpression::IsNull { negated, expr } => {
+            let expr = simplify(*expr, not_null_columns);
+
+            match &expr {
+                ScalarExpression::Constant(value) => boolean(value.is_null() != negated),
+                ScalarExpression::ColumnRef(col) if not_null_columns.contains(col) => boolean(negated),
+                _ => ScalarExpression::IsNull { negated, expr: Box::new(expr) },
+            }
+        }
+        ScalarExpression::Alias { expr, alias } => ScalarExpression::Alias {
+            expr: Box::new(simplify(*expr, not_null_columns)),
+            alias,
+        },
+        ScalarExpression::Coalesce { exprs, ty } => ScalarExpression::Coalesce {
+            exprs: exprs.into_iter().map(|expr| simplify(expr, not_null_columns)).collect(),
+            ty,
+        },
+        expr => expr,
+    }
+}
+
+fn simplify_binary(
+    op: BinaryOperator,
+    left: ScalarExpression,
+    right: ScalarExpression,
+    ty: LogicalType,
+    not_null_columns: &[ColumnRef],
+) -> ScalarExpression {
+    if let (ScalarExpression::Constant(l), ScalarExpression::Constant(r)) = (&left, &right) {
+        if let Ok(value) = binary_op(l, r, &op) {
+            return ScalarExpression::Constant(Arc::new(value));
+        }
+    }
+
+    match op {
+        // FALSE AND NULL is FALSE, TRUE OR NULL is TRUE
+        BinaryOperator::And if is_boolean(&left, false) || is_boolean(&right, false) => boolean(false),
+        BinaryOperator::And if is_boolean(&left, true) => right,
+        BinaryOperator::And if is_boolean(&right, true) => left,
+        BinaryOperator::Or if is_boolean(&left, true) || is_boolean(&right, true) => boolean(true),
+        BinaryOperator::Or if is_boolean(&left, false) => right,
+        BinaryOperator::Or if is_boolean(&right, false) => left,
+        BinaryOperator::And | BinaryOperator::Or if left == right => left,
+        _ => match (&left, flip_comparison(&op)) {
+            (ScalarExpression::ColumnRef(col), Some(_))
+                if left == right && negate_comparison(&op).is_some() && not_null_columns.contains(col) => {
+                boolean(matches!(op, BinaryOperator::Eq | BinaryOperator::GtEq | BinaryOperator::LtEq))
+            }
+            (ScalarExpression::Constant(_), Some(flipped)) if !matches!(right, ScalarExpression::Constant(_)) => {
+                ScalarExpression::Binary {
+                    op: flipped,
+                    left_expr: Box::new(right),
+                    right_expr: Box::new(left),
+                    ty,
+                }
+            }
+            _ => ScalarExpression::Binary {
+                op,
+                left_expr: Box::new(left),
+                right_expr: Box::new(right),
+                ty,
+            },
+        },
+    }
+}
+
+/// The NOT NULL columns of the table read below the node, only through the operators which
+/// can't pad NULLs or rename the columns.
+fn not_null_columns(graph: &HepGraph, node_id: HepNodeId) -> Vec<ColumnRef> {
+    match graph.operator(node_id) {
+        Operator::Scan(op) => op.columns
+            .iter()
+            .filter_map(|expr| match expr {
+                ScalarExpression::ColumnRef(col) if !col.nullable => Some(col.clone()),
+                _ => None,
+            })
+            .collect(),
+        Operator::Filter(_) | Operator::Sort(_) | Operator::Limit(_) => graph
+            .children_at(node_id)
+            .first()
+            .map(|child_id| not_null_columns(graph, *child_id))
+            .unwrap_or_default(),
+        _ => vec![],
+    }
+}
+
+/// Simplifies the predicates of `Filter`, the join filter of `Join` and `pre_where` of `Scan`.
+pub struct ConstantFolding;
+
+impl Rule for ConstantFolding {
+    fn pattern(&self) -> &Pattern {
+        &CONSTANT_FOLDING_RULE
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        let not_null_columns = not_null_columns(graph, node_id);
+        let operator = graph.operator(node_id);
+
+        let new_operator = match operator.clone() {
+            Operator::Filter(mut op) => {
+                op.predicate = simplify(op.predicate, &not_null_columns);
+                Operator::Filter(op)
+            }
+            Operator::Join(JoinOperator { on: JoinCondition::On { on, filter }, join_type }) => {
+                let filter = filter
+                    .map(|expr| simplify(expr, &[]))
+                    .filter(|expr| !is_boolean(expr, true));
+
+                Operator::Join(JoinOperator { on: JoinCondition::On { on, filter }, join_type })
+            }
+            Operator::Scan(mut op) => {
+                op.pre_where = op.pre_where
+                    .into_iter()
+                    .map(|expr| simplify(expr, &not_null_columns))
+                    .filter(|expr| !is_boolean(expr, true))
+                    .collect();
+                Operator::Scan(op)
+            }
+            _ => return,
+        };
+
+        if &new_operator != operator {
+            graph.replace_node(node_id, OptExprNode::OperatorRef(new_operator));
+        }
+    }
+}
+
+/// Removes the filters which are always true, and replaces the filters which are always false
+/// or NULL with an empty relation.
+pub struct PruneFilters;
+
+impl Rule for PruneFilters {
+    fn pattern(&self) -> &Pattern {
+        &PRUNE_FILTERS_RULE
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        let Operator::Filter(op) = graph.operator(node_id) else {
+            return;
+        };
+        let ScalarExpression::Constant(value) = &op.predicate else {
+            return;
+        };
+
+        if value.as_ref() == &DataValue::Boolean(Some(true)) {
+            graph.remove_node(node_id, false);
+        } else if value.is_null() || value.as_ref() == &DataValue::Boolean(Some(false)) {
+            let child_id = graph.children_at(node_id)[0];
+            // the columns the child outputs, a projection or an aggregation outputs other
+            // columns than it references
+            let columns = graph.to_plan_with_index(child_id).output_columns();
+
+            for id in graph.nodes_iter(HepMatchOrder::BottomUp, Some(child_id)).collect::<Vec<_>>() {
+                graph.remove_node(id, true);
+            }
+            graph.replace_node(
+                node_id,
+                OptExprNode::OperatorRef(Operator::Values(ValuesOperator { rows: vec![], columns }))
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binder::test::select_sql_run;
+    use crate::execution::ExecutorError;
+    use crate::optimizer::heuristic::batch::HepBatchStrategy;
+    use crate::optimizer::heuristic::optimizer::HepOptimizer;
+    use crate::optimizer::rule::RuleImpl;
+    use crate::planner::operator::Operator;
+
+    #[tokio::test]
+    async fn test_constant_folding() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select * from t1 where 1 + 2 < c2 and not not (true and c1 = c1) and not c2 > 1").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_constant_folding".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![RuleImpl::ConstantFolding]
+            )
+            .find_best();
+
+        if let Operator::Filter(op) = &best_plan.childrens[0].operator {
+            // c1 is the primary key, so `c1 = c1` is always true
+            assert_eq!(op.predicate.to_string(), "((t1.c2 > 3) && (t1.c2 <= 1))");
+        } else {
+            unreachable!("Should be a filter operator")
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_prune_filters() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select c1 from t1 where c2 > 1 and 1 > 2").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_prune_filters".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![RuleImpl::ConstantFolding, RuleImpl::PruneFilters]
+            )
+            .find_best();
+
+        if let Operator::Values(op) = &best_plan.childrens[0].operator {
+            assert!(op.rows.is_empty());
+            assert!(best_plan.childrens[0].childrens.is_empty());
+        } else {
+            unreachable!("Should be an empty relation")
+        }
+
+        let plan = select_sql_run("select c1 from t1 where 1 < 2").await?;
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_prune_filters".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![RuleImpl::ConstantFolding, RuleImpl::PruneFilters]
+            )
+            .find_best();
+
+        assert!(matches!(best_plan.childrens[0].operator, Operator::Scan(_)));
+
+        // the empty relation has the columns of the aggregation below the filter
+        let plan = select_sql_run("select c1, count(c2) from t1 group by c1 having false").await?;
+        let aggregate_columns = plan.childrens[0].childrens[0].output_columns();
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_prune_filters".to_string(),
+                HepBatchStrategy::fix_point_topdown(10),
+                vec![RuleImpl::ConstantFolding, RuleImpl::PruneFilters]
+            )
+            .find_best();
+
+        if let Operator::Values(op) = &best_plan.childrens[0].operator {
+            assert_eq!(op.columns, aggregate_columns);
+        } else {
+            unreachable!("Should be an empty relation")
+        }
+
+        Ok(())
+    }
+}
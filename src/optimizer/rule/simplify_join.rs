@@ -0,0 +1,235 @@
+use lazy_static::lazy_static;
+use crate::catalog::ColumnRef;
+use crate::expression::{BinaryOperator, ScalarExpression};
+use crate::optimizer::core::opt_expr::OptExprNode;
+use crate::optimizer::core::pattern::{Pattern, PatternChildrenPredicate};
+use crate::optimizer::core::rule::Rule;
+use crate::optimizer::heuristic::batch::HepMatchOrder;
+use crate::optimizer::heuristic::graph::{HepGraph, HepNodeId};
+use crate::optimizer::rule::subtree_columns;
+use crate::optimizer::rule::pushdown_predicates::{is_subset_cols, split_conjunctive_predicates};
+use crate::planner::operator::join::{JoinCondition, JoinOperator, JoinType};
+use crate::planner::operator::Operator;
+
+lazy_static! {
+    static ref SIMPLIFY_OUTER_JOIN_RULE: Pattern = {
+        Pattern {
+            predicate: |op| matches!(op, Operator::Filter(_)),
+            children: PatternChildrenPredicate::Predicate(vec![Pattern {
+                predicate: |op| matches!(op, Operator::Join(_)),
+                children: PatternChildrenPredicate::None,
+            }]),
+        }
+    };
+    static ref ELIMINATE_LEFT_JOIN_RULE: Pattern = {
+        Pattern {
+            predicate: |op| matches!(op, Operator::Project(_) | Operator::Aggregate(_)),
+            children: PatternChildrenPredicate::Predicate(vec![Pattern {
+                predicate: |op| matches!(op, Operator::Join(_)),
+                children: PatternChildrenPredicate::None,
+            }]),
+        }
+    };
+}
+
+/// Whether the expression is NULL when all the columns are NULL.
+fn is_null_on_null(expr: &ScalarExpression, columns: &[ColumnRef]) -> bool {
+    match expr {
+        ScalarExpression::ColumnRef(col) => columns.contains(col),
+        ScalarExpression::Unary { expr, .. }
+        | ScalarExpression::TypeCast { expr, .. }
+        | ScalarExpression::Alias { expr, .. } => is_null_on_null(expr, columns),
+        ScalarExpression::Binary { op, left_expr, right_expr, .. }
+            if !matches!(op, BinaryOperator::And | BinaryOperator::Or | BinaryOperator::Spaceship) => {
+            is_null_on_null(left_expr, columns) || is_null_on_null(right_expr, columns)
+        }
+        _ => false,
+    }
+}
+
+/// Whether the predicate is never true when all the columns are NULL, so it rejects the rows
+/// padded with NULLs by an outer join.
+fn is_null_rejecting(predicate: &ScalarExpression, columns: &[ColumnRef]) -> bool {
+    match predicate {
+        ScalarExpression::Binary { op: BinaryOperator::And, left_expr, right_expr, .. } => {
+            is_null_rejecting(left_expr, columns) || is_null_rejecting(right_expr, columns)
+        }
+        ScalarExpression::Binary { op: BinaryOperator::Or, left_expr, right_expr, .. } => {
+            is_null_rejecting(left_expr, columns) && is_null_rejecting(right_expr, columns)
+        }
+        ScalarExpression::IsNull { negated: true, expr } => is_null_on_null(expr, columns),
+        predicate => is_null_on_null(predicate, columns),
+    }
+}
+
+/// Turns an outer join into a join which preserves fewer sides when the filter above it
+/// rejects the NULLs padded for the other side, e.g. `t1 LEFT JOIN t2 ... WHERE t2.c > 1` is
+/// an inner join, which the predicates can be pushed through and the joins reordered.
+pub struct SimplifyOuterJoin;
+
+impl Rule for SimplifyOuterJoin {
+    fn pattern(&self) -> &Pattern {
+        &SIMPLIFY_OUTER_JOIN_RULE
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        let child_id = graph.children_at(node_id)[0];
+        if let (Operator::Filter(op), Operator::Join(join_op)) = (graph.operator(node_id), graph.operator(child_id)) {
+            if !matches!(join_op.join_type, JoinType::Left | JoinType::Right | JoinType::Full) {
+                return;
+            }
+            let join_childs = graph.children_at(child_id);
+            let left_columns = subtree_columns(graph, join_childs[0]);
+            let right_columns = subtree_columns(graph, join_childs[1]);
+            let filters = split_conjunctive_predicates(&op.predicate);
+
+            let left_rejected = filters.iter().any(|f| is_null_rejecting(f, &left_columns));
+            let right_rejected = filters.iter().any(|f| is_null_rejecting(f, &right_columns));
+
+            let join_type = match (join_op.join_type, left_rejected, right_rejected) {
+                (JoinType::Left, _, true) | (JoinType::Right, true, _) | (JoinType::Full, true, true) => JoinType::Inner,
+                (JoinType::Full, true, false) => JoinType::Left,
+                (JoinType::Full, false, true) => JoinType::Right,
+                _ => return,
+            };
+
+            graph.replace_node(
+                child_id,
+                OptExprNode::OperatorRef(Operator::Join(JoinOperator {
+                    on: join_op.on.clone(),
+                    join_type,
+                }))
+            );
+        }
+    }
+}
+
+/// The output of the subtree holds at most one row for every value of the columns, when the
+/// subtree only filters a scan and the columns include the primary key.
+fn is_unique(graph: &HepGraph, node_id: HepNodeId, columns: &[ColumnRef]) -> bool {
+    match graph.operator(node_id) {
+        Operator::Scan(op) => op.columns
+            .iter()
+            .any(|expr| matches!(expr, ScalarExpression::ColumnRef(col) if col.desc.is_primary && columns.contains(col))),
+        Operator::Filter(_) => is_unique(graph, graph.children_at(node_id)[0], columns),
+        _ => false,
+    }
+}
+
+/// Removes a left join whose right side isn't referenced above it and matches at most one row
+/// for every left row, e.g. `SELECT t1.* FROM t1 LEFT JOIN t2 ON t1.a = t2.id`.
+pub struct EliminateLeftJoin;
+
+impl Rule for EliminateLeftJoin {
+    fn pattern(&self) -> &Pattern {
+        &ELIMINATE_LEFT_JOIN_RULE
+    }
+
+    fn apply(&self, node_id: HepNodeId, graph: &mut HepGraph) {
+        let child_id = graph.children_at(node_id)[0];
+        let Operator::Join(JoinOperator { on: JoinCondition::On { on, .. }, join_type: JoinType::Left }) = graph.operator(child_id) else {
+            return;
+        };
+        let join_childs = graph.children_at(child_id);
+        let left_columns = subtree_columns(graph, join_childs[0]);
+        let right_columns = subtree_columns(graph, join_childs[1]);
+
+        if !is_subset_cols(&graph.operator(node_id).referenced_columns(), &left_columns) {
+            return;
+        }
+        let right_keys = on
+            .iter()
+            .flat_map(|(left_expr, right_expr)| [left_expr, right_expr])
+            .filter_map(|expr| match expr {
+                ScalarExpression::ColumnRef(col) if right_columns.contains(col) => Some(col.clone()),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !is_unique(graph, join_childs[1], &right_keys) {
+            return;
+        }
+
+        for id in graph.nodes_iter(HepMatchOrder::BottomUp, Some(join_childs[1])).collect::<Vec<_>>() {
+            graph.remove_node(id, true);
+        }
+        graph.remove_node(child_id, false);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::binder::test::select_sql_run;
+    use crate::execution::ExecutorError;
+    use crate::optimizer::heuristic::batch::HepBatchStrategy;
+    use crate::optimizer::heuristic::optimizer::HepOptimizer;
+    use crate::optimizer::rule::RuleImpl;
+    use crate::planner::operator::join::JoinType;
+    use crate::planner::operator::Operator;
+
+    #[tokio::test]
+    async fn test_simplify_outer_join() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select * from t1 left join t2 on c1 = c3 where c4 + 1 > 2 or c3 < 0").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_simplify_outer_join".to_string(),
+                HepBatchStrategy::once_topdown(),
+                vec![RuleImpl::SimplifyOuterJoin]
+            )
+            .find_best();
+
+        if let Operator::Join(op) = &best_plan.childrens[0].childrens[0].operator {
+            assert_eq!(op.join_type, JoinType::Inner);
+        } else {
+            unreachable!("Should be a join operator")
+        }
+
+        let plan = select_sql_run("select * from t1 full join t2 on c1 = c3 where c2 > 1 or c4 > 1").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_simplify_outer_join".to_string(),
+                HepBatchStrategy::once_topdown(),
+                vec![RuleImpl::SimplifyOuterJoin]
+            )
+            .find_best();
+
+        if let Operator::Join(op) = &best_plan.childrens[0].childrens[0].operator {
+            assert_eq!(op.join_type, JoinType::Full);
+        } else {
+            unreachable!("Should be a join operator")
+        }
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_eliminate_left_join() -> Result<(), ExecutorError> {
+        let plan = select_sql_run("select c1, c2 from t1 left join t2 on c2 = c3").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_eliminate_left_join".to_string(),
+                HepBatchStrategy::once_topdown(),
+                vec![RuleImpl::EliminateLeftJoin]
+            )
+            .find_best();
+
+        assert!(matches!(best_plan.childrens[0].operator, Operator::Scan(_)));
+
+        // c4 isn't unique
+        let plan = select_sql_run("select c1, c2 from t1 left join t2 on c2 = c4").await?;
+
+        let best_plan = HepOptimizer::new(plan)
+            .batch(
+                "test_eliminate_left_join".to_string(),
+                HepBatchStrategy::once_topdown(),
+                vec![RuleImpl::EliminateLeftJoin]
+            )
+            .find_best();
+
+        assert!(matches!(best_plan.childrens[0].operator, Operator::Join(_)));
+
+        Ok(())
+    }
+}
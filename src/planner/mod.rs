@@ -1,6 +1,13 @@
 pub mod operator;
 
+use itertools::Itertools;
+use crate::catalog::ColumnRef;
+use crate::expression::ScalarExpression;
+use crate::planner::operator::join::{JoinCondition, JoinType};
 use crate::planner::operator::Operator;
+use crate::planner::operator::sort::SortField;
+use crate::types::errors::TypeError;
+use crate::types::tuple::Tuple;
 
 #[derive(Debug, PartialEq, Clone)]
 pub struct LogicalPlan {
@@ -13,4 +20,215 @@ impl LogicalPlan {
         self.childrens
             .get(index)
     }
+
+    /// Format the plan as a tree for `EXPLAIN`, one operator per line and the children
+    /// indented under their parent.
+    pub fn explain(&self, indentation: usize) -> String {
+        self.explain_with(indentation, &mut |_| String::new())
+    }
+
+    /// Like `explain`, with `suffix` appended to the line of each operator, which are visited
+    /// in pre-order, e.g. the metrics of `EXPLAIN ANALYZE`.
+    pub fn explain_with(&self, indentation: usize, suffix: &mut dyn FnMut(&Operator) -> String) -> String {
+        let mut output = format!(
+            "{:width$}{}{}\n",
+            "",
+            self.operator,
+            suffix(&self.operator),
+            width = indentation * 2
+        );
+
+        for child in self.childrens.iter() {
+            output.push_str(&child.explain_with(indentation + 1, suffix));
+        }
+        output
+    }
+
+    /// Replace the column references in the expressions of the whole plan, e.g. binding the
+    /// correlated columns of a `LATERAL` subquery to the values of the current outer row.
+    pub fn replace_column_refs(&mut self, f: &impl Fn(&ColumnRef) -> Option<ScalarExpression>) {
+        let mut exprs: Vec<&mut ScalarExpression> = match &mut self.operator {
+            Operator::Aggregate(op) => op.agg_calls
+                .iter_mut()
+                .chain(op.groupby_exprs.iter_mut())
+                .collect(),
+            Operator::Filter(op) => vec![&mut op.predicate],
+            Operator::Join(op) => Self::join_condition_exprs(&mut op.on),
+            Operator::Apply(op) => Self::join_condition_exprs(&mut op.on),
+            Operator::Project(op) => op.columns.iter_mut().collect(),
+            Operator::Scan(op) => op.pre_where.iter_mut().collect(),
+            Operator::Sort(op) => op.sort_fields
+                .iter_mut()
+                .map(|field| &mut field.expr)
+                .collect(),
+            _ => vec![],
+        };
+        for expr in exprs.iter_mut() {
+            expr.replace_column_refs(f);
+        }
+        for child in self.childrens.iter_mut() {
+            child.replace_column_refs(f);
+        }
+    }
+
+    fn join_condition_exprs(on: &mut JoinCondition) -> Vec<&mut ScalarExpression> {
+        match on {
+            JoinCondition::On { on, filter } => on
+                .iter_mut()
+                .flat_map(|(left_expr, right_expr)| [left_expr, right_expr])
+                .chain(filter.iter_mut())
+                .collect(),
+            JoinCondition::None => vec![],
+        }
+    }
+
+    /// The columns of the tuples produced by the plan in order, as the executors name them.
+    /// Only the positions and the names matter, e.g. the nullability of outer joins is ignored.
+    pub fn output_columns(&self) -> Vec<ColumnRef> {
+        let header = |plan: &LogicalPlan| Tuple { id: None, columns: plan.output_columns(), values: vec![] };
+
+        match &self.operator {
+            Operator::Scan(op) => {
+                let header = Tuple { id: None, columns: vec![], values: vec![] };
+
+                op.columns.iter().map(|expr| expr.output_columns(&header)).collect()
+            }
+            Operator::Values(op) => op.columns.clone(),
+            Operator::Filter(_) | Operator::Sort(_) | Operator::Limit(_) => self.childrens[0].output_columns(),
+            Operator::Project(op) => {
+                let header = header(&self.childrens[0]);
+
+                op.columns.iter().map(|expr| expr.output_columns(&header)).collect()
+            }
+            Operator::Aggregate(op) => {
+                let header = header(&self.childrens[0]);
+
+                op.agg_calls
+                    .iter()
+                    .chain(op.groupby_exprs.iter())
+                    .map(|expr| expr.output_columns(&header))
+                    .collect()
+            }
+            Operator::Join(op) => {
+                let mut columns = self.childrens[0].output_columns();
+
+                if !matches!(op.join_type, JoinType::LeftSemi | JoinType::LeftAnti | JoinType::NullAwareLeftAnti) {
+                    columns.append(&mut self.childrens[1].output_columns());
+                }
+                columns
+            }
+            Operator::Apply(op) => {
+                let mut columns = self.childrens[0].output_columns();
+
+                columns.extend(op.right_columns.iter().cloned());
+                columns
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Resolve the column references in the expressions of the plan to `InputRef`s, the
+    /// positions of the columns in the tuples of the inputs of the operators, so that the
+    /// columns of joined tables with the same name are told apart without looking them up for
+    /// each row. It is done once the plan is optimized, a column which is missing or ambiguous
+    /// in the input is an error.
+    ///
+    /// The scans read the stored tuples of a single table and `Apply` binds its correlated
+    /// columns, so they are not resolved, the right plan of `Apply` is resolved by its executor
+    /// once the correlated columns are replaced, like the plan of `EXPLAIN ANALYZE`.
+    pub fn resolve_column_refs(&mut self) -> Result<(), TypeError> {
+        let resolve = |exprs: Vec<&mut ScalarExpression>, columns: &[ColumnRef]| {
+            exprs
+                .into_iter()
+                .try_for_each(|expr| expr.resolve_column_refs(columns))
+        };
+
+        // the operators are resolved before their children, whose output columns are named
+        // after their expressions
+        match &mut self.operator {
+            Operator::Aggregate(op) => resolve(
+                op.agg_calls.iter_mut().chain(op.groupby_exprs.iter_mut()).collect(),
+                &self.childrens[0].output_columns(),
+            )?,
+            Operator::Filter(op) => resolve(vec![&mut op.predicate], &self.childrens[0].output_columns())?,
+            Operator::Project(op) => resolve(op.columns.iter_mut().collect(), &self.childrens[0].output_columns())?,
+            Operator::Sort(op) => resolve(
+                op.sort_fields.iter_mut().map(|field| &mut field.expr).collect(),
+                &self.childrens[0].output_columns(),
+            )?,
+            Operator::Join(op) => {
+                if let JoinCondition::On { on, filter } = &mut op.on {
+                    let left_columns = self.childrens[0].output_columns();
+                    let right_columns = self.childrens[1].output_columns();
+                    let (left_keys, right_keys): (Vec<_>, Vec<_>) = on
+                        .iter_mut()
+                        .map(|(left_key, right_key)| (left_key, right_key))
+                        .unzip();
+
+                    resolve(left_keys, &left_columns)?;
+                    resolve(right_keys, &right_columns)?;
+                    // the filter is evaluated on the joined tuples, also for the semi joins
+                    resolve(
+                        filter.iter_mut().collect(),
+                        &left_columns.into_iter().chain(right_columns).collect::<Vec<_>>(),
+                    )?;
+                }
+            }
+            _ => (),
+        }
+
+        match &self.operator {
+            Operator::Apply(_) => self.childrens[0].resolve_column_refs(),
+            Operator::Explain(_) => Ok(()),
+            _ => self.childrens
+                .iter_mut()
+                .try_for_each(|child| child.resolve_column_refs()),
+        }
+    }
+
+    /// The sort fields that the output of this plan is known to be ordered by.
+    ///
+    /// `primary_key_ordered`: whether the storage reads tuples in primary key order.
+    pub fn output_ordering(&self, primary_key_ordered: bool) -> Vec<SortField> {
+        match &self.operator {
+            Operator::Sort(op) => op.sort_fields.clone(),
+            Operator::Scan(op) => {
+                if !op.sort_fields.is_empty() {
+                    return op.sort_fields.clone();
+                }
+
+                op.columns
+                    .iter()
+                    .find(|expr| matches!(expr, ScalarExpression::ColumnRef(col) if col.desc.is_primary))
+                    .filter(|_| primary_key_ordered)
+                    .map(|expr| vec![SortField::new(expr.clone(), false, false)])
+                    .unwrap_or_default()
+            }
+            Operator::Filter(_) | Operator::Limit(_) => self.childrens[0].output_ordering(primary_key_ordered),
+            // the ordering of the input, as long as its columns are output by the projection
+            Operator::Project(op) => {
+                let columns = self.childrens[0].output_columns();
+                let resolved = |expr: &ScalarExpression| {
+                    let mut expr = expr.clone();
+                    expr.resolve_column_refs(&columns).ok().map(|_| expr)
+                };
+                let exprs = op.columns.iter().map(resolved).collect_vec();
+
+                self.childrens[0]
+                    .output_ordering(primary_key_ordered)
+                    .into_iter()
+                    .map_while(|field| {
+                        let expr = resolved(&field.expr)?;
+                        let index = exprs.iter().position(|project_expr| project_expr.as_ref() == Some(&expr))?;
+
+                        Some(SortField {
+                            expr: ScalarExpression::InputRef { index, ty: expr.return_type() },
+                            ..field
+                        })
+                    })
+                    .collect()
+            }
+            _ => vec![],
+        }
+    }
 }
@@ -0,0 +1,19 @@
+use crate::catalog::{ColumnRef, TableName};
+use crate::planner::LogicalPlan;
+use crate::planner::operator::Operator;
+
+/// Collect the statistics of the columns from the rows of the child, a scan of the table.
+#[derive(Debug, PartialEq, Clone)]
+pub struct AnalyzeOperator {
+    pub table_name: TableName,
+    pub columns: Vec<ColumnRef>,
+}
+
+impl AnalyzeOperator {
+    pub fn new(table_name: TableName, columns: Vec<ColumnRef>, children: LogicalPlan) -> LogicalPlan {
+        LogicalPlan {
+            operator: Operator::Analyze(AnalyzeOperator { table_name, columns }),
+            childrens: vec![children],
+        }
+    }
+}
@@ -0,0 +1,38 @@
+use crate::catalog::ColumnRef;
+use crate::planner::LogicalPlan;
+use crate::planner::operator::join::{JoinCondition, JoinType};
+
+use super::Operator;
+
+/// Evaluates the right plan once for every row of the left input, e.g. `LATERAL` subqueries
+/// whose right side references columns of the left side.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ApplyOperator {
+    pub on: JoinCondition,
+    pub join_type: JoinType,
+    /// Columns of the left input referenced by the right plan
+    pub correlated_columns: Vec<ColumnRef>,
+    /// Output columns of the right plan, used to pad NULLs when it produces no rows
+    pub right_columns: Vec<ColumnRef>,
+}
+
+impl ApplyOperator {
+    pub fn new(
+        left: LogicalPlan,
+        right: LogicalPlan,
+        on: JoinCondition,
+        join_type: JoinType,
+        correlated_columns: Vec<ColumnRef>,
+        right_columns: Vec<ColumnRef>,
+    ) -> LogicalPlan {
+        LogicalPlan {
+            operator: Operator::Apply(ApplyOperator {
+                on,
+                join_type,
+                correlated_columns,
+                right_columns,
+            }),
+            childrens: vec![left, right],
+        }
+    }
+}
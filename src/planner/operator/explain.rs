@@ -0,0 +1,31 @@
+use crate::optimizer::OptimizerTrace;
+use crate::planner::LogicalPlan;
+
+use super::Operator;
+
+/// `EXPLAIN [ANALYZE] [VERBOSE]`: outputs the plan of its child as text rows, the plan is only
+/// executed for `ANALYZE`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct ExplainOperator {
+    pub verbose: bool,
+    /// Runs the plan and reports the metrics of each operator
+    pub analyze: bool,
+    /// The plan before optimizing, only kept for `VERBOSE`
+    pub source_plan: Option<Box<LogicalPlan>>,
+    /// The plan after each optimizer step with the rules that fired, only kept for `VERBOSE`
+    pub traces: Vec<OptimizerTrace>,
+}
+
+impl ExplainOperator {
+    pub fn new(verbose: bool, analyze: bool, children: LogicalPlan) -> LogicalPlan {
+        LogicalPlan {
+            operator: Operator::Explain(ExplainOperator {
+                verbose,
+                analyze,
+                source_plan: None,
+                traces: vec![],
+            }),
+            childrens: vec![children],
+        }
+    }
+}
@@ -10,6 +10,14 @@ pub enum JoinType {
     Right,
     Full,
     Cross,
+    /// Left rows that have at least one match, e.g. `IN (subquery)` and `EXISTS`.
+    LeftSemi,
+    /// Left rows that have no match, e.g. `NOT EXISTS`.
+    LeftAnti,
+    /// `NOT IN (subquery)`: like `LeftAnti`, but a NULL on either side makes the comparison
+    /// unknown, so no left row is returned once the right side holds a NULL key, and a left row
+    /// with a NULL key is only returned when the right side is empty.
+    NullAwareLeftAnti,
 }
 #[derive(Debug, Clone, PartialEq)]
 pub enum JoinCondition {
@@ -40,4 +48,9 @@ impl JoinOperator {
             childrens: vec![left, right],
         }
     }
+
+    /// Whether the join can be evaluated by matching equal keys, e.g. with a hash table.
+    pub fn has_equi_keys(&self) -> bool {
+        matches!(&self.on, JoinCondition::On { on, .. } if !on.is_empty())
+    }
 }
@@ -1,4 +1,6 @@
 pub mod aggregate;
+pub mod analyze;
+pub mod apply;
 pub mod create_table;
 pub mod filter;
 pub mod join;
@@ -12,13 +14,19 @@ pub mod update;
 pub mod delete;
 pub mod drop_table;
 pub mod truncate;
+pub mod explain;
 
+use std::fmt;
+use std::fmt::Formatter;
 use itertools::Itertools;
 use crate::catalog::ColumnRef;
 use crate::expression::ScalarExpression;
+use crate::planner::operator::analyze::AnalyzeOperator;
+use crate::planner::operator::apply::ApplyOperator;
 use crate::planner::operator::create_table::CreateTableOperator;
 use crate::planner::operator::delete::DeleteOperator;
 use crate::planner::operator::drop_table::DropTableOperator;
+use crate::planner::operator::explain::ExplainOperator;
 use crate::planner::operator::insert::InsertOperator;
 use crate::planner::operator::join::JoinCondition;
 use crate::planner::operator::truncate::TruncateOperator;
@@ -37,6 +45,7 @@ pub enum Operator {
     Aggregate(AggregateOperator),
     Filter(FilterOperator),
     Join(JoinOperator),
+    Apply(ApplyOperator),
     Project(ProjectOperator),
     Scan(ScanOperator),
     Sort(SortOperator),
@@ -50,6 +59,9 @@ pub enum Operator {
     CreateTable(CreateTableOperator),
     DropTable(DropTableOperator),
     Truncate(TruncateOperator),
+    // Utility
+    Analyze(AnalyzeOperator),
+    Explain(ExplainOperator),
 }
 
 impl Operator {
@@ -114,6 +126,22 @@ impl Operator {
 
                 exprs
             }
+            Operator::Apply(op) => {
+                let mut exprs = op.correlated_columns.clone();
+
+                if let JoinCondition::On { on, filter } = &op.on {
+                    for (left_expr, right_expr) in on {
+                        exprs.append(&mut left_expr.referenced_columns());
+                        exprs.append(&mut right_expr.referenced_columns());
+                    }
+
+                    if let Some(filter_expr) = filter {
+                        exprs.append(&mut filter_expr.referenced_columns());
+                    }
+                }
+
+                exprs
+            }
             Operator::Project(op) => {
                 op.columns
                     .iter()
@@ -143,3 +171,116 @@ impl Operator {
         }
     }
 }
+
+/// A single line of `EXPLAIN`, the children are formatted by `LogicalPlan::explain`.
+impl fmt::Display for Operator {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Operator::Dummy => write!(f, "Dummy"),
+            Operator::Aggregate(op) => {
+                write!(f, "Aggregate [{}]", op.agg_calls.iter().join(", "))?;
+                if !op.groupby_exprs.is_empty() {
+                    write!(f, " group by [{}]", op.groupby_exprs.iter().join(", "))?;
+                }
+                Ok(())
+            }
+            Operator::Filter(op) => {
+                let name = if op.having { "Having" } else { "Filter" };
+
+                write!(f, "{} {}", name, op.predicate)
+            }
+            Operator::Join(op) => {
+                write!(f, "{:?} Join", op.join_type)?;
+                fmt_join_condition(f, &op.on)
+            }
+            Operator::Apply(op) => {
+                write!(f, "{:?} Apply", op.join_type)?;
+                if !op.correlated_columns.is_empty() {
+                    let columns = op.correlated_columns
+                        .iter()
+                        .map(|col| ScalarExpression::ColumnRef(col.clone()))
+                        .join(", ");
+
+                    write!(f, " correlated [{}]", columns)?;
+                }
+                fmt_join_condition(f, &op.on)
+            }
+            Operator::Project(op) => write!(f, "Project [{}]", op.columns.iter().join(", ")),
+            Operator::Scan(op) => {
+                write!(f, "Scan {} [{}]", op.table_name, op.columns.iter().join(", "))?;
+                if !op.pre_where.is_empty() {
+                    write!(f, " where [{}]", op.pre_where.iter().join(", "))?;
+                }
+                if !op.sort_fields.is_empty() {
+                    write!(f, " order by [{}]", op.sort_fields.iter().join(", "))?;
+                }
+                let (offset, limit) = op.limit;
+                if let Some(limit) = limit {
+                    write!(f, " limit {}", limit)?;
+                }
+                if let Some(offset) = offset {
+                    write!(f, " offset {}", offset)?;
+                }
+                Ok(())
+            }
+            Operator::Sort(op) => {
+                write!(f, "Sort [{}]", op.sort_fields.iter().join(", "))?;
+                if let Some(limit) = op.limit {
+                    write!(f, " limit {}", limit)?;
+                }
+                Ok(())
+            }
+            Operator::Limit(op) => write!(f, "Limit {} offset {}", op.limit, op.offset),
+            Operator::Values(op) => {
+                let columns = op.columns
+                    .iter()
+                    .map(|col| ScalarExpression::ColumnRef(col.clone()))
+                    .join(", ");
+
+                write!(f, "Values [{}], {} rows", columns, op.rows.len())
+            }
+            Operator::Insert(op) => {
+                let name = if op.is_overwrite { "Insert Overwrite" } else { "Insert" };
+
+                write!(f, "{} {}", name, op.table_name)
+            }
+            Operator::Update(op) => write!(f, "Update {}", op.table_name),
+            Operator::Delete(op) => write!(f, "Delete {}", op.table_name),
+            Operator::CreateTable(op) => {
+                write!(f, "Create Table {} [{}]", op.table_name, op.columns.iter().map(|col| &col.name).join(", "))
+            }
+            Operator::DropTable(op) => write!(f, "Drop Table {}", op.table_name),
+            Operator::Truncate(op) => write!(f, "Truncate {}", op.table_name),
+            Operator::Analyze(op) => {
+                write!(f, "Analyze {} [{}]", op.table_name, op.columns.iter().map(|col| &col.name).join(", "))
+            }
+            Operator::Explain(op) => {
+                write!(f, "Explain")?;
+                if op.analyze {
+                    write!(f, " Analyze")?;
+                }
+                if op.verbose {
+                    write!(f, " Verbose")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+fn fmt_join_condition(f: &mut Formatter, on: &JoinCondition) -> fmt::Result {
+    if let JoinCondition::On { on, filter } = on {
+        if !on.is_empty() {
+            let keys = on
+                .iter()
+                .map(|(left_expr, right_expr)| format!("{} = {}", left_expr, right_expr))
+                .join(", ");
+
+            write!(f, " on [{}]", keys)?;
+        }
+        if let Some(expr) = filter {
+            write!(f, " filter {}", expr)?;
+        }
+    }
+    Ok(())
+}
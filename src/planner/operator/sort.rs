@@ -1,6 +1,8 @@
+use std::fmt;
+use std::fmt::Formatter;
 use crate::expression::ScalarExpression;
 
-#[derive(Debug, PartialEq, Clone)]
+#[derive(Debug, PartialEq, Eq, Clone, Hash)]
 pub struct SortField {
     pub expr: ScalarExpression,
     pub desc: bool,
@@ -17,6 +19,19 @@ impl SortField {
     }
 }
 
+impl fmt::Display for SortField {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.expr)?;
+        if self.desc {
+            write!(f, " DESC")?;
+        }
+        if self.nulls_first {
+            write!(f, " NULLS FIRST")?;
+        }
+        Ok(())
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct SortOperator {
     pub sort_fields: Vec<SortField>,
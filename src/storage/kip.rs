@@ -9,8 +9,9 @@ use kip_db::kernel::lsm::iterator::Iter;
 use kip_db::kernel::lsm::storage::Config;
 use kip_db::kernel::Storage as Kip_Storage;
 use kip_db::kernel::utils::lru_cache::ShardingLruCache;
-use crate::catalog::{ColumnCatalog, TableCatalog, TableName};
-use crate::storage::{Bounds, Projections, Storage, StorageError, Table, Transaction};
+use itertools::Itertools;
+use crate::catalog::{CatalogError, ColumnCatalog, STATISTICS_TABLE_NAME, statistics_row_id, TableCatalog, TableName, TableStatistics};
+use crate::storage::{Bounds, is_satisfied, KeyRange, Predicates, Projections, Storage, StorageError, Table, Transaction};
 use crate::storage::table_codec::TableCodec;
 use crate::types::tuple::{Tuple, TupleId};
 
@@ -55,6 +56,13 @@ impl Storage for KipStorage {
     }
 
     async fn drop_table(&self, name: &String) -> Result<(), StorageError> {
+        let column_names = self.table_catalog(name)
+            .await
+            .map(|catalog| catalog.all_columns().iter().map(|col| col.name.clone()).collect_vec())
+            .unwrap_or_default();
+        let statistics_codec = self.table_catalog(&STATISTICS_TABLE_NAME.to_string())
+            .await
+            .map(|catalog| TableCodec { table: catalog.clone() });
         self.drop_data(name).await?;
 
         let (min, max) = TableCodec::columns_bound(name);
@@ -72,6 +80,19 @@ impl Storage for KipStorage {
         for col_key in col_keys {
             tx.remove(&col_key)?
         }
+        if tx.get(&TableCodec::statistics_key(name))?.is_some() {
+            tx.remove(&TableCodec::statistics_key(name))?;
+        }
+        // the rows of the analyzed columns in the system table
+        if let Some(statistics_codec) = statistics_codec {
+            for column_name in column_names {
+                let key = statistics_codec.encode_tuple_key(&statistics_row_id(name, &column_name))?;
+
+                if tx.get(&key)?.is_some() {
+                    tx.remove(&key)?;
+                }
+            }
+        }
         tx.commit().await?;
 
         let _ = self.cache.remove(name);
@@ -132,13 +153,37 @@ impl Storage for KipStorage {
                 }
             }
 
-            if let Some(catalog) = name_option.and_then(|table_name| TableCatalog::new(table_name, columns).ok()) {
+            let statistics = tx.get(&TableCodec::statistics_key(name)).ok()
+                .flatten()
+                .and_then(|bytes| TableCodec::decode_statistics(&bytes));
+            drop(iter);
+
+            if let Some(mut catalog) = name_option.and_then(|table_name| TableCatalog::new(table_name, columns).ok()) {
+                if let Some(statistics) = statistics {
+                    catalog.set_statistics(statistics);
+                }
                 option = self.cache.get_or_insert(name.to_string(), |_| Ok(catalog)).ok();
             }
         }
 
         option
     }
+
+    async fn save_statistics(&self, name: &String, statistics: TableStatistics) -> Result<(), StorageError> {
+        let mut catalog = self.table_catalog(name)
+            .await
+            .cloned()
+            .ok_or_else(|| CatalogError::NotFound("table", name.to_string()))?;
+
+        catalog.set_statistics(statistics);
+        self.cache.put(name.to_string(), catalog);
+
+        Ok(())
+    }
+
+    fn is_primary_key_ordered(&self) -> bool {
+        true
+    }
 }
 
 pub struct KipTable {
@@ -150,14 +195,31 @@ pub struct KipTable {
 impl Table for KipTable {
     type TransactionType<'a> = KipTraction<'a>;
 
-    fn read(&self, bounds: Bounds, projections: Projections) -> Result<Self::TransactionType<'_>, StorageError> {
+    fn read(&self, bounds: Bounds, projections: Projections, predicates: Predicates) -> Result<Self::TransactionType<'_>, StorageError> {
+        let mut transaction = self.read_range((Bound::Unbounded, Bound::Unbounded), projections, predicates)?;
+        transaction.offset = bounds.0.unwrap_or(0);
+        transaction.limit = bounds.1;
+
+        Ok(transaction)
+    }
+
+    fn read_range(&self, (lower, upper): KeyRange, projections: Projections, predicates: Predicates) -> Result<Self::TransactionType<'_>, StorageError> {
         let (min, max) = self.table_codec.tuple_bound();
-        let iter = self.tx.iter(Bound::Included(&min), Bound::Included(&max))?;
+        let encode = |bound: Bound<TupleId>, unbounded: Vec<u8>| -> Result<Bound<Vec<u8>>, StorageError> {
+            Ok(match bound {
+                Bound::Included(tuple_id) => Bound::Included(self.table_codec.encode_tuple_key(&tuple_id)?),
+                Bound::Excluded(tuple_id) => Bound::Excluded(self.table_codec.encode_tuple_key(&tuple_id)?),
+                Bound::Unbounded => Bound::Included(unbounded),
+            })
+        };
+        let (min, max) = (encode(lower, min)?, encode(upper, max)?);
+        let iter = self.tx.iter(as_slice_bound(&min), as_slice_bound(&max))?;
 
         Ok(KipTraction {
-            offset: bounds.0.unwrap_or(0),
-            limit: bounds.1,
+            offset: 0,
+            limit: None,
             projections,
+            predicates,
             table_codec: &self.table_codec,
             iter,
         })
@@ -181,6 +243,13 @@ impl Table for KipTable {
         Ok(())
     }
 
+    fn save_statistics(&mut self, name: &str, statistics: &TableStatistics) -> Result<(), StorageError> {
+        let (key, value) = TableCodec::encode_statistics(name, statistics)?;
+        self.tx.set(key, value);
+
+        Ok(())
+    }
+
     async fn commit(self) -> Result<(), StorageError> {
         self.tx.commit().await?;
 
@@ -188,21 +257,25 @@ impl Table for KipTable {
     }
 }
 
+fn as_slice_bound(bound: &Bound<Vec<u8>>) -> Bound<&[u8]> {
+    match bound {
+        Bound::Included(key) => Bound::Included(key),
+        Bound::Excluded(key) => Bound::Excluded(key),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
 pub struct KipTraction<'a> {
     offset: usize,
     limit: Option<usize>,
     projections: Projections,
+    predicates: Predicates,
     table_codec: &'a TableCodec,
     iter: TransactionIter<'a>
 }
 
 impl Transaction for KipTraction<'_> {
     fn next_tuple(&mut self) -> Result<Option<Tuple>, StorageError> {
-        while self.offset > 0 {
-            let _ = self.iter.try_next()?;
-            self.offset -= 1;
-        }
-
         if let Some(num) = self.limit {
             if num == 0 {
                 return Ok(None);
@@ -213,6 +286,14 @@ impl Transaction for KipTraction<'_> {
             if let (_, Some(value)) = item {
                 let tuple = self.table_codec.decode_tuple(&value);
 
+                if !is_satisfied(&self.predicates, &tuple)? {
+                    continue;
+                }
+                if self.offset > 0 {
+                    self.offset -= 1;
+                    continue;
+                }
+
                 let projection_len = self.projections.len();
 
                 let mut columns = Vec::with_capacity(projection_len);
@@ -281,7 +362,8 @@ mod test {
 
         let mut tx = table.read(
             (Some(1), Some(1)),
-            vec![ScalarExpression::InputRef { index: 0, ty: LogicalType::Integer }]
+            vec![ScalarExpression::InputRef { index: 0, ty: LogicalType::Integer }],
+            vec![]
         )?;
 
         let option_1 = tx.next_tuple()?;
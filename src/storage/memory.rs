@@ -1,10 +1,12 @@
 use std::cell::Cell;
+use std::collections::Bound;
+use std::ops::RangeBounds;
 use std::fmt::{Debug, Formatter};
 use std::slice;
 use std::sync::Arc;
 use async_trait::async_trait;
-use crate::catalog::{ColumnCatalog, RootCatalog, TableCatalog, TableName};
-use crate::storage::{Bounds, Projections, Storage, StorageError, Table, Transaction};
+use crate::catalog::{CatalogError, ColumnCatalog, RootCatalog, STATISTICS_TABLE_NAME, statistics_row_id, TableCatalog, TableName, TableStatistics};
+use crate::storage::{Bounds, is_satisfied, KeyRange, Predicates, Projections, Storage, StorageError, Table, Transaction};
 use crate::types::tuple::{Tuple, TupleId};
 
 // WARRING: Only single-threaded and tested using
@@ -66,6 +68,16 @@ impl Storage for MemStorage {
     }
 
     async fn drop_table(&self, name: &String) -> Result<(), StorageError> {
+        let column_names = self.table_catalog(name)
+            .await
+            .map(|catalog| catalog.all_columns().iter().map(|col| col.name.clone()).collect::<Vec<_>>())
+            .unwrap_or_default();
+
+        if let Some(mut statistics_table) = self.table(&STATISTICS_TABLE_NAME.to_string()).await {
+            for column_name in column_names {
+                statistics_table.delete(statistics_row_id(name, &column_name))?;
+            }
+        }
         let inner = unsafe {
             self.inner
                 .as_ptr()
@@ -114,6 +126,27 @@ impl Storage for MemStorage {
                 .get_table(name)
         }
     }
+
+    async fn save_statistics(&self, name: &String, statistics: TableStatistics) -> Result<(), StorageError> {
+        let inner = unsafe { self.inner.as_ptr().as_mut() }.unwrap();
+
+        inner.root
+            .get_table_mut(name)
+            .ok_or_else(|| CatalogError::NotFound("table", name.to_string()))?
+            .set_statistics(statistics);
+
+        Ok(())
+    }
+
+    fn supports_parallel_scan(&self) -> bool {
+        false
+    }
+
+    async fn table_row_count(&self, name: &String) -> Option<usize> {
+        let table = self.table(name).await?;
+
+        unsafe { table.tuples.as_ptr().as_ref() }.map(Vec::len)
+    }
 }
 
 unsafe impl Send for MemTable {
@@ -143,19 +176,28 @@ impl Debug for MemTable {
 impl Table for MemTable {
     type TransactionType<'a> = MemTraction<'a>;
 
-    fn read(&self, bounds: Bounds, projection: Projections) -> Result<Self::TransactionType<'_>, StorageError> {
+    fn read(&self, bounds: Bounds, projection: Projections, predicates: Predicates) -> Result<Self::TransactionType<'_>, StorageError> {
         unsafe {
             Ok(
                 MemTraction {
                     offset: bounds.0.unwrap_or(0),
                     limit: bounds.1,
+                    range: (Bound::Unbounded, Bound::Unbounded),
                     projections: projection,
+                    predicates,
                     iter: self.tuples.as_ptr().as_ref().unwrap().iter(),
                 }
             )
         }
     }
 
+    fn read_range(&self, range: KeyRange, projection: Projections, predicates: Predicates) -> Result<Self::TransactionType<'_>, StorageError> {
+        let mut transaction = self.read((None, None), projection, predicates)?;
+        transaction.range = range;
+
+        Ok(transaction)
+    }
+
     fn append(&mut self, tuple: Tuple, is_overwrite: bool) -> Result<(), StorageError> {
         let tuples = unsafe {
             self.tuples
@@ -195,46 +237,51 @@ impl Table for MemTable {
 pub struct MemTraction<'a> {
     offset: usize,
     limit: Option<usize>,
+    range: KeyRange,
     projections: Projections,
+    predicates: Predicates,
     iter: slice::Iter<'a, Tuple>
 }
 
 impl Transaction for MemTraction<'_> {
     fn next_tuple(&mut self) -> Result<Option<Tuple>, StorageError> {
-        while self.offset > 0 {
-            let _ = self.iter.next();
-            self.offset -= 1;
-        }
-
         if let Some(num) = self.limit {
             if num == 0 {
                 return Ok(None);
             }
         }
 
-        self.iter
-            .next()
-            .cloned()
-            .map(|tuple| {
-                let projection_len = self.projections.len();
+        for tuple in self.iter.by_ref() {
+            if !tuple.id.as_ref().map_or(true, |id| self.range.contains(id)) {
+                continue;
+            }
+            if !is_satisfied(&self.predicates, tuple)? {
+                continue;
+            }
+            if self.offset > 0 {
+                self.offset -= 1;
+                continue;
+            }
+            let projection_len = self.projections.len();
 
-                let mut columns = Vec::with_capacity(projection_len);
-                let mut values = Vec::with_capacity(projection_len);
+            let mut columns = Vec::with_capacity(projection_len);
+            let mut values = Vec::with_capacity(projection_len);
 
-                for expr in self.projections.iter() {
-                    values.push(expr.eval_column(&tuple)?);
-                    columns.push(expr.output_columns(&tuple));
-                }
+            for expr in self.projections.iter() {
+                values.push(expr.eval_column(tuple)?);
+                columns.push(expr.output_columns(tuple));
+            }
+
+            self.limit = self.limit.map(|num| num - 1);
 
-                self.limit = self.limit.map(|num| num - 1);
+            return Ok(Some(Tuple {
+                id: tuple.id.clone(),
+                columns,
+                values,
+            }));
+        }
 
-                Ok(Tuple {
-                    id: tuple.id,
-                    columns,
-                    values,
-                })
-            })
-            .transpose()
+        Ok(None)
     }
 }
 
@@ -302,7 +349,8 @@ pub(crate) mod test {
 
         let mut tx = table.read(
             (Some(1), Some(1)),
-            vec![ScalarExpression::InputRef { index: 0, ty: LogicalType::Integer }]
+            vec![ScalarExpression::InputRef { index: 0, ty: LogicalType::Integer }],
+            vec![]
         )?;
 
         let option_1 = tx.next_tuple()?;
@@ -311,6 +359,16 @@ pub(crate) mod test {
         let option_2 = tx.next_tuple()?;
         assert_eq!(option_2, None);
 
+        // where c2
+        let mut tx = table.read(
+            (None, None),
+            vec![ScalarExpression::InputRef { index: 0, ty: LogicalType::Integer }],
+            vec![ScalarExpression::InputRef { index: 1, ty: LogicalType::Boolean }]
+        )?;
+
+        assert_eq!(tx.next_tuple()?.unwrap().id, Some(Arc::new(DataValue::Int32(Some(1)))));
+        assert_eq!(tx.next_tuple()?, None);
+
         Ok(())
     }
 }
\ No newline at end of file
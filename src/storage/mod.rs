@@ -2,13 +2,16 @@ pub mod memory;
 mod table_codec;
 pub mod kip;
 
+use std::collections::Bound;
 use async_trait::async_trait;
+use itertools::Itertools;
 use kip_db::error::CacheError;
 use kip_db::KernelError;
-use crate::catalog::{CatalogError, ColumnCatalog, TableCatalog, TableName};
+use crate::catalog::{CatalogError, ColumnCatalog, TableCatalog, TableName, TableStatistics};
 use crate::expression::ScalarExpression;
 use crate::types::errors::TypeError;
 use crate::types::tuple::{Tuple, TupleId};
+use crate::types::value::DataValue;
 
 #[async_trait]
 pub trait Storage: Sync + Send + Clone + 'static {
@@ -25,11 +28,84 @@ pub trait Storage: Sync + Send + Clone + 'static {
 
     async fn table(&self, name: &String) -> Option<Self::TableType>;
     async fn table_catalog(&self, name: &String) -> Option<&TableCatalog>;
+
+    /// Replace the statistics of the table collected by `ANALYZE` in its catalog, once they are
+    /// written by `Table::save_statistics` and committed.
+    async fn save_statistics(&self, name: &String, statistics: TableStatistics) -> Result<(), StorageError>;
+
+    /// Number of rows of the table if it is known without a scan, used to estimate the costs.
+    async fn table_row_count(&self, name: &String) -> Option<usize> {
+        self.table_catalog(name)
+            .await?
+            .statistics()
+            .map(|statistics| statistics.row_count)
+    }
+
+    /// Whether `Table::read` returns the tuples in ascending order of the primary key.
+    fn is_primary_key_ordered(&self) -> bool {
+        false
+    }
+
+    /// Whether the tables may be read from several threads at once, for the parallel scans.
+    fn supports_parallel_scan(&self) -> bool {
+        true
+    }
+
+    /// Disjoint ranges of the primary keys of the table in ascending order, at most `count` of
+    /// them, which cover all of its tuples. They are split at the bounds of the buckets of the
+    /// histogram of the primary key, so a table without statistics is a single range.
+    async fn table_ranges(&self, name: &String, count: usize) -> Vec<KeyRange> {
+        match self.table_catalog(name).await {
+            Some(catalog) => histogram_ranges(catalog, count),
+            None => vec![(Bound::Unbounded, Bound::Unbounded)],
+        }
+    }
+}
+
+/// Optional bounds of the primary keys, of the form (lower, upper).
+pub type KeyRange = (Bound<TupleId>, Bound<TupleId>);
+
+fn histogram_ranges(catalog: &TableCatalog, count: usize) -> Vec<KeyRange> {
+    let buckets = catalog.all_columns_with_id()
+        .into_iter()
+        .find(|(_, col)| col.desc.is_primary)
+        .and_then(|(id, _)| catalog.statistics()?.column(id))
+        .map(|statistics| statistics.histogram.buckets())
+        .unwrap_or_default();
+    let mut split_keys = (1..count)
+        .map(|i| i * buckets.len() / count)
+        .filter(|pos| *pos > 0)
+        .map(|pos| buckets[pos - 1].upper.clone())
+        .collect_vec();
+    split_keys.dedup();
+
+    let mut ranges = Vec::with_capacity(split_keys.len() + 1);
+    let mut lower = Bound::Unbounded;
+
+    for key in split_keys {
+        ranges.push((lower, Bound::Included(key.clone())));
+        lower = Bound::Excluded(key);
+    }
+    ranges.push((lower, Bound::Unbounded));
+
+    ranges
 }
 
 /// Optional bounds of the reader, of the form (offset, limit).
 pub(crate) type Bounds = (Option<usize>, Option<usize>);
 type Projections = Vec<ScalarExpression>;
+type Predicates = Vec<ScalarExpression>;
+
+/// Whether the tuple satisfies all the predicates, `NULL` is not satisfied.
+fn is_satisfied(predicates: &[ScalarExpression], tuple: &Tuple) -> Result<bool, TypeError> {
+    for predicate in predicates {
+        if !matches!(predicate.eval_column(tuple)?.as_ref(), DataValue::Boolean(Some(true))) {
+            return Ok(false);
+        }
+    }
+
+    Ok(true)
+}
 
 #[async_trait]
 pub trait Table: Sync + Send + 'static {
@@ -38,16 +114,34 @@ pub trait Table: Sync + Send + 'static {
     /// The bounds is applied to the whole data batches, not per batch.
     ///
     /// The projections is column indices.
+    ///
+    /// The predicates are evaluated on the decoded tuples before the projections, only the
+    /// tuples satisfying all of them are returned and counted by the bounds.
     fn read(
         &self,
         bounds: Bounds,
         projection: Projections,
+        predicates: Predicates,
+    ) -> Result<Self::TransactionType<'_>, StorageError>;
+
+    /// Like `read` without bounds, but only the tuples whose primary keys are in the range.
+    fn read_range(
+        &self,
+        range: KeyRange,
+        projection: Projections,
+        predicates: Predicates,
     ) -> Result<Self::TransactionType<'_>, StorageError>;
 
     fn append(&mut self, tuple: Tuple, is_overwrite: bool) -> Result<(), StorageError>;
 
     fn delete(&mut self, tuple_id: TupleId) -> Result<(), StorageError>;
 
+    /// Write the statistics of the table `name` in the transaction, nothing for the storages
+    /// whose catalogs are only in memory.
+    fn save_statistics(&mut self, _name: &str, _statistics: &TableStatistics) -> Result<(), StorageError> {
+        Ok(())
+    }
+
     async fn commit(self) -> Result<(), StorageError>;
 }
 
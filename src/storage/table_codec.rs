@@ -1,6 +1,6 @@
 use std::sync::Arc;
 use bytes::Bytes;
-use crate::catalog::{ColumnCatalog, ColumnRef, TableCatalog, TableName};
+use crate::catalog::{ColumnCatalog, ColumnRef, TableCatalog, TableName, TableStatistics};
 use crate::types::errors::TypeError;
 use crate::types::tuple::{Tuple, TupleId};
 
@@ -87,11 +87,27 @@ impl TableCodec {
             })
     }
 
+    /// Key: TableName_Statistics_0
+    /// Value: TableStatistics
+    pub fn statistics_key(name: &str) -> Vec<u8> {
+        format!("{}_Statistics_{}", name, BOUND_MIN_TAG).into_bytes()
+    }
+
+    pub fn encode_statistics(name: &str, statistics: &TableStatistics) -> Result<(Bytes, Bytes), TypeError> {
+        let bytes = bincode::serialize(statistics)
+            .map_err(|err| TypeError::InternalError(format!("encode statistics of {}: {}", name, err)))?;
+
+        Ok((Bytes::from(Self::statistics_key(name)), Bytes::from(bytes)))
+    }
+
+    pub fn decode_statistics(bytes: &[u8]) -> Option<TableStatistics> {
+        bincode::deserialize(bytes).ok()
+    }
+
     pub fn decode_column(key: &[u8], bytes: &[u8]) -> Option<(TableName, ColumnCatalog)> {
         String::from_utf8(key.to_owned()).ok()?
-            .split("_")
-            .nth(0)
-            .and_then(|table_name| {
+            .split_once("_Catalog_")
+            .and_then(|(table_name, _)| {
                 bincode::deserialize::<ColumnCatalog>(bytes).ok()
                     .and_then(|col| {
                         Some((Arc::new(table_name.to_string()), col))
@@ -176,6 +192,24 @@ mod tests {
         assert_eq!(table_name, table_catalog.name);
     }
 
+    #[test]
+    fn test_table_codec_column_with_underscore() {
+        let columns = vec![
+            ColumnCatalog::new(
+                "c_1".into(),
+                false,
+                ColumnDesc::new(LogicalType::Integer, true)
+            )
+        ];
+        let table_catalog = TableCatalog::new(Arc::new("t_1".to_string()), columns).unwrap();
+        let col = table_catalog.all_columns()[0].clone();
+        let (key, bytes) = TableCodec::encode_column(&col).unwrap();
+        let (table_name, decode_col) = TableCodec::decode_column(&key, &bytes).unwrap();
+
+        assert_eq!(&decode_col, col.as_ref());
+        assert_eq!(table_name.as_str(), "t_1");
+    }
+
     #[test]
     fn test_table_codec_column_bound() {
         let mut set = BTreeSet::new();
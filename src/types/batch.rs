@@ -0,0 +1,599 @@
+use std::iter;
+use std::mem;
+use std::sync::Arc;
+use itertools::Itertools;
+use crate::catalog::ColumnRef;
+use crate::expression::column_position;
+use crate::types::errors::TypeError;
+use crate::types::LogicalType;
+use crate::types::tuple::Tuple;
+use crate::types::value::DataValue;
+
+/// Validity of the values of a vector, a set bit for a value which isn't NULL.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Bitmap {
+    bits: Vec<u64>,
+    len: usize,
+}
+
+impl Bitmap {
+    pub fn new(len: usize, is_set: bool) -> Self {
+        let mut bits = vec![if is_set { u64::MAX } else { 0 }; (len + 63) / 64];
+
+        if let (Some(last), true) = (bits.last_mut(), len % 64 != 0) {
+            *last &= (1 << (len % 64)) - 1;
+        }
+        Bitmap { bits, len }
+    }
+
+    pub fn with_capacity(capacity: usize) -> Self {
+        Bitmap {
+            bits: Vec::with_capacity((capacity + 63) / 64),
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn get(&self, i: usize) -> bool {
+        self.bits[i / 64] & (1 << (i % 64)) != 0
+    }
+
+    pub fn set(&mut self, i: usize, is_set: bool) {
+        if is_set {
+            self.bits[i / 64] |= 1 << (i % 64);
+        } else {
+            self.bits[i / 64] &= !(1 << (i % 64));
+        }
+    }
+
+    pub fn push(&mut self, is_set: bool) {
+        if self.len % 64 == 0 {
+            self.bits.push(0);
+        }
+        self.len += 1;
+        self.set(self.len - 1, is_set);
+    }
+
+    pub fn count_set(&self) -> usize {
+        self.bits.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub fn and(&self, other: &Bitmap) -> Bitmap {
+        Bitmap {
+            bits: self.bits.iter().zip(other.bits.iter()).map(|(a, b)| a & b).collect(),
+            len: self.len,
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = bool> + '_ {
+        (0..self.len).map(|i| self.get(i))
+    }
+}
+
+impl FromIterator<bool> for Bitmap {
+    fn from_iter<T: IntoIterator<Item = bool>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let mut bitmap = Bitmap::with_capacity(iter.size_hint().0);
+
+        for is_set in iter {
+            bitmap.push(is_set);
+        }
+        bitmap
+    }
+}
+
+/// The values of a vector, with one variant for each variant of `DataValue`. The slots of NULLs
+/// hold the default value of the type.
+#[derive(Debug, Clone, PartialEq)]
+pub enum VectorData {
+    Boolean(Vec<bool>),
+    Float32(Vec<f32>),
+    Float64(Vec<f64>),
+    Int8(Vec<i8>),
+    Int16(Vec<i16>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    UInt8(Vec<u8>),
+    UInt16(Vec<u16>),
+    UInt32(Vec<u32>),
+    UInt64(Vec<u64>),
+    Utf8(Vec<String>),
+    /// The unscaled values, the precision and scale are the ones of the type of the vector
+    Decimal(Vec<i128>),
+    Date32(Vec<i32>),
+    Date64(Vec<i64>),
+}
+
+/// Evaluates `$body` with the values of any variant bound to `$values`.
+macro_rules! with_vector_data {
+    ($data:expr, $values:ident => $body:expr) => {
+        match $data {
+            VectorData::Boolean($values) => $body,
+            VectorData::Float32($values) => $body,
+            VectorData::Float64($values) => $body,
+            VectorData::Int8($values) => $body,
+            VectorData::Int16($values) => $body,
+            VectorData::Int32($values) => $body,
+            VectorData::Int64($values) => $body,
+            VectorData::UInt8($values) => $body,
+            VectorData::UInt16($values) => $body,
+            VectorData::UInt32($values) => $body,
+            VectorData::UInt64($values) => $body,
+            VectorData::Utf8($values) => $body,
+            VectorData::Decimal($values) => $body,
+            VectorData::Date32($values) => $body,
+            VectorData::Date64($values) => $body,
+        }
+    };
+}
+
+/// Like `with_vector_data`, but `$body` gives the values of a new data of the same variant.
+macro_rules! map_vector_data {
+    ($data:expr, $values:ident => $body:expr) => {
+        match $data {
+            VectorData::Boolean($values) => VectorData::Boolean($body),
+            VectorData::Float32($values) => VectorData::Float32($body),
+            VectorData::Float64($values) => VectorData::Float64($body),
+            VectorData::Int8($values) => VectorData::Int8($body),
+            VectorData::Int16($values) => VectorData::Int16($body),
+            VectorData::Int32($values) => VectorData::Int32($body),
+            VectorData::Int64($values) => VectorData::Int64($body),
+            VectorData::UInt8($values) => VectorData::UInt8($body),
+            VectorData::UInt16($values) => VectorData::UInt16($body),
+            VectorData::UInt32($values) => VectorData::UInt32($body),
+            VectorData::UInt64($values) => VectorData::UInt64($body),
+            VectorData::Utf8($values) => VectorData::Utf8($body),
+            VectorData::Decimal($values) => VectorData::Decimal($body),
+            VectorData::Date32($values) => VectorData::Date32($body),
+            VectorData::Date64($values) => VectorData::Date64($body),
+        }
+    };
+}
+
+macro_rules! impl_vector_data {
+    ($($variant:ident),*) => {
+        impl VectorData {
+            /// Push a value of the variant of the data, returns whether it isn't NULL.
+            fn push(&mut self, value: &DataValue) -> bool {
+                match (self, value) {
+                    $((VectorData::$variant(values), DataValue::$variant(value)) => {
+                        values.push(value.clone().unwrap_or_default());
+                        value.is_some()
+                    })*
+                    (VectorData::Decimal(values), DataValue::Decimal(value, ..)) => {
+                        values.push(value.unwrap_or_default());
+                        value.is_some()
+                    }
+                    (data, value) => {
+                        data.push_null();
+                        assert!(value.is_null(), "{:?} pushed into the data of another type", value);
+                        false
+                    }
+                }
+            }
+
+            fn value(&self, i: usize, ty: &LogicalType) -> DataValue {
+                match (self, ty) {
+                    $((VectorData::$variant(values), _) => DataValue::$variant(Some(values[i].clone())),)*
+                    (VectorData::Decimal(values), LogicalType::Decimal(precision, scale)) => {
+                        DataValue::Decimal(Some(values[i]), *precision, *scale)
+                    }
+                    (data, ty) => unreachable!("{:?} of the type {}", data, ty),
+                }
+            }
+
+            /// Append the values of data of the same variant.
+            fn extend(&mut self, other: &VectorData) {
+                match (self, other) {
+                    $((VectorData::$variant(values), VectorData::$variant(others)) => {
+                        values.extend(others.iter().cloned())
+                    })*
+                    (VectorData::Decimal(values), VectorData::Decimal(others)) => values.extend(others),
+                    (data, other) => unreachable!("{:?} extended with {:?}", data, other),
+                }
+            }
+        }
+    };
+}
+
+impl_vector_data!(
+    Boolean, Float32, Float64, Int8, Int16, Int32, Int64, UInt8, UInt16, UInt32, UInt64, Utf8, Date32, Date64
+);
+
+impl VectorData {
+    /// The values of `SqlNull` are stored as booleans, they are all NULL.
+    fn with_capacity(ty: &LogicalType, capacity: usize) -> Self {
+        match ty {
+            LogicalType::Invalid | LogicalType::SqlNull | LogicalType::Boolean => VectorData::Boolean(Vec::with_capacity(capacity)),
+            LogicalType::Tinyint => VectorData::Int8(Vec::with_capacity(capacity)),
+            LogicalType::UTinyint => VectorData::UInt8(Vec::with_capacity(capacity)),
+            LogicalType::Smallint => VectorData::Int16(Vec::with_capacity(capacity)),
+            LogicalType::USmallint => VectorData::UInt16(Vec::with_capacity(capacity)),
+            LogicalType::Integer => VectorData::Int32(Vec::with_capacity(capacity)),
+            LogicalType::UInteger => VectorData::UInt32(Vec::with_capacity(capacity)),
+            LogicalType::Bigint => VectorData::Int64(Vec::with_capacity(capacity)),
+            LogicalType::UBigint => VectorData::UInt64(Vec::with_capacity(capacity)),
+            LogicalType::Float => VectorData::Float32(Vec::with_capacity(capacity)),
+            LogicalType::Double => VectorData::Float64(Vec::with_capacity(capacity)),
+            LogicalType::Decimal(..) => VectorData::Decimal(Vec::with_capacity(capacity)),
+            LogicalType::Varchar => VectorData::Utf8(Vec::with_capacity(capacity)),
+            LogicalType::Date => VectorData::Date32(Vec::with_capacity(capacity)),
+            LogicalType::DateTime => VectorData::Date64(Vec::with_capacity(capacity)),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        with_vector_data!(self, values => values.len())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn push_null(&mut self) {
+        with_vector_data!(self, values => values.push(Default::default()))
+    }
+}
+
+/// The values of a column of a batch, all of the same type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColumnVector {
+    ty: LogicalType,
+    data: VectorData,
+    validity: Bitmap,
+}
+
+pub type VectorRef = Arc<ColumnVector>;
+
+impl ColumnVector {
+    pub fn new(ty: LogicalType, data: VectorData, validity: Bitmap) -> Self {
+        assert_eq!(data.len(), validity.len());
+
+        ColumnVector { ty, data, validity }
+    }
+
+    /// The values of another type are cast to `ty`.
+    pub fn from_values(ty: LogicalType, values: impl IntoIterator<Item = DataValue>) -> Result<Self, TypeError> {
+        let values = values.into_iter();
+        let mut data = VectorData::with_capacity(&ty, values.size_hint().0);
+        let mut validity = Bitmap::with_capacity(values.size_hint().0);
+
+        for value in values {
+            let is_valid = if value.is_null() {
+                data.push_null();
+                false
+            } else if value.logical_type() == ty {
+                data.push(&value)
+            } else {
+                data.push(&value.cast(&ty)?)
+            };
+            validity.push(is_valid);
+        }
+
+        Ok(ColumnVector { ty, data, validity })
+    }
+
+    /// The type is the one of the first value which isn't NULL, or `ty` when they are all NULL.
+    pub fn infer_from_values(values: Vec<DataValue>, ty: LogicalType) -> Result<Self, TypeError> {
+        let ty = values
+            .iter()
+            .find(|value| !value.is_null())
+            .map_or(ty, DataValue::logical_type);
+
+        Self::from_values(ty, values)
+    }
+
+    pub fn from_constant(value: &DataValue, len: usize) -> Result<Self, TypeError> {
+        Self::from_values(value.logical_type(), iter::repeat(value).take(len).cloned())
+    }
+
+    pub fn new_null(ty: LogicalType, len: usize) -> Self {
+        let mut data = VectorData::with_capacity(&ty, len);
+
+        for _ in 0..len {
+            data.push_null();
+        }
+        ColumnVector { ty, data, validity: Bitmap::new(len, false) }
+    }
+
+    pub fn len(&self) -> usize {
+        self.validity.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn logical_type(&self) -> &LogicalType {
+        &self.ty
+    }
+
+    pub fn data(&self) -> &VectorData {
+        &self.data
+    }
+
+    pub fn validity(&self) -> &Bitmap {
+        &self.validity
+    }
+
+    pub fn is_valid(&self, i: usize) -> bool {
+        self.validity.get(i)
+    }
+
+    pub fn value(&self, i: usize) -> DataValue {
+        if self.is_valid(i) {
+            self.data.value(i, &self.ty)
+        } else {
+            DataValue::none(&self.ty)
+        }
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = DataValue> + '_ {
+        (0..self.len()).map(|i| self.value(i))
+    }
+
+    /// Keep the values whose flag in `mask` is true.
+    pub fn filter(&self, mask: &[bool]) -> Self {
+        let is_selected = |i: &usize| mask[*i];
+
+        ColumnVector {
+            ty: self.ty,
+            data: map_vector_data!(&self.data, values => values
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| is_selected(i))
+                .map(|(_, value)| value)
+                .cloned()
+                .collect()),
+            validity: (0..self.len()).filter(is_selected).map(|i| self.is_valid(i)).collect(),
+        }
+    }
+
+    /// The values at `indices`, in their order.
+    pub fn take(&self, indices: &[usize]) -> Self {
+        ColumnVector {
+            ty: self.ty,
+            data: map_vector_data!(&self.data, values => indices.iter().map(|i| &values[*i]).cloned().collect()),
+            validity: indices.iter().map(|i| self.is_valid(*i)).collect(),
+        }
+    }
+
+    pub fn concat(vectors: &[&ColumnVector]) -> Result<Self, TypeError> {
+        let ty = vectors
+            .iter()
+            .map(|vector| vector.ty)
+            .find(|ty| ty != &LogicalType::SqlNull)
+            .unwrap_or(LogicalType::SqlNull);
+
+        if vectors.iter().any(|vector| vector.ty != ty) {
+            return Self::from_values(ty, vectors.iter().flat_map(|vector| vector.values()));
+        }
+        let len = vectors.iter().map(|vector| vector.len()).sum();
+        let mut data = VectorData::with_capacity(&ty, len);
+        let mut validity = Bitmap::with_capacity(len);
+
+        for vector in vectors {
+            data.extend(&vector.data);
+            vector.validity.iter().for_each(|is_valid| validity.push(is_valid));
+        }
+
+        Ok(ColumnVector { ty, data, validity })
+    }
+
+    /// Approximate number of bytes the vector holds in memory.
+    pub fn memory_size(&self) -> usize {
+        let strings_len = match &self.data {
+            VectorData::Utf8(values) => values.iter().map(String::len).sum(),
+            _ => 0,
+        };
+
+        mem::size_of::<Self>()
+            + with_vector_data!(&self.data, values => mem::size_of_val(values.as_slice()))
+            + strings_len
+            + self.validity.bits.len() * mem::size_of::<u64>()
+    }
+}
+
+/// Rows stored column by column, what the vectorized executors work on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordBatch {
+    pub columns: Vec<ColumnRef>,
+    pub vectors: Vec<VectorRef>,
+    len: usize,
+}
+
+impl RecordBatch {
+    pub fn new(columns: Vec<ColumnRef>, vectors: Vec<VectorRef>, len: usize) -> Self {
+        assert!(vectors.iter().all(|vector| vector.len() == len));
+
+        RecordBatch { columns, vectors, len }
+    }
+
+    /// The tuples have the same columns, the type of a column is inferred from its values.
+    pub fn from_tuples(tuples: &[Tuple]) -> Result<Self, TypeError> {
+        let Some(first) = tuples.first() else {
+            return Ok(RecordBatch { columns: vec![], vectors: vec![], len: 0 });
+        };
+        let vectors = first.columns
+            .iter()
+            .enumerate()
+            .map(|(i, col)| {
+                let values = tuples.iter().map(|tuple| DataValue::clone(&tuple.values[i])).collect();
+
+                ColumnVector::infer_from_values(values, *col.datatype()).map(Arc::new)
+            })
+            .try_collect()?;
+
+        Ok(RecordBatch { columns: first.columns.clone(), vectors, len: tuples.len() })
+    }
+
+    /// The id of a tuple is the value of its primary key, as for the tuples read from a table.
+    pub fn to_tuples(&self) -> Vec<Tuple> {
+        (0..self.len).map(|i| self.row(i)).collect()
+    }
+
+    pub fn row(&self, i: usize) -> Tuple {
+        let values = self.vectors
+            .iter()
+            .map(|vector| Arc::new(vector.value(i)))
+            .collect_vec();
+        let id = self.columns
+            .iter()
+            .position(|col| col.desc.is_primary)
+            .map(|pos| values[pos].clone());
+
+        Tuple { id, columns: self.columns.clone(), values }
+    }
+
+    /// A tuple of the columns without values, for what only needs the columns of the rows,
+    /// e.g. `ScalarExpression::output_columns`.
+    pub fn header(&self) -> Tuple {
+        Tuple { id: None, columns: self.columns.clone(), values: vec![] }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// The position of the column, resolved like `ScalarExpression::eval_column`.
+    pub fn column_index(&self, col: &ColumnRef) -> Result<usize, TypeError> {
+        column_position(&self.columns, col)
+    }
+
+    pub fn filter(&self, mask: &[bool]) -> Self {
+        RecordBatch {
+            columns: self.columns.clone(),
+            vectors: self.vectors.iter().map(|vector| Arc::new(vector.filter(mask))).collect(),
+            len: mask.iter().filter(|is_selected| **is_selected).count(),
+        }
+    }
+
+    pub fn take(&self, indices: &[usize]) -> Self {
+        RecordBatch {
+            columns: self.columns.clone(),
+            vectors: self.vectors.iter().map(|vector| Arc::new(vector.take(indices))).collect(),
+            len: indices.len(),
+        }
+    }
+
+    /// The rows of all the batches, which have the same columns.
+    pub fn concat(batches: &[RecordBatch]) -> Result<Self, TypeError> {
+        let Some(first) = batches.first() else {
+            return Ok(RecordBatch { columns: vec![], vectors: vec![], len: 0 });
+        };
+        let vectors = (0..first.vectors.len())
+            .map(|i| {
+                let vectors = batches.iter().map(|batch| batch.vectors[i].as_ref()).collect_vec();
+
+                ColumnVector::concat(&vectors).map(Arc::new)
+            })
+            .try_collect()?;
+
+        Ok(RecordBatch {
+            columns: first.columns.clone(),
+            vectors,
+            len: batches.iter().map(RecordBatch::len).sum(),
+        })
+    }
+
+    /// The columns of both batches side by side, they have the same number of rows.
+    pub fn merge(left: RecordBatch, right: RecordBatch) -> Self {
+        assert_eq!(left.len, right.len);
+
+        RecordBatch {
+            columns: left.columns.into_iter().chain(right.columns).collect(),
+            vectors: left.vectors.into_iter().chain(right.vectors).collect(),
+            len: left.len,
+        }
+    }
+
+    /// Approximate number of bytes the batch holds in memory, the columns are shared.
+    pub fn memory_size(&self) -> usize {
+        mem::size_of::<Self>() + self.vectors.iter().map(|vector| vector.memory_size()).sum::<usize>()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+    use crate::catalog::{ColumnCatalog, ColumnDesc};
+    use crate::types::batch::{Bitmap, ColumnVector, RecordBatch, VectorData};
+    use crate::types::errors::TypeError;
+    use crate::types::LogicalType;
+    use crate::types::tuple::Tuple;
+    use crate::types::value::DataValue;
+
+    #[test]
+    fn test_bitmap() {
+        let mut bitmap = Bitmap::new(70, true);
+        assert_eq!(bitmap.count_set(), 70);
+
+        bitmap.set(3, false);
+        bitmap.push(false);
+        bitmap.push(true);
+        assert_eq!(bitmap.len(), 72);
+        assert_eq!(bitmap.count_set(), 70);
+        assert!(!bitmap.get(3) && !bitmap.get(70) && bitmap.get(71));
+
+        let other = (0..72).map(|i| i % 2 == 0).collect::<Bitmap>();
+        assert_eq!(bitmap.and(&other).count_set(), 35);
+    }
+
+    #[test]
+    fn test_batch_from_tuples() -> Result<(), TypeError> {
+        let columns = vec![
+            Arc::new(ColumnCatalog::new("c1".to_string(), false, ColumnDesc::new(LogicalType::Integer, true))),
+            Arc::new(ColumnCatalog::new("c2".to_string(), true, ColumnDesc::new(LogicalType::Varchar, false))),
+        ];
+        let tuples = (0..3)
+            .map(|i| Tuple {
+                id: Some(Arc::new(DataValue::Int32(Some(i)))),
+                columns: columns.clone(),
+                values: vec![
+                    Arc::new(DataValue::Int32(Some(i))),
+                    Arc::new(DataValue::Utf8((i != 1).then(|| i.to_string()))),
+                ],
+            })
+            .collect::<Vec<_>>();
+
+        let batch = RecordBatch::from_tuples(&tuples)?;
+        assert_eq!(batch.len(), 3);
+        assert_eq!(batch.vectors[0].data(), &VectorData::Int32(vec![0, 1, 2]));
+        assert_eq!(batch.vectors[1].validity().iter().collect::<Vec<_>>(), vec![true, false, true]);
+        assert_eq!(batch.to_tuples(), tuples);
+
+        let filtered = batch.filter(&[true, false, true]);
+        assert_eq!(filtered.to_tuples(), vec![tuples[0].clone(), tuples[2].clone()]);
+        assert_eq!(batch.take(&[1, 1]).to_tuples(), vec![tuples[1].clone(), tuples[1].clone()]);
+        assert_eq!(RecordBatch::concat(&[filtered, batch.take(&[1])])?.vectors[1].values().collect::<Vec<_>>(), vec![
+            DataValue::Utf8(Some("0".to_string())),
+            DataValue::Utf8(Some("2".to_string())),
+            DataValue::Utf8(None),
+        ]);
+
+        // values of another type are cast to the type of the vector
+        let vector = ColumnVector::infer_from_values(
+            vec![DataValue::Null, DataValue::Int64(Some(1)), DataValue::Int32(Some(2))],
+            LogicalType::Integer
+        )?;
+        assert_eq!(vector.logical_type(), &LogicalType::Bigint);
+        assert_eq!(vector.values().collect::<Vec<_>>(), vec![
+            DataValue::Int64(None),
+            DataValue::Int64(Some(1)),
+            DataValue::Int64(Some(2)),
+        ]);
+
+        Ok(())
+    }
+}
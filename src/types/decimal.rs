@@ -0,0 +1,271 @@
+use std::cmp::Ordering;
+use crate::expression::BinaryOperator;
+use crate::types::errors::TypeError;
+
+/// The decimals are stored as their unscaled value with the precision and scale of their type,
+/// e.g. 12.30 of `DECIMAL(10, 2)` is 1230. 38 digits always fit an i128.
+pub const MAX_PRECISION: u8 = 38;
+
+/// The precision of `DECIMAL` without one.
+pub const DEFAULT_PRECISION: u8 = 10;
+
+/// The digits the result of a division has after the ones of the dividend.
+pub const DIVIDE_SCALE_INCREMENT: u8 = 4;
+
+fn pow10(exp: u8) -> Result<i128, TypeError> {
+    10_i128.checked_pow(exp as u32).ok_or(TypeError::Overflow)
+}
+
+/// Rounded half away from zero, e.g. 2.5 is 3 and -2.5 is -3.
+pub fn div_round(dividend: i128, divisor: i128) -> Result<i128, TypeError> {
+    if divisor == 0 {
+        return Err(TypeError::DivisionByZero);
+    }
+    let quotient = dividend.checked_div(divisor).ok_or(TypeError::Overflow)?;
+    let remainder = dividend % divisor;
+
+    if remainder.unsigned_abs() >= divisor.unsigned_abs() - remainder.unsigned_abs() {
+        Ok(quotient + dividend.signum() * divisor.signum())
+    } else {
+        Ok(quotient)
+    }
+}
+
+/// `v1 / 10^s1 / (v2 / 10^s2)` with `scale` digits after the point, rounded like `div_round`.
+/// The digits of the quotient are computed one at a time, so only the quotient has to fit an
+/// i128, not the dividend scaled to the digits of the quotient.
+pub fn divide(v1: i128, s1: u8, v2: i128, s2: u8, scale: u8) -> Result<i128, TypeError> {
+    if v2 == 0 {
+        return Err(TypeError::DivisionByZero);
+    }
+    // the quotient of the unscaled values has `s1 - s2` digits after the point
+    let shift = scale as i32 + s2 as i32 - s1 as i32;
+    if shift <= 0 {
+        return div_round(rescale(v1, s1, scale + s2)?, v2);
+    }
+    let divisor = v2.unsigned_abs();
+    let mut quotient = v1.unsigned_abs() / divisor;
+    let mut remainder = v1.unsigned_abs() % divisor;
+
+    for _ in 0..shift {
+        // `10 * remainder` may not fit, it is summed up modulo the divisor
+        let mut digit = 0;
+        let mut next_remainder = 0;
+        for _ in 0..10 {
+            next_remainder += remainder;
+            if next_remainder >= divisor {
+                next_remainder -= divisor;
+                digit += 1;
+            }
+        }
+        quotient = quotient
+            .checked_mul(10)
+            .and_then(|quotient| quotient.checked_add(digit))
+            .ok_or(TypeError::Overflow)?;
+        remainder = next_remainder;
+    }
+    if remainder >= divisor - remainder {
+        quotient = quotient.checked_add(1).ok_or(TypeError::Overflow)?;
+    }
+    let quotient = i128::try_from(quotient).map_err(|_| TypeError::Overflow)?;
+
+    Ok(if (v1 < 0) != (v2 < 0) { -quotient } else { quotient })
+}
+
+/// The value with `to` digits after the point instead of `from`, rounded when they are fewer.
+pub fn rescale(value: i128, from: u8, to: u8) -> Result<i128, TypeError> {
+    match to.cmp(&from) {
+        Ordering::Equal => Ok(value),
+        Ordering::Greater => value.checked_mul(pow10(to - from)?).ok_or(TypeError::Overflow),
+        Ordering::Less => div_round(value, pow10(from - to)?),
+    }
+}
+
+/// Fails when the value has more digits than the precision.
+pub fn check_precision(value: i128, precision: u8) -> Result<i128, TypeError> {
+    if value.unsigned_abs() >= pow10(precision)?.unsigned_abs() {
+        return Err(TypeError::Overflow);
+    }
+    Ok(value)
+}
+
+/// Compares the values of different scales.
+pub fn compare(v1: i128, s1: u8, v2: i128, s2: u8) -> Ordering {
+    let scale = s1.max(s2);
+
+    match (rescale(v1, s1, scale), rescale(v2, s2, scale)) {
+        (Ok(v1), Ok(v2)) => v1.cmp(&v2),
+        // too many digits for an i128, the difference is large enough for doubles
+        _ => to_f64(v1, s1).total_cmp(&to_f64(v2, s2)),
+    }
+}
+
+/// Without the trailing zeros after the point, so the equal values of different scales are the same.
+pub fn normalize(mut value: i128, mut scale: u8) -> (i128, u8) {
+    while scale > 0 && value % 10 == 0 {
+        value /= 10;
+        scale -= 1;
+    }
+    (value, scale)
+}
+
+/// e.g. `-12.5`, the digits after `scale` are rounded.
+pub fn parse(str: &str, precision: u8, scale: u8) -> Result<i128, TypeError> {
+    let str = str.trim();
+    let (is_negative, str) = match str.as_bytes().first() {
+        Some(b'-') => (true, &str[1..]),
+        Some(b'+') => (false, &str[1..]),
+        _ => (false, str),
+    };
+    let (integer, fraction) = str.split_once('.').unwrap_or((str, ""));
+
+    if integer.is_empty() && fraction.is_empty()
+        || !integer.bytes().chain(fraction.bytes()).all(|byte| byte.is_ascii_digit())
+    {
+        return Err(TypeError::CastFail);
+    }
+    let digits = integer
+        .bytes()
+        .chain(fraction.bytes().chain(std::iter::repeat(b'0')).take(scale as usize));
+    let mut value = 0_i128;
+
+    for digit in digits {
+        value = value
+            .checked_mul(10)
+            .and_then(|value| value.checked_add((digit - b'0') as i128))
+            .ok_or(TypeError::Overflow)?;
+    }
+    if fraction.as_bytes().get(scale as usize).map_or(false, |digit| *digit >= b'5') {
+        value += 1;
+    }
+
+    check_precision(if is_negative { -value } else { value }, precision)
+}
+
+/// The precision and scale of the digits of a number with a point, `None` when it is not one or
+/// has too many digits.
+pub fn parse_literal(str: &str) -> Option<(i128, u8, u8)> {
+    let (integer, fraction) = str.trim_start_matches('-').split_once('.')?;
+    let scale = u8::try_from(fraction.len()).ok()?;
+    let precision = integer.trim_start_matches('0').len() + fraction.len();
+
+    if precision > MAX_PRECISION as usize {
+        return None;
+    }
+    let precision = (precision as u8).max(1);
+
+    parse(str, precision, scale)
+        .ok()
+        .map(|value| (value, precision, scale))
+}
+
+pub fn format(value: i128, scale: u8) -> String {
+    let digits = format!("{:0width$}", value.unsigned_abs(), width = scale as usize + 1);
+    let (integer, fraction) = digits.split_at(digits.len() - scale as usize);
+    let sign = if value < 0 { "-" } else { "" };
+
+    if fraction.is_empty() {
+        format!("{}{}", sign, integer)
+    } else {
+        format!("{}{}.{}", sign, integer, fraction)
+    }
+}
+
+pub fn to_f64(value: i128, scale: u8) -> f64 {
+    value as f64 / 10_f64.powi(scale as i32)
+}
+
+/// The precision and scale of the result of an arithmetic operator, at most `MAX_PRECISION`
+/// digits:
+/// - `+`, `-` and `%` keep the larger scale and have a digit more than the larger integer part
+/// - `*` adds the scales and the precisions
+/// - `/` has `DIVIDE_SCALE_INCREMENT` digits more than the scale of the dividend
+pub fn arithmetic_type(
+    op: &BinaryOperator,
+    (p1, s1): (u8, u8),
+    (p2, s2): (u8, u8),
+) -> (u8, u8) {
+    let (p1, s1, p2, s2) = (p1 as u32, s1 as u32, p2 as u32, s2 as u32);
+    let (precision, scale) = match op {
+        BinaryOperator::Multiply => (p1 + p2, s1 + s2),
+        BinaryOperator::Divide => {
+            let scale = s1 + DIVIDE_SCALE_INCREMENT as u32;
+
+            (p1 - s1 + s2 + scale, scale)
+        }
+        _ => {
+            let scale = s1.max(s2);
+
+            ((p1 - s1).max(p2 - s2) + scale + 1, scale)
+        }
+    };
+    let scale = scale.min(MAX_PRECISION as u32);
+
+    (precision.max(scale).clamp(1, MAX_PRECISION as u32) as u8, scale as u8)
+}
+
+/// The type both sides are compared as, with the larger integer part and scale.
+pub fn common_type((p1, s1): (u8, u8), (p2, s2): (u8, u8)) -> (u8, u8) {
+    let scale = s1.max(s2);
+    let precision = ((p1 - s1).max(p2 - s2) + scale).min(MAX_PRECISION);
+
+    (precision.max(scale), scale)
+}
+
+#[cfg(test)]
+mod test {
+    use std::cmp::Ordering;
+    use crate::types::decimal::{compare, divide, format, parse, parse_literal, rescale};
+    use crate::types::errors::TypeError;
+
+    #[test]
+    fn test_parse_and_format() -> Result<(), TypeError> {
+        assert_eq!(parse("12.3", 10, 2)?, 1230);
+        assert_eq!(parse("-0.125", 10, 2)?, -13);
+        assert_eq!(parse("+.5", 10, 0)?, 1);
+        assert_eq!(parse("7", 3, 2)?, 700);
+        assert!(matches!(parse("10", 3, 2), Err(TypeError::Overflow)));
+        assert!(matches!(parse("1.2.3", 10, 2), Err(TypeError::CastFail)));
+        assert!(matches!(parse("NaN", 10, 2), Err(TypeError::CastFail)));
+
+        assert_eq!(parse_literal("019.990"), Some((19990, 5, 3)));
+        assert_eq!(parse_literal("0.5"), Some((5, 1, 1)));
+        assert_eq!(parse_literal("-0.5"), Some((-5, 1, 1)));
+        assert_eq!(parse_literal("12"), None);
+
+        assert_eq!(format(1230, 2), "12.30");
+        assert_eq!(format(-5, 3), "-0.005");
+        assert_eq!(format(42, 0), "42");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rescale_and_compare() -> Result<(), TypeError> {
+        assert_eq!(rescale(125, 2, 1)?, 13);
+        assert_eq!(rescale(-125, 2, 1)?, -13);
+        assert_eq!(rescale(124, 2, 1)?, 12);
+        assert_eq!(rescale(12, 1, 3)?, 1200);
+
+        assert_eq!(compare(150, 2, 15, 1), Ordering::Equal);
+        assert_eq!(compare(-1, 0, 1, 38), Ordering::Less);
+        assert_eq!(compare(i128::MAX / 10, 0, 1, 38), Ordering::Greater);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_divide() -> Result<(), TypeError> {
+        // 10.00 / 3 and -2 / 3.00, rounded
+        assert_eq!(divide(1000, 2, 3, 0, 6)?, 3333333);
+        assert_eq!(divide(-2, 0, 300, 2, 4)?, -6667);
+        // 1.0 / 1.0 of `DECIMAL(38, 20)`, the dividend would have 44 digits after the point
+        assert_eq!(divide(10_i128.pow(20), 20, 10_i128.pow(20), 20, 24)?, 10_i128.pow(24));
+        // 0.99..., `10 * remainder` does not fit an u128
+        assert_eq!(divide(10_i128.pow(38) - 2, 0, 10_i128.pow(38) - 1, 0, 1)?, 10);
+        assert!(matches!(divide(i128::MAX, 0, 1, 38, 0), Err(TypeError::Overflow)));
+        assert!(matches!(divide(1, 0, 0, 0, 4), Err(TypeError::DivisionByZero)));
+
+        Ok(())
+    }
+}
@@ -10,10 +10,20 @@ pub enum TypeError {
     NotImplementedSqlparserDataType(String),
     #[error("internal error: {0}")]
     InternalError(String),
+    #[error("column {0} not found")]
+    ColumnNotFound(String),
+    #[error("ambiguous column {0}")]
+    AmbiguousColumn(String),
     #[error("cast fail")]
     CastFail,
     #[error("cannot be Null")]
     NotNull,
+    #[error("numeric value out of range")]
+    Overflow,
+    #[error("division by zero")]
+    DivisionByZero,
+    #[error("unsupported operator: {0}")]
+    UnsupportedOperator(String),
     #[error("try from int")]
     TryFromInt(
         #[source]
@@ -1,12 +1,15 @@
 pub mod errors;
 pub mod value;
 pub mod tuple;
+pub mod batch;
+pub mod decimal;
 
 use std::sync::atomic::AtomicU32;
 use std::sync::atomic::Ordering::{Acquire, Release};
 use serde::{Deserialize, Serialize};
 
 use integer_encoding::FixedInt;
+use sqlparser::ast::ExactNumberInfo;
 use strum_macros::AsRefStr;
 
 use crate::types::errors::TypeError;
@@ -57,6 +60,10 @@ pub enum LogicalType {
     Varchar,
     Date,
     DateTime,
+    /// Exact numbers with the precision and scale, e.g. `DECIMAL(10, 2)`
+    ///
+    /// Tips: appended after the others, as the variants are persisted by their index in the catalog
+    Decimal(u8, u8),
 }
 
 impl LogicalType {
@@ -78,6 +85,7 @@ impl LogicalType {
             LogicalType::Varchar => None,
             LogicalType::Date => Some(4),
             LogicalType::DateTime => Some(8),
+            LogicalType::Decimal(..) => Some(16),
         }
     }
 
@@ -109,6 +117,7 @@ impl LogicalType {
                 | LogicalType::UBigint
                 | LogicalType::Float
                 | LogicalType::Double
+                | LogicalType::Decimal(..)
         )
     }
 
@@ -140,6 +149,20 @@ impl LogicalType {
         )
     }
 
+    /// The precision and scale of the decimals which hold all the values of the type, the
+    /// integers have no digits after the point.
+    pub fn decimal_type(&self) -> Option<(u8, u8)> {
+        match self {
+            LogicalType::Tinyint | LogicalType::UTinyint => Some((3, 0)),
+            LogicalType::Smallint | LogicalType::USmallint => Some((5, 0)),
+            LogicalType::Integer | LogicalType::UInteger => Some((10, 0)),
+            LogicalType::Bigint => Some((19, 0)),
+            LogicalType::UBigint => Some((20, 0)),
+            LogicalType::Decimal(precision, scale) => Some((*precision, *scale)),
+            _ => None,
+        }
+    }
+
     pub fn max_logical_type(
         left: &LogicalType,
         right: &LogicalType,
@@ -178,6 +201,17 @@ impl LogicalType {
         if left == right {
             return Ok(left.clone());
         }
+        // exact unless one of them is not
+        if matches!(left, LogicalType::Decimal(..)) || matches!(right, LogicalType::Decimal(..)) {
+            return Ok(match (left.decimal_type(), right.decimal_type()) {
+                (Some(left), Some(right)) => {
+                    let (precision, scale) = decimal::common_type(left, right);
+
+                    LogicalType::Decimal(precision, scale)
+                }
+                _ => LogicalType::Double,
+            });
+        }
         if left.is_signed_numeric() && right.is_unsigned_numeric() {
             // this method is symmetric
             // arrange it so the left type is smaller
@@ -222,6 +256,7 @@ impl LogicalType {
                     | LogicalType::Bigint
                     | LogicalType::Float
                     | LogicalType::Double
+                    | LogicalType::Decimal(..)
             ),
             LogicalType::UTinyint => matches!(
                 to,
@@ -233,6 +268,7 @@ impl LogicalType {
                     | LogicalType::Bigint
                     | LogicalType::Float
                     | LogicalType::Double
+                    | LogicalType::Decimal(..)
             ),
             LogicalType::Smallint => matches!(
                 to,
@@ -240,6 +276,7 @@ impl LogicalType {
                     | LogicalType::Bigint
                     | LogicalType::Float
                     | LogicalType::Double
+                    | LogicalType::Decimal(..)
             ),
             LogicalType::USmallint => matches!(
                 to,
@@ -249,10 +286,11 @@ impl LogicalType {
                     | LogicalType::Bigint
                     | LogicalType::Float
                     | LogicalType::Double
+                    | LogicalType::Decimal(..)
             ),
             LogicalType::Integer => matches!(
                 to,
-                LogicalType::Bigint | LogicalType::Float | LogicalType::Double
+                LogicalType::Bigint | LogicalType::Float | LogicalType::Double | LogicalType::Decimal(..)
             ),
             LogicalType::UInteger => matches!(
                 to,
@@ -260,11 +298,13 @@ impl LogicalType {
                     | LogicalType::Bigint
                     | LogicalType::Float
                     | LogicalType::Double
+                    | LogicalType::Decimal(..)
             ),
-            LogicalType::Bigint => matches!(to, LogicalType::Float | LogicalType::Double),
-            LogicalType::UBigint => matches!(to, LogicalType::Float | LogicalType::Double),
+            LogicalType::Bigint => matches!(to, LogicalType::Float | LogicalType::Double | LogicalType::Decimal(..)),
+            LogicalType::UBigint => matches!(to, LogicalType::Float | LogicalType::Double | LogicalType::Decimal(..)),
             LogicalType::Float => matches!(to, LogicalType::Double),
             LogicalType::Double => false,
+            LogicalType::Decimal(..) => matches!(to, LogicalType::Double | LogicalType::Decimal(..)),
             LogicalType::Varchar => false,
             LogicalType::Date => matches!(to, LogicalType::DateTime | LogicalType::Varchar),
             LogicalType::DateTime => matches!(to, LogicalType::Date | LogicalType::Varchar),
@@ -285,6 +325,23 @@ impl TryFrom<sqlparser::ast::DataType> for LogicalType {
             | sqlparser::ast::DataType::String => Ok(LogicalType::Varchar),
             sqlparser::ast::DataType::Float(_) => Ok(LogicalType::Float),
             sqlparser::ast::DataType::Double => Ok(LogicalType::Double),
+            sqlparser::ast::DataType::Decimal(info)
+            | sqlparser::ast::DataType::Numeric(info)
+            | sqlparser::ast::DataType::Dec(info) => {
+                let (precision, scale) = match info {
+                    ExactNumberInfo::None => (decimal::DEFAULT_PRECISION as u64, 0),
+                    ExactNumberInfo::Precision(precision) => (precision, 0),
+                    ExactNumberInfo::PrecisionAndScale(precision, scale) => (precision, scale),
+                };
+                if precision == 0 || precision > decimal::MAX_PRECISION as u64 || scale > precision {
+                    return Err(TypeError::InternalError(format!(
+                        "the precision of decimals is 1 to {}, the scale 0 to the precision: ({}, {})",
+                        decimal::MAX_PRECISION, precision, scale
+                    )));
+                }
+
+                Ok(LogicalType::Decimal(precision as u8, scale as u8))
+            }
             sqlparser::ast::DataType::TinyInt(_) => Ok(LogicalType::Tinyint),
             sqlparser::ast::DataType::UnsignedTinyInt(_) => Ok(LogicalType::UTinyint),
             sqlparser::ast::DataType::SmallInt(_) => Ok(LogicalType::Smallint),
@@ -307,15 +364,22 @@ impl TryFrom<sqlparser::ast::DataType> for LogicalType {
 
 impl std::fmt::Display for LogicalType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.as_ref())
+        match self {
+            LogicalType::Decimal(precision, scale) => write!(f, "Decimal({}, {})", precision, scale),
+            _ => write!(f, "{}", self.as_ref()),
+        }
     }
 }
 
 #[cfg(test)]
 mod test {
     use std::sync::atomic::Ordering::Release;
+    use serde::Serialize;
+    use sqlparser::ast::{DataType, ExactNumberInfo};
 
-    use crate::types::{IdGenerator, ID_BUF};
+    use crate::catalog::ColumnDesc;
+    use crate::types::{IdGenerator, LogicalType, ID_BUF};
+    use crate::types::errors::TypeError;
 
     /// Tips: 由于IdGenerator为static全局性质生成的id，因此需要单独测试避免其他测试方法干扰
     #[test]
@@ -338,4 +402,70 @@ mod test {
     fn test_id_generator_reset() {
         ID_BUF.store(0, Release)
     }
+
+    #[test]
+    fn test_decimal_type() -> Result<(), TypeError> {
+        assert_eq!(LogicalType::try_from(DataType::Decimal(ExactNumberInfo::PrecisionAndScale(10, 2)))?, LogicalType::Decimal(10, 2));
+        assert_eq!(LogicalType::try_from(DataType::Numeric(ExactNumberInfo::Precision(5)))?, LogicalType::Decimal(5, 0));
+        assert_eq!(LogicalType::try_from(DataType::Decimal(ExactNumberInfo::None))?, LogicalType::Decimal(10, 0));
+        assert!(LogicalType::try_from(DataType::Decimal(ExactNumberInfo::Precision(39))).is_err());
+        assert!(LogicalType::try_from(DataType::Decimal(ExactNumberInfo::PrecisionAndScale(2, 3))).is_err());
+
+        // integers are widened to hold all their values
+        assert_eq!(LogicalType::max_logical_type(&LogicalType::Decimal(10, 2), &LogicalType::Integer)?, LogicalType::Decimal(12, 2));
+        assert_eq!(LogicalType::max_logical_type(&LogicalType::Decimal(10, 2), &LogicalType::Decimal(5, 4))?, LogicalType::Decimal(12, 4));
+        assert_eq!(LogicalType::max_logical_type(&LogicalType::Decimal(10, 2), &LogicalType::Float)?, LogicalType::Double);
+
+        Ok(())
+    }
+
+    /// The variants before `Decimal` was added, as written by the catalogs already persisted
+    #[allow(dead_code)]
+    #[derive(Serialize)]
+    enum OldLogicalType {
+        Invalid,
+        SqlNull,
+        Boolean,
+        Tinyint,
+        UTinyint,
+        Smallint,
+        USmallint,
+        Integer,
+        UInteger,
+        Bigint,
+        UBigint,
+        Float,
+        Double,
+        Varchar,
+        Date,
+        DateTime,
+    }
+
+    #[derive(Serialize)]
+    struct OldColumnDesc {
+        column_datatype: OldLogicalType,
+        is_primary: bool,
+    }
+
+    #[test]
+    fn test_decode_old_logical_type() {
+        let cases = [
+            (OldLogicalType::Integer, LogicalType::Integer),
+            (OldLogicalType::Double, LogicalType::Double),
+            (OldLogicalType::Varchar, LogicalType::Varchar),
+            (OldLogicalType::Date, LogicalType::Date),
+            (OldLogicalType::DateTime, LogicalType::DateTime),
+        ];
+
+        for (old_ty, ty) in cases {
+            let bytes = bincode::serialize(&OldColumnDesc { column_datatype: old_ty, is_primary: false }).unwrap();
+            let desc = bincode::deserialize::<ColumnDesc>(&bytes).unwrap();
+
+            assert_eq!(desc, ColumnDesc::new(ty, false));
+        }
+
+        let desc = ColumnDesc::new(LogicalType::Decimal(10, 2), true);
+        let bytes = bincode::serialize(&desc).unwrap();
+        assert_eq!(bincode::deserialize::<ColumnDesc>(&bytes).unwrap(), desc);
+    }
 }
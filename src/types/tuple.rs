@@ -1,3 +1,4 @@
+use std::mem;
 use std::sync::Arc;
 use comfy_table::{Cell, Table};
 use integer_encoding::FixedInt;
@@ -56,6 +57,14 @@ impl Tuple {
         }
     }
 
+    /// Approximate number of bytes the tuple holds in memory, the columns are shared.
+    pub fn memory_size(&self) -> usize {
+        mem::size_of::<Tuple>() + self.values
+            .iter()
+            .map(|value| value.memory_size())
+            .sum::<usize>()
+    }
+
     /// e.g.: bits(u8)..|data_0(len for utf8_1)|utf8_0|data_1|
     /// Tips: all len is u32
     pub fn serialize_to(&self) -> Vec<u8> {
@@ -2,6 +2,7 @@ use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Formatter;
 use std::hash::Hash;
+use std::mem;
 use std::str::FromStr;
 use std::sync::Arc;
 use chrono::{NaiveDateTime, Datelike, NaiveDate};
@@ -10,6 +11,7 @@ use integer_encoding::FixedInt;
 use lazy_static::lazy_static;
 
 use ordered_float::OrderedFloat;
+use crate::types::decimal;
 use crate::types::errors::TypeError;
 
 use super::LogicalType;
@@ -40,6 +42,8 @@ pub enum DataValue {
     UInt32(Option<u32>),
     UInt64(Option<u64>),
     Utf8(Option<String>),
+    /// Decimal stored as the unscaled value, with the precision and scale
+    Decimal(Option<i128>, u8, u8),
     /// Date stored as a signed 32bit int days since UNIX epoch 1970-01-01
     Date32(Option<i32>),
     /// Date stored as a signed 64bit int timestamp since UNIX epoch 1970-01-01
@@ -82,6 +86,8 @@ impl PartialEq for DataValue {
             (UInt64(_), _) => false,
             (Utf8(v1), Utf8(v2)) => v1.eq(v2),
             (Utf8(_), _) => false,
+            (Decimal(..), Decimal(..)) => self.partial_cmp(other) == Some(Ordering::Equal),
+            (Decimal(..), _) => false,
             (Null, Null) => true,
             (Null, _) => false,
             (Date32(v1), Date32(v2)) => v1.eq(v2),
@@ -128,6 +134,11 @@ impl PartialOrd for DataValue {
             (UInt64(_), _) => None,
             (Utf8(v1), Utf8(v2)) => v1.partial_cmp(v2),
             (Utf8(_), _) => None,
+            (Decimal(v1, _, s1), Decimal(v2, _, s2)) => match (v1, v2) {
+                (Some(v1), Some(v2)) => Some(decimal::compare(*v1, *s1, *v2, *s2)),
+                (v1, v2) => v1.is_some().partial_cmp(&v2.is_some()),
+            },
+            (Decimal(..), _) => None,
             (Null, Null) => Some(Ordering::Equal),
             (Null, _) => None,
             (Date32(v1), Date32(v2)) => v1.partial_cmp(v2),
@@ -172,6 +183,7 @@ impl Hash for DataValue {
             UInt32(v) => v.hash(state),
             UInt64(v) => v.hash(state),
             Utf8(v) => v.hash(state),
+            Decimal(v, _, scale) => v.map(|v| decimal::normalize(v, *scale)).hash(state),
             Null => 1.hash(state),
             Date32(v) => v.hash(state),
             Date64(v) => v.hash(state),
@@ -187,6 +199,14 @@ impl DataValue {
         }
     }
 
+    /// Approximate number of bytes the value holds in memory.
+    pub fn memory_size(&self) -> usize {
+        mem::size_of::<DataValue>() + match self {
+            DataValue::Utf8(Some(s)) => s.len(),
+            _ => 0,
+        }
+    }
+
     pub fn is_null(&self) -> bool {
         match self {
             DataValue::Null => true,
@@ -202,6 +222,7 @@ impl DataValue {
             DataValue::UInt32(value) => value.is_none(),
             DataValue::UInt64(value) => value.is_none(),
             DataValue::Utf8(value) => value.is_none(),
+            DataValue::Decimal(value, ..) => value.is_none(),
             DataValue::Date32(value) => value.is_none(),
             DataValue::Date64(value) => value.is_none(),
         }
@@ -222,6 +243,7 @@ impl DataValue {
             LogicalType::UBigint => DataValue::UInt64(None),
             LogicalType::Float => DataValue::Float32(None),
             LogicalType::Double => DataValue::Float64(None),
+            LogicalType::Decimal(precision, scale) => DataValue::Decimal(None, *precision, *scale),
             LogicalType::Varchar => DataValue::Utf8(None),
             LogicalType::Date => DataValue::Date32(None),
             LogicalType::DateTime => DataValue::Date64(None)
@@ -243,6 +265,7 @@ impl DataValue {
             LogicalType::UBigint => DataValue::UInt64(Some(0)),
             LogicalType::Float => DataValue::Float32(Some(0.0)),
             LogicalType::Double => DataValue::Float64(Some(0.0)),
+            LogicalType::Decimal(precision, scale) => DataValue::Decimal(Some(0), *precision, *scale),
             LogicalType::Varchar => DataValue::Utf8(Some("".to_string())),
             LogicalType::Date => DataValue::Date32(Some(UNIX_DATETIME.num_days_from_ce())),
             LogicalType::DateTime => DataValue::Date64(Some(UNIX_DATETIME.timestamp()))
@@ -264,6 +287,7 @@ impl DataValue {
             DataValue::UInt32(v) => v.map(|v| v.encode_fixed_vec()),
             DataValue::UInt64(v) => v.map(|v| v.encode_fixed_vec()),
             DataValue::Utf8(v) => v.clone().map(|v| v.into_bytes()),
+            DataValue::Decimal(v, ..) => v.map(|v| v.to_le_bytes().to_vec()),
             DataValue::Date32(v) => v.map(|v| v.encode_fixed_vec()),
             DataValue::Date64(v) => v.map(|v| v.encode_fixed_vec()),
         }.unwrap_or(vec![])
@@ -292,6 +316,11 @@ impl DataValue {
                 buf.copy_from_slice(bytes);
                 f64::from_ne_bytes(buf)
             })),
+            LogicalType::Decimal(precision, scale) => DataValue::Decimal((!bytes.is_empty()).then(|| {
+                let mut buf = [0; 16];
+                buf.copy_from_slice(bytes);
+                i128::from_le_bytes(buf)
+            }), *precision, *scale),
             LogicalType::Varchar => DataValue::Utf8((!bytes.is_empty()).then(|| String::from_utf8(bytes.to_owned()).unwrap())),
             LogicalType::Date => DataValue::Date32((!bytes.is_empty()).then(|| i32::decode_fixed(bytes))),
             LogicalType::DateTime => DataValue::Date64((!bytes.is_empty()).then(|| i64::decode_fixed(bytes))),
@@ -313,6 +342,7 @@ impl DataValue {
             DataValue::UInt32(_) => LogicalType::UInteger,
             DataValue::UInt64(_) => LogicalType::UBigint,
             DataValue::Utf8(_) => LogicalType::Varchar,
+            DataValue::Decimal(_, precision, scale) => LogicalType::Decimal(*precision, *scale),
             DataValue::Date32(_) => LogicalType::Date,
             DataValue::Date64(_) => LogicalType::DateTime,
         }
@@ -328,6 +358,8 @@ impl DataValue {
             DataValue::UInt16(option) => option.map(|v| format!("{:0width$}", v, width = 5)),
             DataValue::UInt32(option) => option.map(|v| format!("{:0width$}", v, width = 10)),
             DataValue::UInt64(option) => option.map(|v| format!("{:0width$}", v, width = 20)),
+            // the values of a column have the same scale
+            DataValue::Decimal(option, ..) => option.map(|v| format!("{:0width$}", signed_to_primary_key!(i128, v), width = 40)),
             DataValue::Utf8(option) => option.clone(),
             _ => return Err(TypeError::InvalidType),
         }.ok_or(TypeError::NotNull)
@@ -350,6 +382,7 @@ impl DataValue {
                     LogicalType::UBigint => Ok(DataValue::UInt64(None)),
                     LogicalType::Float => Ok(DataValue::Float32(None)),
                     LogicalType::Double => Ok(DataValue::Float64(None)),
+                    LogicalType::Decimal(precision, scale) => Ok(DataValue::Decimal(None, *precision, *scale)),
                     LogicalType::Varchar => Ok(DataValue::Utf8(None)),
                     LogicalType::Date => Ok(DataValue::Date32(None)),
                     LogicalType::DateTime => Ok(DataValue::Date64(None)),
@@ -378,6 +411,7 @@ impl DataValue {
                     LogicalType::SqlNull => Ok(DataValue::Null),
                     LogicalType::Float => Ok(DataValue::Float32(value)),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::str_to_decimal(value.map(|v| v.to_string()), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -386,6 +420,7 @@ impl DataValue {
                 match to {
                     LogicalType::SqlNull => Ok(DataValue::Null),
                     LogicalType::Double => Ok(DataValue::Float64(value)),
+                    LogicalType::Decimal(precision, scale) => Self::str_to_decimal(value.map(|v| v.to_string()), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -403,6 +438,7 @@ impl DataValue {
                     LogicalType::Bigint => Ok(DataValue::Int64(value.map(|v| v.into()))),
                     LogicalType::Float => Ok(DataValue::Float32(value.map(|v| v.into()))),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -419,6 +455,7 @@ impl DataValue {
                     LogicalType::Bigint => Ok(DataValue::Int64(value.map(|v| v.into()))),
                     LogicalType::Float => Ok(DataValue::Float32(value.map(|v| v.into()))),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -433,6 +470,7 @@ impl DataValue {
                     LogicalType::Integer => Ok(DataValue::Int32(value.map(|v| v.into()))),
                     LogicalType::Bigint => Ok(DataValue::Int64(value.map(|v| v.into()))),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -445,6 +483,7 @@ impl DataValue {
                     LogicalType::UInteger => Ok(DataValue::UInt32(value.map(|v| u32::try_from(v)).transpose()?)),
                     LogicalType::UBigint => Ok(DataValue::UInt64(value.map(|v| u64::try_from(v)).transpose()?)),
                     LogicalType::Bigint => Ok(DataValue::Int64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -461,6 +500,7 @@ impl DataValue {
                     LogicalType::UBigint => Ok(DataValue::UInt64(value.map(|v| v.into()))),
                     LogicalType::Float => Ok(DataValue::Float32(value.map(|v| v.into()))),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -475,6 +515,7 @@ impl DataValue {
                     LogicalType::UBigint => Ok(DataValue::UInt64(value.map(|v| v.into()))),
                     LogicalType::Float => Ok(DataValue::Float32(value.map(|v| v.into()))),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -486,6 +527,7 @@ impl DataValue {
                     LogicalType::Bigint => Ok(DataValue::Int64(value.map(|v| v.into()))),
                     LogicalType::UBigint => Ok(DataValue::UInt64(value.map(|v| v.into()))),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -494,6 +536,7 @@ impl DataValue {
                 match to {
                     LogicalType::SqlNull => Ok(DataValue::Null),
                     LogicalType::UBigint => Ok(DataValue::UInt64(value.map(|v| v.into()))),
+                    LogicalType::Decimal(precision, scale) => Self::integer_to_decimal(value.map(i128::from), *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| format!("{}", v)))),
                     _ => Err(TypeError::CastFail),
                 }
@@ -513,6 +556,7 @@ impl DataValue {
                     LogicalType::UBigint => Ok(DataValue::UInt64(value.map(|v| u64::from_str(&v)).transpose()?)),
                     LogicalType::Float => Ok(DataValue::Float32(value.map(|v| f32::from_str(&v)).transpose()?)),
                     LogicalType::Double => Ok(DataValue::Float64(value.map(|v| f64::from_str(&v)).transpose()?)),
+                    LogicalType::Decimal(precision, scale) => Self::str_to_decimal(value, *precision, *scale),
                     LogicalType::Varchar => Ok(DataValue::Utf8(value)),
                     LogicalType::Date => {
                         let option = value.map(|v| {
@@ -536,6 +580,35 @@ impl DataValue {
                     }
                 }
             }
+            DataValue::Decimal(value, _, from_scale) => {
+                let to_integer = |value: Option<i128>| value
+                    .map(|v| decimal::rescale(v, from_scale, 0))
+                    .transpose();
+
+                match to {
+                    LogicalType::SqlNull => Ok(DataValue::Null),
+                    LogicalType::Tinyint => Ok(DataValue::Int8(to_integer(value)?.map(i8::try_from).transpose()?)),
+                    LogicalType::UTinyint => Ok(DataValue::UInt8(to_integer(value)?.map(u8::try_from).transpose()?)),
+                    LogicalType::Smallint => Ok(DataValue::Int16(to_integer(value)?.map(i16::try_from).transpose()?)),
+                    LogicalType::USmallint => Ok(DataValue::UInt16(to_integer(value)?.map(u16::try_from).transpose()?)),
+                    LogicalType::Integer => Ok(DataValue::Int32(to_integer(value)?.map(i32::try_from).transpose()?)),
+                    LogicalType::UInteger => Ok(DataValue::UInt32(to_integer(value)?.map(u32::try_from).transpose()?)),
+                    LogicalType::Bigint => Ok(DataValue::Int64(to_integer(value)?.map(i64::try_from).transpose()?)),
+                    LogicalType::UBigint => Ok(DataValue::UInt64(to_integer(value)?.map(u64::try_from).transpose()?)),
+                    LogicalType::Float => Ok(DataValue::Float32(value.map(|v| decimal::format(v, from_scale).parse()).transpose()?)),
+                    LogicalType::Double => Ok(DataValue::Float64(value.map(|v| decimal::format(v, from_scale).parse()).transpose()?)),
+                    LogicalType::Decimal(precision, scale) => {
+                        let option = value.map(|v| {
+                            decimal::rescale(v, from_scale, *scale)
+                                .and_then(|v| decimal::check_precision(v, *precision))
+                        }).transpose()?;
+
+                        Ok(DataValue::Decimal(option, *precision, *scale))
+                    }
+                    LogicalType::Varchar => Ok(DataValue::Utf8(value.map(|v| decimal::format(v, from_scale)))),
+                    _ => Err(TypeError::CastFail),
+                }
+            }
             DataValue::Date32(value) => {
                 match to {
                     LogicalType::SqlNull => Ok(DataValue::Null),
@@ -576,6 +649,24 @@ impl DataValue {
         }
     }
 
+    /// Fails when the value does not fit the precision.
+    fn integer_to_decimal(value: Option<i128>, precision: u8, scale: u8) -> Result<DataValue, TypeError> {
+        let option = value.map(|v| {
+            decimal::rescale(v, 0, scale)
+                .and_then(|v| decimal::check_precision(v, precision))
+        }).transpose()?;
+
+        Ok(DataValue::Decimal(option, precision, scale))
+    }
+
+    fn str_to_decimal(value: Option<String>, precision: u8, scale: u8) -> Result<DataValue, TypeError> {
+        let option = value
+            .map(|v| decimal::parse(&v, precision, scale))
+            .transpose()?;
+
+        Ok(DataValue::Decimal(option, precision, scale))
+    }
+
     fn date_format<'a>(v: i32) -> Option<DelayedFormat<StrftimeItems<'a>>> {
         NaiveDate::from_num_days_from_ce_opt(v)
             .map(|date| date.format(DATE_FMT))
@@ -666,6 +757,7 @@ impl fmt::Display for DataValue {
             DataValue::UInt32(e) => format_option!(f, e)?,
             DataValue::UInt64(e) => format_option!(f, e)?,
             DataValue::Utf8(e) => format_option!(f, e)?,
+            DataValue::Decimal(e, _, scale) => format_option!(f, e.map(|v| decimal::format(v, *scale)))?,
             DataValue::Null => write!(f, "null")?,
             DataValue::Date32(e) => {
                 format_option!(f, e.and_then(|s| DataValue::date_format(s)))?
@@ -694,6 +786,7 @@ impl fmt::Debug for DataValue {
             DataValue::UInt64(_) => write!(f, "UInt64({})", self),
             DataValue::Utf8(None) => write!(f, "Utf8({})", self),
             DataValue::Utf8(Some(_)) => write!(f, "Utf8(\"{}\")", self),
+            DataValue::Decimal(_, precision, scale) => write!(f, "Decimal({}, {}, {})", self, precision, scale),
             DataValue::Null => write!(f, "null"),
             DataValue::Date32(_) => write!(f, "Date32({})", self),
             DataValue::Date64(_) => write!(f, "Date64({})", self),
@@ -744,6 +837,14 @@ mod test {
         assert!(key_i64_1 < key_i64_2);
         assert!(key_i64_2 < key_i64_3);
 
+        let keys_decimal = [-99999, -150, -1, 0, 1, 225, 99999]
+            .into_iter()
+            .map(|v| DataValue::Decimal(Some(v), 5, 2).to_primary_key())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        println!("{:?}", keys_decimal);
+        assert!(keys_decimal.windows(2).all(|keys| keys[0] < keys[1]));
+
         Ok(())
     }
 }